@@ -3,13 +3,31 @@ use std::f32::consts::PI;
 use bevy::prelude::*;
 use bevy_rapier2d::{prelude::Group, geometry::CollisionGroups};
 
+use crate::simple::gameplay::classes::ClassType;
+
 pub const SERVER_STR: &str = "\x1b[94mServer\x1b[0m";
 pub const CLIENT_STR: &str = "\x1b[93mClient:\x1b[0m";
 
+pub const CLASS_MANIFEST_PATH: &str = "classes.manifest"; // Lists every class, relative to the assets folder
+pub const ACTION_BINDINGS_PATH: &str = "bindings.ron"; // Where the client's key bindings are loaded from and saved to
+pub const PLAYER_PROFILE_PATH: &str = "profile.ron"; // Where the client's player profile (used by servers to recognise them) is kept
+pub const TALENT_PROGRESS_PATH: &str = "talent_progress.ron"; // Where the server saves every player's talent progress
+pub const GAMEPAD_STICK_DEADZONE: f32 = 0.2; // Stick inputs shorter than this are ignored
+pub const GAMEPAD_AIM_POINT_DISTANCE: f32 = 300.0; // How far in front of the player the aimed at point is when using a gamepad (e.g. for cursor guided missiles)
+pub const AIM_ASSIST_RANGE: f32 = 400.0; // Max distance to an enemy that aim assist will snap to
+pub const AIM_ASSIST_MAX_ANGLE: f32 = PI / 12.0; // Max angle (radians) between the stick and an enemy that aim assist will snap to
+
 pub const ENEMY_COLOR: Color = Color::rgb(0.25, 0.65, 0.1);
 pub const ENEMY_BASE_SPEED: f32 = 100.0;
 pub const ENEMY_BASE_HEALTH: f32 = 5.0;
 pub const ENEMY_SPAWN_SEPARATION_RADIANS: f32 = PI * 0.25;
+pub const ENEMY_CONTACT_REHIT_INTERVAL: f32 = 0.5; // Seconds between an enemy's contact hits on the same target
+
+pub const MAX_DAMAGE_CHAIN_DEPTH: usize = 16; // How many damage events deep a chain of damage causing damage can get before it is cut off
+pub const MAX_DAMAGE_EVENTS_PER_FRAME: usize = 4096; // The most damage events that will be processed in one frame
+
+pub const PLAYER_DEFAULT_CLASS: ClassType = ClassType::MELEE; // The class players have when they join, until they pick one in the lobby
+pub const TALENT_POINTS_PER_WAVE: u32 = 1; // Talent points given to a player's current class each time a wave is completed
 
 pub const PLAYER_GROUP: Group = Group::GROUP_1; // Layer that players exist on
 
@@ -29,46 +47,66 @@ pub const ENEMY_SENSOR_FILTER: Group = PLAYER_GROUP.union(PLAYER_PROJECTILE_GROU
 
 pub const ENEMY_PROJECTILE_GROUP: Group = Group::GROUP_5;
 pub const PLAYER_PROJECTILE_GROUP: Group = Group::GROUP_6;
+pub const PLAYER_FACTION_FIRST_BIT: u32 = 16; // Groups 17-32 are reserved for per-player factions (used to exclude a player's own abilities in PvP)
+pub const PLAYER_FACTION_COUNT: u32 = 16;
+pub const PLAYER_FACTION_GROUPS: Group = Group::from_bits_truncate(0xFFFF_0000);
+pub const PVP_RESPAWN_DELAY: f32 = 3.0;
+
 pub const PLAYER_PROJECTILE_GROUPS: CollisionGroups = CollisionGroups { memberships: PLAYER_PROJECTILE_GROUP, filters: ENEMY_GROUP };
 
 
-pub const BASE_BULLET_SPEED: f32 = 75.0;
 pub const DEFAULT_LASER_WIDTH: f32 = 4.5f32;
+pub const ZONE_ALPHA: f32 = 0.35; // Zones are drawn see-through so actors inside them stay visible
+pub const BEAM_CHANNEL_TIMEOUT: f32 = 0.25; // Seconds without an aim update from its owner before a beam stops being channeled
+pub const BEAM_REPLICATION_TOLERANCE: f32 = 1.0; // How far a beam's endpoints must move before they are replicated again
 
-pub const DEFAULT_CLASS_BULLET_LIFETIME: f32 = 4.0;
+pub const MELEE_ATTACK_OFFSET: f32 = 20.0; // How far in front of the caster directional melee attacks are centred
 
-pub const MELEE_DASH_SPEED: f32 = 350.0;
-pub const MELEE_DASH_DURATION: f32 = 0.3;
-pub const MELEE_ATTACK_LIFETIME: f32 = 0.25;
+pub const UPGRADE_OFFER_COUNT: usize = 3; // Used when the upgrade collection does not set its own
+pub const UPGRADE_REROLLS_PER_BREAK: u32 = 1;
+
+pub const ABILITY_RATE_LIMIT_WINDOW: f32 = 1.0; // Seconds
+pub const ABILITY_RATE_LIMIT_MAX_EVENTS: u32 = 30; // Ability events a client may send each rate limit window
+pub const ABILITY_COOLDOWN_TOLERANCE: f32 = 0.05; // Leeway given to cooldowns to account for network jitter
+
+pub const AMMO_RELOAD_DURATION: f32 = 1.5; // Seconds
 
-pub const RANGED_BULLET_SPEED: f32 = 175.0;
 pub const RANGED_BULLET_SIZE: f32 = 7.5;
-pub const RANGED_BULLET_LIFETIME: f32 = 4.0;
-pub const RANGED_GRENADE_SPEED: f32 = 75.0;
-pub const RANGED_GRENADE_SIZE: f32 = 7.5;
+pub const BULLET_SPREAD_LIFETIME: f32 = 0.75; // Bullets from a spread (eg. cluster fragments) are short ranged
+pub const BULLET_SPREAD_KNOCKBACK: f32 = 100.0;
 pub const RANGED_GRENADE_EXPLOSION_SIZE: f32 = 50.0;
-pub const RANGED_GRENADE_FUSE_TIME: f32 = 2.5;
 pub const RANGED_GRENADE_EXPLOSION_KNOCKBACK_STRENGTH: f32 = 450.0;
 pub const RANGED_GRENADE_DAMAGE: f32 = 1.0;
-pub const RANGED_SHOTGUN_DISTANCE: f32 = 75.0;
-pub const RANGED_SHOTGUN_KNOCKBACK: f32 = 350.0;
-pub const RANGED_SHOTGUN_SELF_KNOCKBACK_SPEED: f32 = 350.0;
-pub const RANGED_SHOTGUN_SELF_KNOCKBACK_DURATION: f32 = 0.15;
+
+pub const PROJECTILE_RICOCHET_RANGE: f32 = 300.0; // How far a ricocheting projectile will look for its next target
 
 pub const RANGED_BULLET_COLOR: Color = Color::rgb(0.15, 0.5, 0.69);
-pub const RANGED_GRENADE_COLOR: Color = Color::rgb(0.6, 0.6, 0.4);
 
 pub const RANGED_MAX_MISSILE_SPEED: f32 = 1200.0;
-pub const RANGED_MISSILE_INITIAL_SPEED: f32 = 50.0;
 pub const RANGED_MAX_MISSILE_ACCELERATION: f32 = 1000.0;
 pub const RANGED_MAX_MISSILE_ANGULAR_ACCELERATION: f32 = 2.0 * PI; // Radians/s
+pub const MISSILE_NAVIGATION_CONSTANT: f32 = 4.0; // How strongly missiles steer against the rotation of their line of sight to the target
+pub const MISSILE_PURSUIT_GAIN: f32 = 4.0; // How strongly missiles turn towards targets that are far off their heading
+pub const MISSILE_PURSUIT_MIN_ANGLE: f32 = PI * 0.5; // How far off a missile's heading a target must be before it is pursued directly
 pub const RANGED_MISSILE_LIFETIME: f32 = 3.0;
 pub const RANGED_MISSILE_WIDTH: f32 = 5.0;
 pub const RANGED_MISSILE_LENGTH: f32 = 10.0;
-pub const RANGED_MISSILE_DAMAGE: f32 = 1.0;
 pub const RANGED_MISSILE_EXPLOSION_RADIUS: f32 = 15.0;
 pub const RANGED_MISSILE_EXPLOSION_DAMAGE: f32 = 1.5;
 pub const RANGED_MISSILE_EXPLOSION_KNOCKBACK_STRENGTH: f32 = 200.0;
 
 pub const RANGED_MISSILE_COLOR: Color = Color::rgb(0.8, 0.4, 0.4);
 
+pub const RANGED_BOOMERANG_CATCH_RADIUS: f32 = 20.0; // How close a returning boomerang must get to its owner to be caught
+pub const RANGED_BOOMERANG_LIFETIME: f32 = 6.0; // Safety net in case the boomerang can never reach its owner
+pub const RANGED_BOOMERANG_COLOR: Color = Color::rgb(0.75, 0.6, 0.2);
+
+
+pub const SUMMON_SIZE: f32 = 25.0;
+pub const SUMMON_FIRE_INTERVAL: f32 = 0.6; // Seconds between each shot of turrets and drones
+pub const SUMMON_RANGE: f32 = 350.0; // How close an enemy must be for turrets and drones to shoot at it
+pub const SUMMON_BULLET_SPEED: f32 = 250.0;
+pub const SUMMON_DRONE_FOLLOW_DISTANCE: f32 = 60.0; // How far from their summoner drones try to stay
+pub const SUMMON_DRONE_SPEED: f32 = 220.0;
+pub const SUMMON_DECOY_TAUNT_RANGE: f32 = 300.0; // Enemies this close to a decoy chase it instead of players
+pub const SUMMON_COLOR: Color = Color::rgb(0.35, 0.7, 0.45);
@@ -1,7 +1,10 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::simple::gameplay::actor::{SerializedStat, effect::SerializedEffectTrigger};
+use crate::simple::gameplay::{
+    actor::{SerializedStat, effect::SerializedEffectTrigger},
+    classes::{AbilityDefinition, ClassType, ClassUiMetadata},
+};
 
 use super::ron_asset::RonSerializedAsset;
 
@@ -12,6 +15,9 @@ pub struct ClassBaseData
     pub stats: Vec<SerializedStat>,
     pub description: String,
     pub name: String,
+    /// Abilities that are entirely described by data, these are used alongside any abilities the class sets up in code
+    #[serde(default)]
+    pub abilities: Vec<AbilityDefinition>,
 }
 
 impl RonSerializedAsset for ClassBaseData
@@ -20,3 +26,31 @@ impl RonSerializedAsset for ClassBaseData
         &[".cbd"]
     }
 }
+
+/// One class listed in the class manifest
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ClassManifestEntry
+{
+    pub id: ClassType,
+    /// Path of the class's [`ClassBaseData`], relative to the assets folder
+    pub data: String,
+    #[serde(default)]
+    pub ui: ClassUiMetadata,
+    /// Path of the class's [`TalentTree`](super::TalentTree), relative to the assets folder
+    #[serde(default)]
+    pub talents: Option<String>,
+}
+
+/// Lists every class that can be played, adding an entry (and its class data) is all that is needed to add a class
+#[derive(Asset, Clone, Debug, Default, TypePath, Deserialize, Serialize, PartialEq)]
+pub struct ClassManifest
+{
+    pub classes: Vec<ClassManifestEntry>,
+}
+
+impl RonSerializedAsset for ClassManifest
+{
+    fn extensions() -> &'static [&'static str] {
+        &[".manifest"]
+    }
+}
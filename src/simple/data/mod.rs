@@ -4,14 +4,16 @@ mod enemy;
 mod fonts;
 mod images;
 mod ron_asset;
+mod talent;
 mod upgrade;
 mod wave;
 
 pub use bounds::{Bounds, WorldBounds};
-pub use class::ClassBaseData;
+pub use class::{ClassBaseData, ClassManifest};
 pub use enemy::EnemyData;
 pub use fonts::Fonts;
 pub use images::Images;
+pub use talent::TalentTree;
 pub use wave::{WaveData, WaveDataResus};
 pub use upgrade::{StaticUpgrades, UpgradeCollection};
 
@@ -46,6 +48,10 @@ impl Plugin for DataPlugin
             .init_asset_loader::<ron_asset::RonAssetLoader<UpgradeCollection>>()
             .init_asset::<ClassBaseData>()
             .init_asset_loader::<ron_asset::RonAssetLoader<ClassBaseData>>()
+            .init_asset::<ClassManifest>()
+            .init_asset_loader::<ron_asset::RonAssetLoader<ClassManifest>>()
+            .init_asset::<TalentTree>()
+            .init_asset_loader::<ron_asset::RonAssetLoader<TalentTree>>()
             .add_systems(Startup, (fonts::cs_setup_fonts, images::setup_images));
     }
 }
@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::ron_asset::RonSerializedAsset;
+
+use crate::simple::gameplay::TalentNode;
+
+/// Every talent a class can unlock, the path to a class's tree is given in the class manifest
+#[derive(Asset, Clone, Debug, Default, TypePath, Deserialize, Serialize, PartialEq)]
+pub struct TalentTree
+{
+    pub nodes: Vec<TalentNode>,
+}
+
+impl TalentTree
+{
+    pub fn node(&self, id: &str) -> Option<&TalentNode>
+    {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+}
+
+impl RonSerializedAsset for TalentTree
+{
+    fn extensions() -> &'static [&'static str] {
+        &[".talents"]
+    }
+}
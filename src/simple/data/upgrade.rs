@@ -3,12 +3,43 @@ use serde::{Deserialize, Serialize};
 
 use super::ron_asset::RonSerializedAsset;
 
-use crate::simple::gameplay::Upgrade;
+use crate::simple::{gameplay::Upgrade, consts::{UPGRADE_OFFER_COUNT, UPGRADE_REROLLS_PER_BREAK}};
 
-#[derive(Asset, Clone, Default, Deserialize, Serialize, Reflect)]
+#[derive(Asset, Clone, Deserialize, Serialize, Reflect)]
 pub struct UpgradeCollection
 {
     pub upgrades: Vec<Upgrade>,
+    /// How many upgrades each player is offered to choose from
+    #[serde(default = "UpgradeCollection::default_offer_count")]
+    pub offer_count: usize,
+    /// How many times each player may reroll their offered upgrades every break
+    #[serde(default = "UpgradeCollection::default_rerolls_per_break")]
+    pub rerolls_per_break: u32,
+}
+
+impl UpgradeCollection
+{
+    fn default_offer_count() -> usize
+    {
+        UPGRADE_OFFER_COUNT
+    }
+
+    fn default_rerolls_per_break() -> u32
+    {
+        UPGRADE_REROLLS_PER_BREAK
+    }
+}
+
+impl Default for UpgradeCollection
+{
+    fn default() -> Self {
+        Self
+        {
+            upgrades: Vec::new(),
+            offer_count: Self::default_offer_count(),
+            rerolls_per_break: Self::default_rerolls_per_break(),
+        }
+    }
 }
 
 impl RonSerializedAsset for UpgradeCollection
@@ -1,11 +1,17 @@
-use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
 use serde::{Serialize, Deserialize};
 
-use crate::simple::gameplay::Position;
+use crate::simple::{gameplay::Position, consts::{SERVER_STR, MAX_DAMAGE_CHAIN_DEPTH, MAX_DAMAGE_EVENTS_PER_FRAME}};
 
 use super::{
+    ActorChild,
     ActorContext,
+    responsible_actor,
+    summoner_of,
     effect_application::*,
+    shield,
     stats::Stat,
 };
 
@@ -18,7 +24,7 @@ pub enum DamageSource
 }
 
 /// Event to send to trigger damage events in an easy way
-#[derive(Clone, Copy, Debug, Event)]
+#[derive(Clone, Copy, Debug, PartialEq, Event)]
 pub struct DamageEvent
 {
     pub instigator: Entity,
@@ -45,7 +51,16 @@ pub struct Damage
     pub destroy_on_damage: bool,
     pub deal_damage_once: bool,
     pub knockback: Option<DamageKnockback>,
-    pub did_damage: bool,
+    /// How long after hitting a victim this object may hit that same victim again, None means only ever hit each victim once
+    pub rehit_interval: Option<f32>,
+    /// The maximum number of hits this object can land before it stops dealing damage (and is destroyed if destroy_on_damage is set), None means no limit
+    pub max_hits: Option<u32>,
+    /// Victims this object has hit, mapped to the time remaining until they can be hit again
+    #[serde(skip)]
+    pub hits: HashMap<Entity, f32>,
+    /// Total number of hits this object has landed
+    #[serde(skip)]
+    pub hit_count: u32,
 }
 
 impl Damage
@@ -58,73 +73,58 @@ impl Damage
             destroy_on_damage,
             deal_damage_once,
             knockback,
-            did_damage: false
+            rehit_interval: None,
+            max_hits: None,
+            hits: HashMap::new(),
+            hit_count: 0,
         }
     }
 
+    /// Whether the object should be destroyed, assumes the current hit has already been registered
     pub fn should_destroy(&self) -> bool
     {
-        self.destroy_on_damage
+        self.destroy_on_damage && self.max_hits.is_none_or(|max_hits| self.hit_count >= max_hits)
     }
 
-    pub fn new_typical_bullet(damage: f32) -> Self
+    pub fn with_rehit_interval(&mut self, rehit_interval: Option<f32>) -> &mut Self
     {
-        Self
-        {
-            damage,
-            destroy_on_damage: true,
-            deal_damage_once: true,
-            knockback: None,
-            did_damage: false,
-        }
+        self.rehit_interval = rehit_interval;
+        self
     }
 
-    pub fn new_typical_explosion(damage: f32) -> Self
+    pub fn with_max_hits(&mut self, max_hits: Option<u32>) -> &mut Self
     {
-        Self
-        {
-            damage,
-            destroy_on_damage: false,
-            deal_damage_once: false,
-            knockback: None,
-            did_damage: false,
-        }
+        self.max_hits = max_hits;
+        self
     }
 
-    pub fn new_typical_laser(damage: f32) -> Self
+    /// Whether this object can still hit anything at all
+    pub fn can_hit_anything(&self) -> bool
     {
-        Self
-        {
-            damage, 
-            destroy_on_damage: false,
-            deal_damage_once: false,
-            knockback: None,
-            did_damage: false,
-        }
+        !(self.deal_damage_once && self.hit_count > 0) && self.max_hits.is_none_or(|max_hits| self.hit_count < max_hits)
     }
 
-    pub fn new_typical_hitscan(damage: f32) -> Self
+    /// Whether this object is currently allowed to hit the given victim
+    pub fn can_hit(&self, victim: Entity) -> bool
     {
-        Self
-        {
-            damage,
-            destroy_on_damage: false,
-            deal_damage_once: true,
-            knockback: None,
-            did_damage: false,
-        }
+        self.can_hit_anything() && !self.hits.contains_key(&victim)
     }
 
-    pub fn with_destroy_on_damage(&mut self, destroy_on_damage: bool) -> &mut Self
+    /// Records a hit against a victim, preventing further hits on them until the re-hit interval (if any) elapses
+    pub fn register_hit(&mut self, victim: Entity)
     {
-        self.destroy_on_damage = destroy_on_damage;
-        self
+        self.hits.insert(victim, self.rehit_interval.unwrap_or(f32::INFINITY));
+        self.hit_count += 1;
     }
 
-    pub fn with_knockback(&mut self, knockback: Option<DamageKnockback>) -> &mut Self
+    /// Counts down re-hit timers, forgetting victims that may be hit again
+    pub fn tick_hits(&mut self, delta_seconds: f32)
     {
-        self.knockback = knockback;
-        self
+        self.hits.retain(|_, remaining| 
+        {
+            *remaining -= delta_seconds;
+            *remaining > 0.0
+        });
     }
 }
 
@@ -145,86 +145,333 @@ fn do_dmg(
     actor_context: &mut ActorContext,
     instigator: Entity,
 ) {
+    let damage_to_do = shield::absorb_damage(actor_context, damage_to_do);
     let existing_health = *actor_context.stats.get(&Stat::Health).unwrap_or(&0.0_f32);
     let new_health = existing_health - damage_to_do;
     actor_context.stats.insert(Stat::Health, new_health);
     actor_context.last_damage_source = Some(super::DamageSource::Actor(instigator));
 }
 
-fn dmg_events(
-    coms: &mut Commands,
-    actor_lookup: &mut Query<(&mut ActorContext, &mut Position)>,
-    read_events: &Vec<DamageEvent>,
-    write_events: &mut Vec<DamageEvent>,
-) {
-    for DamageEvent { instigator, victim, damage } in read_events
-    {
-        if instigator == victim
+/// A damage event waiting to be processed, along with the chain of events that caused it
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueuedDamage
+{
+    pub event: DamageEvent,
+    /// Every (instigator, victim) pair from the originating event up to (and including) this one
+    pub chain: Vec<(Entity, Entity)>,
+}
+
+/// Reasons a damage event caused by another damage event can be dropped
+#[derive(Clone, Debug, PartialEq)]
+pub enum DamageChainError
+{
+    /// The chain of events grew deeper than the maximum allowed depth
+    TooDeep{ chain: Vec<(Entity, Entity)> },
+    /// The same instigator damaged the same victim twice in one chain (eg. two actors reflecting damage back and forth)
+    Cycle{ chain: Vec<(Entity, Entity)> },
+    /// Too many damage events were processed this frame
+    QueueFull,
+}
+
+impl std::fmt::Display for DamageChainError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
         {
-            let Ok((mut actor_context, mut actor_pos)) = actor_lookup.get_mut(*instigator) else { 
-                error!("Could not find actor comps for entity {:?} in damage event!", instigator); 
-                continue;
-            };
+            Self::TooDeep { chain } => write!(f, "damage chain exceeded {} levels", chain.len() - 1),
+            Self::Cycle { chain } => write!(f, "damage chain of {} events formed a cycle", chain.len()),
+            Self::QueueFull => write!(f, "too many damage events were queued in one frame"),
+        }
+    }
+}
 
-            let mut damage_context = ActorDamageEffectContext
-            {
-                world_access: &mut EffectContextWorldAccess { commands: coms, damage_instances: write_events },
-                instigator: &mut ActorReference { entity: *instigator, context: &mut actor_context, location: &mut actor_pos },
-                victim: None,
-            };
+/// Bounded work queue of damage events.
+/// Events caused by other events remember their chain so runaway effect loops can be caught and reported
+pub struct DamageQueue
+{
+    queue: VecDeque<QueuedDamage>,
+    queued_total: usize,
+    max_depth: usize,
+    max_total: usize,
+}
+
+impl DamageQueue
+{
+    pub fn new(max_depth: usize, max_total: usize) -> Self
+    {
+        Self { queue: VecDeque::new(), queued_total: 0, max_depth, max_total }
+    }
+
+    fn push_chain(&mut self, event: DamageEvent, chain: Vec<(Entity, Entity)>) -> Result<(), DamageChainError>
+    {
+        if self.queued_total >= self.max_total
+        {
+            return Err(DamageChainError::QueueFull);
+        }
+        self.queued_total += 1;
+        self.queue.push_back(QueuedDamage { event, chain });
+        Ok(())
+    }
 
-            let dmg = calc_dmg_effects(&mut damage_context, *damage);
-            do_dmg(dmg, &mut actor_context, *instigator);
+    /// Queues an event that was not caused by another damage event
+    pub fn push_origin(&mut self, event: DamageEvent) -> Result<(), DamageChainError>
+    {
+        self.push_chain(event, vec![(event.instigator, event.victim)])
+    }
 
-            continue;
+    /// Queues an event caused by processing the given parent event
+    pub fn push_caused(&mut self, parent: &QueuedDamage, event: DamageEvent) -> Result<(), DamageChainError>
+    {
+        let link = (event.instigator, event.victim);
+        let mut chain = parent.chain.clone();
+        let is_cycle = chain.contains(&link);
+        chain.push(link);
+        if is_cycle
+        {
+            return Err(DamageChainError::Cycle { chain });
         }
-        let Ok(
-            [(mut instigator_context, mut instigator_position), 
-            (mut victim_context, mut victim_position)]
-            ) = actor_lookup.get_many_mut([*instigator, *victim]) 
-            else 
-            { 
-                error!("Did not find actor comps in query from damage event!"); 
-                trace!("Instigator: {:?}, Victim: {:?}", instigator, victim);
-                continue; 
-            };
-
-        
-        let mut victim = ActorReference { entity: *victim, context: &mut victim_context, location: &mut victim_position };
-
-        let mut damage_context = ActorDamageEffectContext 
+        if chain.len() > self.max_depth
         {
-            world_access: &mut EffectContextWorldAccess { commands: coms, damage_instances: write_events },
-            instigator: &mut ActorReference { entity: *instigator, context: &mut instigator_context, location: &mut instigator_position },
-            victim: Some(&mut victim),
+            return Err(DamageChainError::TooDeep { chain });
+        }
+        self.push_chain(event, chain)
+    }
+
+    pub fn pop(&mut self) -> Option<QueuedDamage>
+    {
+        self.queue.pop_front()
+    }
+}
+
+fn report_dropped_damage(error: &DamageChainError, event: &DamageEvent)
+{
+    match error
+    {
+        DamageChainError::TooDeep { chain } | DamageChainError::Cycle { chain } =>
+            error!(origin = ?chain.first(), depth = chain.len(), chain = ?chain, dropped = ?event, "{SERVER_STR} Dropping damage event: {error}"),
+        DamageChainError::QueueFull =>
+            error!(dropped = ?event, "{SERVER_STR} Dropping damage event: {error}"),
+    }
+}
+
+fn dmg_event(
+    coms: &mut Commands,
+    actor_lookup: &mut Query<(&mut ActorContext, &mut Position)>,
+    event: &DamageEvent,
+    caused_events: &mut Vec<DamageEvent>,
+) {
+    let DamageEvent { instigator, victim, damage } = event;
+    if instigator == victim
+    {
+        let Ok((mut actor_context, mut actor_pos)) = actor_lookup.get_mut(*instigator) else { 
+            error!("Could not find actor comps for entity {:?} in damage event!", instigator); 
+            return;
+        };
+
+        let mut damage_context = ActorDamageEffectContext
+        {
+            world_access: &mut EffectContextWorldAccess { commands: coms, damage_instances: caused_events },
+            instigator: &mut ActorReference { entity: *instigator, context: &mut actor_context, location: &mut actor_pos },
+            victim: None,
         };
 
         let dmg = calc_dmg_effects(&mut damage_context, *damage);
-        do_dmg(dmg, &mut victim_context, *instigator);
+        do_dmg(dmg, &mut actor_context, *instigator);
+
+        return;
     }
+    let Ok(
+        [(mut instigator_context, mut instigator_position), 
+        (mut victim_context, mut victim_position)]
+        ) = actor_lookup.get_many_mut([*instigator, *victim]) 
+        else 
+        { 
+            error!("Did not find actor comps in query from damage event!"); 
+            trace!("Instigator: {:?}, Victim: {:?}", instigator, victim);
+            return; 
+        };
+
+    
+    let mut victim = ActorReference { entity: *victim, context: &mut victim_context, location: &mut victim_position };
+
+    let mut damage_context = ActorDamageEffectContext 
+    {
+        world_access: &mut EffectContextWorldAccess { commands: coms, damage_instances: caused_events },
+        instigator: &mut ActorReference { entity: *instigator, context: &mut instigator_context, location: &mut instigator_position },
+        victim: Some(&mut victim),
+    };
+
+    let dmg = calc_dmg_effects(&mut damage_context, *damage);
+    do_dmg(dmg, &mut victim_context, *instigator);
 }
 
 pub fn s_do_damage_events(
     mut commands: Commands,
     mut actor_lookup: Query<(&mut ActorContext, &mut Position)>,
     mut damage_events: EventReader<DamageEvent>,
+    summons: Query<&ActorChild, With<ActorContext>>,
 ) {
-    let mut damage_es_1: Vec<DamageEvent> = Vec::new();
-    let mut damage_es_2: Vec<DamageEvent> = Vec::new();
+    let mut queue = DamageQueue::new(MAX_DAMAGE_CHAIN_DEPTH, MAX_DAMAGE_EVENTS_PER_FRAME);
+    for event in damage_events.read()
+    {
+        // Damage done by summons is done on behalf of their summoner, so they get the kill credit and damage effects
+        let mut event = *event;
+        if event.instigator != event.victim
+        {
+            event.instigator = responsible_actor(event.instigator, |entity| summoner_of(&summons, entity));
+        }
+        if let Err(e) = queue.push_origin(event)
+        {
+            report_dropped_damage(&e, &event);
+        }
+    }
+
+    let mut caused_events = Vec::new();
+    while let Some(queued) = queue.pop()
+    {
+        dmg_event(&mut commands, &mut actor_lookup, &queued.event, &mut caused_events);
+        for caused in caused_events.drain(..)
+        {
+            if let Err(e) = queue.push_caused(&queued, caused)
+            {
+                report_dropped_damage(&e, &caused);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_damage_hits_each_victim_once()
+    {
+        let victim_a = Entity::from_raw(1);
+        let victim_b = Entity::from_raw(2);
+        let mut damage = Damage::new(5.0, false, false, None);
+
+        assert!(damage.can_hit(victim_a));
+        damage.register_hit(victim_a);
+        assert!(!damage.can_hit(victim_a));
+        assert!(damage.can_hit(victim_b));
 
-    let mut count = 0;
+        damage.tick_hits(100.0);
+        assert!(!damage.can_hit(victim_a));
+    }
 
-    damage_es_1.extend(damage_events.read());
-    while !damage_es_1.is_empty() || !damage_es_2.is_empty()
+    #[test]
+    fn test_damage_deal_once_blocks_other_victims()
     {
-        dmg_events(&mut commands, &mut actor_lookup, &damage_es_1, &mut damage_es_2);
-        damage_es_1.clear();
-        dmg_events(&mut commands, &mut actor_lookup, &damage_es_2, &mut damage_es_1);
-        count += 1;
-        if count >= 10
+        let victim_a = Entity::from_raw(1);
+        let victim_b = Entity::from_raw(2);
+        let mut damage = Damage::new(5.0, true, true, None);
+
+        damage.register_hit(victim_a);
+        assert!(!damage.can_hit_anything());
+        assert!(!damage.can_hit(victim_b));
+    }
+
+    #[test]
+    fn test_damage_rehit_interval()
+    {
+        let victim = Entity::from_raw(1);
+        let mut damage = Damage::new(5.0, false, false, None);
+        damage.with_rehit_interval(Some(0.5));
+
+        damage.register_hit(victim);
+        damage.tick_hits(0.25);
+        assert!(!damage.can_hit(victim));
+        damage.tick_hits(0.3);
+        assert!(damage.can_hit(victim));
+        assert_eq!(damage.hit_count, 1);
+    }
+
+    #[test]
+    fn test_damage_max_hits()
+    {
+        let mut damage = Damage::new(5.0, true, false, None);
+        damage.with_max_hits(Some(2));
+
+        damage.register_hit(Entity::from_raw(1));
+        assert!(!damage.should_destroy());
+        assert!(damage.can_hit(Entity::from_raw(2)));
+
+        damage.register_hit(Entity::from_raw(2));
+        assert!(damage.should_destroy());
+        assert!(!damage.can_hit_anything());
+    }
+
+    fn dmg(instigator: u32, victim: u32) -> DamageEvent
+    {
+        DamageEvent { instigator: Entity::from_raw(instigator), victim: Entity::from_raw(victim), damage: 1.0 }
+    }
+
+    #[test]
+    fn test_damage_queue_chain()
+    {
+        let mut queue = DamageQueue::new(4, 100);
+        queue.push_origin(dmg(1, 2)).unwrap();
+
+        let origin = queue.pop().unwrap();
+        assert_eq!(origin.chain.len(), 1);
+        queue.push_caused(&origin, dmg(2, 3)).unwrap();
+        queue.push_caused(&origin, dmg(1, 1)).unwrap();
+
+        let caused = queue.pop().unwrap();
+        assert_eq!(caused.event, dmg(2, 3));
+        assert_eq!(caused.chain, vec![(Entity::from_raw(1), Entity::from_raw(2)), (Entity::from_raw(2), Entity::from_raw(3))]);
+        assert_eq!(queue.pop().unwrap().event, dmg(1, 1));
+        assert!(queue.pop().is_none());
+    }
+
+    #[test]
+    fn test_damage_queue_detects_reflection_cycle()
+    {
+        let mut queue = DamageQueue::new(16, 100);
+        queue.push_origin(dmg(1, 2)).unwrap();
+
+        let origin = queue.pop().unwrap();
+        queue.push_caused(&origin, dmg(2, 1)).unwrap();
+        let reflected = queue.pop().unwrap();
+
+        let err = queue.push_caused(&reflected, dmg(1, 2)).unwrap_err();
+        assert_eq!(err, DamageChainError::Cycle { chain: vec![
+            (Entity::from_raw(1), Entity::from_raw(2)),
+            (Entity::from_raw(2), Entity::from_raw(1)),
+            (Entity::from_raw(1), Entity::from_raw(2)),
+        ] });
+        assert!(queue.pop().is_none());
+    }
+
+    #[test]
+    fn test_damage_queue_depth_limit()
+    {
+        let mut queue = DamageQueue::new(3, 100);
+        queue.push_origin(dmg(0, 1)).unwrap();
+
+        let mut current = queue.pop().unwrap();
+        for i in 1..3
         {
-            error!("Damage event recursion reached 10 levels! WTF?");
-            break;
+            queue.push_caused(&current, dmg(i, i + 1)).unwrap();
+            current = queue.pop().unwrap();
         }
+        assert_eq!(current.chain.len(), 3);
+
+        let err = queue.push_caused(&current, dmg(3, 4)).unwrap_err();
+        assert!(matches!(err, DamageChainError::TooDeep { chain } if chain.len() == 4));
+    }
+
+    #[test]
+    fn test_damage_queue_total_limit()
+    {
+        let mut queue = DamageQueue::new(16, 2);
+        queue.push_origin(dmg(1, 2)).unwrap();
+        queue.push_origin(dmg(3, 4)).unwrap();
+        assert_eq!(queue.push_origin(dmg(5, 6)), Err(DamageChainError::QueueFull));
+
+        let origin = queue.pop().unwrap();
+        assert_eq!(queue.push_caused(&origin, dmg(2, 7)), Err(DamageChainError::QueueFull));
     }
 }
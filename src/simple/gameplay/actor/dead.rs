@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::simple::{gameplay::{Dead, Position}, consts::SERVER_STR};
+use crate::simple::{gameplay::{match_rules::Respawns, Dead, Orientation, Position, Velocity}, consts::SERVER_STR};
 
 use super::{
     effect_application::{
@@ -8,13 +8,14 @@ use super::{
         apply_on_death_effects, 
         apply_on_kill_effects,
         ActorReference,
-        ActorEffectContext,
+        ActorAbilityEndEffectContext,
         ActorKillEffectContext,
         ActorDeathEffectContext,
         EffectContextWorldAccess,
     },
     ActorChild, 
     ActorContext, 
+    Fragment,
     DamageSource,
     DamageEvent,
 };
@@ -22,42 +23,82 @@ use super::{
 
 
 
+/// The direction an ability object was going when it ended: where it was moving, otherwise where it was facing
+fn ability_end_direction(velocity: Option<&Velocity>, orientation: Option<&Orientation>) -> Vec2
+{
+    if let Some(direction) = velocity.and_then(|velocity| velocity.0.try_normalize())
+    {
+        return direction;
+    }
+    orientation.map_or(Vec2::X, |orientation| Vec2::from_angle(orientation.0))
+}
+
+/// Type alias for dead ability objects (and summons), with what is needed to work out where and which way they ended
+type DeadChildren<'w, 's> = Query<'w, 's, (Entity, &'static ActorChild, Option<&'static GlobalTransform>, Option<&'static Velocity>, Option<&'static Orientation>, Has<Fragment>), With<Dead>>;
+
+/// Type alias for actors that died since the last run, and whether they will respawn
+type NewlyDeadActors<'w, 's> = Query<'w, 's, (Entity, Has<Respawns>), (With<ActorContext>, Added<Dead>)>;
+
 pub fn s_destroy_dead_things(
     mut commands: Commands,
     mut parent_lookup: Query<(Entity, &mut ActorContext, &mut Position), Without<Dead>>,
     mut dead_parents: Query<(Entity, &mut ActorContext, &mut Position), With<Dead>>,
-    dead_children: Query<(Entity, &ActorChild), With<Dead>>,
+    newly_dead_parents: NewlyDeadActors,
+    dead_children: DeadChildren,
     dead_things: Query<Entity, (Without<ActorChild>, Without<ActorContext>, With<Dead>)>,
     mut damage_events: EventWriter<DamageEvent>,
 ) {
     let mut damage_es = Vec::new();
-    for (entity, child) in &dead_children
+    for (entity, child, transform, velocity, orientation, is_fragment) in &dead_children
     {
-        if let Ok((parent_ent, mut actor_context, mut position)) = parent_lookup.get_mut(child.parent_actor)
+        let end_direction = ability_end_direction(velocity, orientation);
+        if is_fragment
+        {
+            // Fragments are not given their own ability end effects
+        }
+        else if let Ok((parent_ent, mut actor_context, mut position)) = parent_lookup.get_mut(child.parent_actor)
         {
-            apply_on_ability_end_effects(child.ability_type, &mut ActorEffectContext{ 
+            // Children without a transform (eg. abilities that never got their authority bundle) end where their parent is
+            let end_location = transform.map_or(position.0, |transform| transform.translation().truncate());
+            apply_on_ability_end_effects(&mut ActorAbilityEndEffectContext{ 
                 actor: &mut ActorReference { context: &mut actor_context, entity: parent_ent, location: &mut position },
                 world_access: &mut EffectContextWorldAccess { commands: &mut commands, damage_instances: &mut damage_es },
+                ability_type: child.ability_type,
+                end_location,
+                end_direction,
             });
         }
         else if let Ok((parent_ent, mut actor_context, mut position)) = dead_parents.get_mut(entity) 
         {
-            apply_on_ability_end_effects(child.ability_type, &mut ActorEffectContext{ 
+            let end_location = position.0;
+            apply_on_ability_end_effects(&mut ActorAbilityEndEffectContext{ 
                 actor: &mut ActorReference { context: &mut actor_context, entity: parent_ent, location: &mut position },
                 world_access: &mut EffectContextWorldAccess { commands: &mut commands, damage_instances: &mut damage_es },
+                ability_type: child.ability_type,
+                end_location,
+                end_direction,
             });
         }
         else
         {
             error!("Did not find parent for dead entity!");
         }
-        commands.entity(entity).despawn_recursive();
+        // Summoned actors are also dead actors, and are despawned with them below
+        if !dead_parents.contains(entity)
+        {
+            commands.entity(entity).despawn_recursive();
+        }
     }
     let mut dying_actors = Vec::new();
-    for (entity, actor, _) in &mut dead_parents
+    for (entity, respawns) in &newly_dead_parents
     {
+        let Ok((_, actor, _)) = dead_parents.get(entity) else { continue; };
         dying_actors.push((entity, actor.last_damage_source));
-        commands.entity(entity).despawn_recursive();
+        // Actors that respawn stay dead until they do, rather than being despawned
+        if !respawns
+        {
+            commands.entity(entity).despawn_recursive();
+        }
     }
     for entity in &dead_things
     {
@@ -115,3 +156,19 @@ pub fn s_destroy_dead_things(
 
 
 
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_ability_end_direction()
+    {
+        assert_eq!(ability_end_direction(Some(&Velocity(Vec2::new(0.0, -5.0))), Some(&Orientation(0.0))), Vec2::NEG_Y);
+        // Stationary abilities use where they were facing
+        let facing = ability_end_direction(Some(&Velocity(Vec2::ZERO)), Some(&Orientation(std::f32::consts::FRAC_PI_2)));
+        assert!(facing.abs_diff_eq(Vec2::Y, 0.0001));
+        assert_eq!(ability_end_direction(None, None), Vec2::X);
+    }
+}
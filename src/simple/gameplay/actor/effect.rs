@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use super::stats::StatusEffect;
+use crate::simple::gameplay::objects::MissileGuidance;
 
 //   Temporary solution to allowing specific abilities to have certain effects
 //   v
@@ -14,12 +15,47 @@ pub enum ChildType
     Missile,
     Grenade,
     Explosion,
+    Boomerang,
     ChildActor,
 }
 
 //   ^
 //   Temporary brainstormed solution allowing specific abilities have certain effects
 
+/// Extra behaviour a projectile can have when it hits things or reaches the edge of the world
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Reflect)]
+pub struct ProjectileModifiers
+{
+    /// How many extra enemies the projectile can pass through
+    #[serde(default)]
+    pub pierce: u32,
+    /// How many times the projectile will redirect itself towards the nearest other enemy after a hit
+    #[serde(default)]
+    pub ricochet: u32,
+    /// How many times the projectile will bounce off the world bounds
+    #[serde(default)]
+    pub bounce: u32,
+}
+
+impl ProjectileModifiers
+{
+    pub fn combined(&self, other: &ProjectileModifiers) -> Self
+    {
+        Self
+        {
+            pierce: self.pierce + other.pierce,
+            ricochet: self.ricochet + other.ricochet,
+            bounce: self.bounce + other.bounce,
+        }
+    }
+
+    /// The total number of enemies a projectile with these modifiers can hit
+    pub fn max_hits(&self) -> u32
+    {
+        1 + self.pierce + self.ricochet
+    }
+}
+
 // ^
 // Useful structs
 
@@ -32,13 +68,63 @@ pub enum ChildType
 // These enums contain the serializable form of every effect
 // It is used in combination with the Trigger enum (the Trigger enum stores instances of this enum)
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Reflect)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Reflect)]
 pub enum SpawnType
 {
     Explosion{ radius: f32, damage: f32, knockback_strength: f32 },
     // Future ideas v
-    Missile{ damage: f32, speed: f32, acceleration: f32, knockback_strength: f32 },
+    Missile{ 
+        damage: f32, speed: f32, acceleration: f32, knockback_strength: f32, 
+        #[serde(default)] guidance: MissileGuidance, 
+        /// The fastest the missile can turn (radians/s), the default missile turn rate if None
+        #[serde(default)] turn_rate: Option<f32>,
+    },
     Lightning{  },
+    /// A lingering area that applies an effect to everything (of the affected faction) inside it every tick.
+    /// The effect can not spawn anything itself, see [`SpawnType::nested_spawn`]
+    Zone{ radius: f32, duration: f32, tick_interval: f32, #[reflect(ignore, default = "SpawnType::reflected_zone_effect")] effect: Box<SerializedActorEffect>, #[serde(default)] affects: ZoneTargets },
+    /// An actor that fights on behalf of the caster for a duration, damage is the damage of each of its attacks
+    Summon{ kind: SummonKind, health: f32, duration: f32, #[serde(default)] damage: f32 },
+    /// Fires bullets evenly spread (spread is the radians between the outermost bullets) around the spawn direction.
+    /// When spawned by an ability ending, the bullets count as that ability and do not trigger ability end effects themselves
+    BulletSpread{ count: u32, spread: f32, speed: f32, damage: f32 },
+}
+
+impl SpawnType
+{
+    /// Whether this spawns an object that would spawn objects itself (a zone with a spawn effect), which is not supported
+    pub fn nested_spawn(&self) -> bool
+    {
+        matches!(self, Self::Zone { effect, .. } if matches!(**effect, SerializedActorEffect::SpawnEffect(..)))
+    }
+
+    /// The zone effect reflection falls back to, as boxed effects are not reflected
+    fn reflected_zone_effect() -> Box<SerializedActorEffect>
+    {
+        Box::new(SerializedActorEffect::AffectHealth(0.0))
+    }
+}
+
+/// The behaviour of a summoned actor
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Reflect)]
+pub enum SummonKind
+{
+    /// Stays where it was placed, shooting the nearest enemy in range
+    Turret,
+    /// Follows its summoner, shooting the nearest enemy in range
+    Drone,
+    /// Stays where it was placed, drawing the attention of nearby enemies
+    Decoy,
+}
+
+/// Which faction a zone affects, relative to its owner
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Reflect)]
+pub enum ZoneTargets
+{
+    #[default]
+    Enemies,
+    Allies,
+    Everyone,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Reflect)]
@@ -68,12 +154,14 @@ impl DamageActor
     }
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Reflect)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Reflect)]
 pub enum SerializedActorEffect
 {
     InflictStatusEffect(StatusEffect),
     SpawnEffect(SpawnType, SpawnLocation),
     AffectHealth(f32),
+    /// Gives the actor a shield that absorbs this much damage, decaying if it goes over their max shield
+    GrantShield(f32),
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Reflect)]
@@ -83,7 +171,7 @@ pub enum SerializedDamageChangeEffect
     AddDamageEffect{ amount: f32 },
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Reflect)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Reflect)]
 pub enum SerializedDamageViewEffect
 {
     SpawnObjectAt{ which_actor: DamageActor, spawn_type: SpawnType },
@@ -92,25 +180,32 @@ pub enum SerializedDamageViewEffect
     RegularEffect{ effect: SerializedActorEffect },
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Reflect)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Reflect)]
 pub enum SerializedKillEffect
 {
     RegularEffect{ effect: SerializedActorEffect },
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Reflect)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Reflect)]
 pub enum SerializedDeathEffect
 {
     RegularEffect{ effect: SerializedActorEffect },
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Reflect)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Reflect)]
 pub enum SerializedOnHitEffect
 {
     SpawnEffectAtHitLocation{ spawn_type: SpawnType },
     RegularEffect{ effect: SerializedActorEffect },
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Reflect)]
+pub enum SerializedAbilityEndEffect
+{
+    SpawnEffectAtEndLocation{ spawn_type: SpawnType },
+    RegularEffect{ effect: SerializedActorEffect },
+}
+
 impl Into<SerializedDamageViewEffect> for SerializedActorEffect
 {
     fn into(self) -> SerializedDamageViewEffect {
@@ -139,7 +234,14 @@ impl Into<SerializedOnHitEffect> for SerializedActorEffect
     }
 }
 
-#[derive(Clone, Debug, Copy, Serialize, Deserialize, PartialEq, Reflect)]
+impl From<SerializedActorEffect> for SerializedAbilityEndEffect
+{
+    fn from(effect: SerializedActorEffect) -> Self {
+        Self::RegularEffect { effect }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Reflect)]
 pub enum SerializedEffectTrigger
 {
     OnKill(SerializedKillEffect),
@@ -151,7 +253,96 @@ pub enum SerializedEffectTrigger
     OnDamageReceived(SerializedDamageViewEffect),   // <- DOESN'T CHANGE DAMAGE
     OnAbilityCast{ ability_type: ChildType, effect: SerializedActorEffect },
     OnAbilityHit{ ability_type: ChildType, effect: SerializedOnHitEffect },
-    OnAbilityEnd{ ability_type: ChildType, effect: SerializedActorEffect },
+    OnAbilityEnd{ ability_type: ChildType, effect: SerializedAbilityEndEffect },
+    ModifyProjectiles{ ability_type: ChildType, modifiers: ProjectileModifiers }, // <- Passive, read when the projectile is created
+}
+
+/// What triggers an effect, without the effect itself. Used to pick out effects of a certain kind
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Reflect)]
+pub enum TriggerType
+{
+    OnKill,
+    OnDeath,
+    Periodically,
+    OnDoDamage,
+    OnDamageDone,
+    OnReceiveDamage,
+    OnDamageReceived,
+    OnAbilityCast,
+    OnAbilityHit,
+    OnAbilityEnd,
+    ModifyProjectiles,
+}
+
+impl SerializedEffectTrigger
+{
+    pub fn trigger_type(&self) -> TriggerType
+    {
+        match self
+        {
+            Self::OnKill(_) => TriggerType::OnKill,
+            Self::OnDeath(_) => TriggerType::OnDeath,
+            Self::Periodically { .. } => TriggerType::Periodically,
+            Self::OnDoDamage(_) => TriggerType::OnDoDamage,
+            Self::OnDamageDone(_) => TriggerType::OnDamageDone,
+            Self::OnReceiveDamage(_) => TriggerType::OnReceiveDamage,
+            Self::OnDamageReceived(_) => TriggerType::OnDamageReceived,
+            Self::OnAbilityCast { .. } => TriggerType::OnAbilityCast,
+            Self::OnAbilityHit { .. } => TriggerType::OnAbilityHit,
+            Self::OnAbilityEnd { .. } => TriggerType::OnAbilityEnd,
+            Self::ModifyProjectiles { .. } => TriggerType::ModifyProjectiles,
+        }
+    }
+
+    /// The type of ability this effect is limited to, if it is limited to one
+    pub fn ability_type(&self) -> Option<ChildType>
+    {
+        match *self
+        {
+            Self::OnAbilityCast { ability_type, .. }
+            | Self::OnAbilityHit { ability_type, .. }
+            | Self::OnAbilityEnd { ability_type, .. }
+            | Self::ModifyProjectiles { ability_type, .. } => Some(ability_type),
+            _ => None,
+        }
+    }
+
+    /// The object this effect spawns, if it spawns one
+    pub fn spawn_type(&self) -> Option<&SpawnType>
+    {
+        match self
+        {
+            Self::OnKill(SerializedKillEffect::RegularEffect { effect })
+            | Self::OnDeath(SerializedDeathEffect::RegularEffect { effect })
+            | Self::Periodically { effect, .. }
+            | Self::OnAbilityCast { effect, .. }
+            | Self::OnAbilityHit { effect: SerializedOnHitEffect::RegularEffect { effect }, .. }
+            | Self::OnAbilityEnd { effect: SerializedAbilityEndEffect::RegularEffect { effect }, .. } => effect.spawn_type(),
+            Self::OnAbilityHit { effect: SerializedOnHitEffect::SpawnEffectAtHitLocation { spawn_type }, .. }
+            | Self::OnAbilityEnd { effect: SerializedAbilityEndEffect::SpawnEffectAtEndLocation { spawn_type }, .. } => Some(spawn_type),
+            Self::OnDamageDone(effect) | Self::OnDamageReceived(effect) => match effect
+            {
+                SerializedDamageViewEffect::SpawnObjectAt { spawn_type, .. } => Some(spawn_type),
+                SerializedDamageViewEffect::EveryXDamageEffect { effect, .. }
+                | SerializedDamageViewEffect::EveryXHealedEffect { effect, .. }
+                | SerializedDamageViewEffect::RegularEffect { effect } => effect.spawn_type(),
+            },
+            Self::OnDoDamage(_) | Self::OnReceiveDamage(_) | Self::ModifyProjectiles { .. } => None,
+        }
+    }
+}
+
+impl SerializedActorEffect
+{
+    /// The object this effect spawns, if it spawns one
+    pub fn spawn_type(&self) -> Option<&SpawnType>
+    {
+        match self
+        {
+            Self::SpawnEffect(spawn_type, _) => Some(spawn_type),
+            _ => None,
+        }
+    }
 }
 
 // If ever needed, OR, NOT, and AND 'operators' (and other binary operators) could be made as convenience structs for ActorCondition
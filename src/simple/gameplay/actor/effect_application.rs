@@ -101,6 +101,16 @@ pub struct ActorOnHitEffectContext<'a, 'b, 'c, 'd>
     pub hit_location: Vec2,
 }
 
+// All values needed for applying an on ability end effect
+pub struct ActorAbilityEndEffectContext<'a, 'b, 'c, 'd>
+{
+    pub world_access: &'a mut EffectContextWorldAccess<'b, 'c, 'd>,
+    pub actor: &'a mut ActorReference<'b>,
+    pub ability_type: ChildType,
+    pub end_location: Vec2, // Where the ability object was when it ended
+    pub end_direction: Vec2, // The (normalized) direction the ability object was moving/facing when it ended
+}
+
 // ^
 // Effects
 // Public Facing Effect Interface
@@ -148,8 +158,9 @@ pub fn apply_on_ability_hit_effects<'a, 'b, 'c, 'd>(ability_type: ChildType, con
     }
 }
 
-pub fn apply_on_ability_end_effects<'a, 'b, 'c, 'd>(ability_type: ChildType, context: &mut ActorEffectContext<'a, 'b, 'c, 'd>)
+pub fn apply_on_ability_end_effects<'a, 'b, 'c, 'd>(context: &mut ActorAbilityEndEffectContext<'a, 'b, 'c, 'd>)
 {
+    let ability_type = context.ability_type;
     let mut effects = Vec::new();
     for effect_trigger in &mut context.actor.context.effects
     {
@@ -169,6 +180,21 @@ pub fn apply_on_ability_end_effects<'a, 'b, 'c, 'd>(ability_type: ChildType, con
     }
 }
 
+/// Sums all projectile modifiers an actor has for the given ability type
+pub fn get_projectile_modifiers(ability_type: ChildType, actor: &ActorContext) -> ProjectileModifiers
+{
+    let mut modifiers = ProjectileModifiers::default();
+    for effect_trigger in &actor.effects
+    {
+        let SerializedEffectTrigger::ModifyProjectiles{ ability_type: ability_trigger_type, modifiers: effect_modifiers } = effect_trigger else { continue; };
+        if *ability_trigger_type == ability_type
+        {
+            modifiers = modifiers.combined(effect_modifiers);
+        }
+    }
+    modifiers
+}
+
 pub fn apply_on_kill_effects<'a, 'b, 'c, 'd>(context: &mut ActorKillEffectContext<'a, 'b, 'c, 'd>)
 {
     let mut effects = Vec::new();
@@ -317,14 +343,14 @@ mod tests
 
         my_actor.stats.insert(Stat::Health, 50.0_f32);
 
-        my_actor.effects.push(SerializedEffectTrigger::OnDeath(test_effect.into()));
-        my_actor.effects.push(SerializedEffectTrigger::OnKill(test_effect.into()));
-        my_actor.effects.push(SerializedEffectTrigger::OnAbilityCast{ ability_type: ChildType::Grenade, effect: test_effect.into() });
-        my_actor.effects.push(SerializedEffectTrigger::OnAbilityHit{ ability_type: ChildType::Grenade, effect: test_effect.into() });
-        my_actor.effects.push(SerializedEffectTrigger::OnAbilityEnd{ ability_type: ChildType::Grenade, effect: test_effect.into() });
-        my_actor.effects.push(SerializedEffectTrigger::Periodically { remaining_period: 0.0_f32, period: 2.0_f32, effect: test_effect.into() });
+        my_actor.effects.push(SerializedEffectTrigger::OnDeath(test_effect.clone().into()));
+        my_actor.effects.push(SerializedEffectTrigger::OnKill(test_effect.clone().into()));
+        my_actor.effects.push(SerializedEffectTrigger::OnAbilityCast{ ability_type: ChildType::Grenade, effect: test_effect.clone().into() });
+        my_actor.effects.push(SerializedEffectTrigger::OnAbilityHit{ ability_type: ChildType::Grenade, effect: test_effect.clone().into() });
+        my_actor.effects.push(SerializedEffectTrigger::OnAbilityEnd{ ability_type: ChildType::Grenade, effect: test_effect.clone().into() });
+        my_actor.effects.push(SerializedEffectTrigger::Periodically { remaining_period: 0.0_f32, period: 2.0_f32, effect: test_effect.clone().into() });
         my_actor.effects.push(SerializedEffectTrigger::OnDoDamage(test_dmg_effect));
-        my_actor.effects.push(SerializedEffectTrigger::OnDamageDone(test_effect.into()));
+        my_actor.effects.push(SerializedEffectTrigger::OnDamageDone(test_effect.clone().into()));
         my_actor.effects.push(SerializedEffectTrigger::OnReceiveDamage(test_dmg_effect));
         my_actor.effects.push(SerializedEffectTrigger::OnDamageReceived(test_effect.into()));
         
@@ -358,12 +384,15 @@ mod tests
         {
             let mut world_access = EffectContextWorldAccess { commands: &mut fake_commands, damage_instances: &mut fake_damage_events };
             let mut test_actor_ref = ActorReference { context: &mut my_actor, entity: Entity::PLACEHOLDER, location: &mut fake_position };
-            let mut fake_context = ActorEffectContext {
+            let mut fake_context = ActorAbilityEndEffectContext {
                 world_access: &mut world_access,
-                actor: &mut test_actor_ref
+                actor: &mut test_actor_ref,
+                ability_type: ChildType::Grenade,
+                end_location: Vec2::ZERO,
+                end_direction: Vec2::X,
             };
 
-            apply_on_ability_end_effects(ChildType::Grenade, &mut fake_context);
+            apply_on_ability_end_effects(&mut fake_context);
         }
 
         assert_eq!(my_actor.status_effects.len(), 1);
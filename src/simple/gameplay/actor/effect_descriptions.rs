@@ -1,4 +1,4 @@
-use super::effect::{ChildType, SerializedEffectTrigger};
+use super::effect::{ChildType, ProjectileModifiers, SerializedEffectTrigger, TriggerType};
 
 
 impl ChildType
@@ -12,11 +12,37 @@ impl ChildType
             ChildType::Missile => "Missile",
             ChildType::Projectile => "Projectile",
             ChildType::Explosion => "Explosion",
-            ChildType::ChildActor => todo!(),
+            ChildType::Boomerang => "Boomerang",
+            ChildType::ChildActor => "Summon",
         }
     }
 }
 
+impl ProjectileModifiers
+{
+    pub fn describe(&self) -> String
+    {
+        let mut parts = Vec::new();
+        if self.pierce > 0
+        {
+            parts.push(format!("pierce {} more enemies", self.pierce));
+        }
+        if self.ricochet > 0
+        {
+            parts.push(format!("ricochet to {} more enemies", self.ricochet));
+        }
+        if self.bounce > 0
+        {
+            parts.push(format!("bounce off walls {} times", self.bounce));
+        }
+        if parts.is_empty()
+        {
+            return "no change".into();
+        }
+        parts.join(", ")
+    }
+}
+
 impl SerializedEffectTrigger
 {
     pub fn describe(&self) -> String
@@ -37,6 +63,29 @@ impl SerializedEffectTrigger
             Self::OnAbilityEnd { ability_type, effect } =>
                 format!("When a {} ability ends (destroyed/finishes): {}", ability_type.name(), effect.describe()),
             Self::OnKill(e) => format!("Upon killing an enemy: {}", e.describe()),
+            Self::ModifyProjectiles { ability_type, modifiers } =>
+                format!("{} abilities {}", ability_type.name(), modifiers.describe()),
+        }
+    }
+}
+
+impl TriggerType
+{
+    pub fn describe(&self) -> &'static str
+    {
+        match self
+        {
+            Self::OnKill => "upon killing an enemy",
+            Self::OnDeath => "upon dying",
+            Self::Periodically => "periodically",
+            Self::OnDoDamage => "that change damage you do",
+            Self::OnDamageDone => "when doing damage",
+            Self::OnReceiveDamage => "that change damage you receive",
+            Self::OnDamageReceived => "when receiving damage",
+            Self::OnAbilityCast => "on casting an ability",
+            Self::OnAbilityHit => "when an ability hits",
+            Self::OnAbilityEnd => "when an ability ends",
+            Self::ModifyProjectiles => "that modify projectiles",
         }
     }
 }
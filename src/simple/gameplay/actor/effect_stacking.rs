@@ -0,0 +1,256 @@
+use super::{
+    effect::*,
+    stats::{StatModification, StatusEffect},
+};
+
+// Stacking scales the magnitude of an effect as if it had been applied `stacks` times:
+// amounts are multiplied by the stacks, factors and powers are raised to them.
+// Shapes (radii, durations, thresholds, periods, etc.) are left as they are
+
+
+impl StatModification
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        match *self
+        {
+            Self::Multiply { factor } => Self::Multiply { factor: factor.powi(stacks as i32) },
+            Self::Add { amount } => Self::Add { amount: amount * stacks as f32 },
+            Self::Exponent { power } => Self::Exponent { power: power.powi(stacks as i32) },
+        }
+    }
+}
+
+impl StatusEffect
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        Self { modification: self.modification.stacked(stacks), ..*self }
+    }
+
+    /// Whether two status effects are the same, ignoring how long they have left
+    pub fn same_as(&self, other: &Self) -> bool
+    {
+        self.stat == other.stat && self.modification == other.modification
+    }
+}
+
+impl ProjectileModifiers
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        Self
+        {
+            pierce: self.pierce * stacks,
+            ricochet: self.ricochet * stacks,
+            bounce: self.bounce * stacks,
+        }
+    }
+}
+
+impl SpawnType
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        let factor = stacks as f32;
+        match *self
+        {
+            Self::Explosion { radius, damage, knockback_strength } => Self::Explosion { radius, damage: damage * factor, knockback_strength },
+            Self::Missile { damage, speed, acceleration, knockback_strength, guidance, turn_rate } =>
+                Self::Missile { damage: damage * factor, speed, acceleration, knockback_strength, guidance, turn_rate },
+            Self::Lightning {  } => Self::Lightning {  },
+            Self::Zone { radius, duration, tick_interval, ref effect, affects } => Self::Zone { radius, duration, tick_interval, effect: Box::new(effect.stacked(stacks)), affects },
+            Self::Summon { kind, health, duration, damage } => Self::Summon { kind, health, duration, damage: damage * factor },
+            Self::BulletSpread { count, spread, speed, damage } => Self::BulletSpread { count, spread, speed, damage: damage * factor },
+        }
+    }
+}
+
+impl SerializedActorEffect
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        match *self
+        {
+            Self::InflictStatusEffect(status) => Self::InflictStatusEffect(status.stacked(stacks)),
+            Self::SpawnEffect(ref spawn_type, location) => Self::SpawnEffect(spawn_type.stacked(stacks), location),
+            Self::AffectHealth(amount) => Self::AffectHealth(amount * stacks as f32),
+            Self::GrantShield(amount) => Self::GrantShield(amount * stacks as f32),
+        }
+    }
+}
+
+impl SerializedDamageChangeEffect
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        match *self
+        {
+            Self::MultiplyDamageEffect { factor } => Self::MultiplyDamageEffect { factor: factor.powi(stacks as i32) },
+            Self::AddDamageEffect { amount } => Self::AddDamageEffect { amount: amount * stacks as f32 },
+        }
+    }
+}
+
+impl SerializedDamageViewEffect
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        match *self
+        {
+            Self::SpawnObjectAt { which_actor, ref spawn_type } => Self::SpawnObjectAt { which_actor, spawn_type: spawn_type.stacked(stacks) },
+            Self::EveryXDamageEffect { accumulated_damage, damage_threshold, which_actor, ref effect } =>
+                Self::EveryXDamageEffect { accumulated_damage, damage_threshold, which_actor, effect: effect.stacked(stacks) },
+            Self::EveryXHealedEffect { accumulated_healing, healing_threshold, which_actor, ref effect } =>
+                Self::EveryXHealedEffect { accumulated_healing, healing_threshold, which_actor, effect: effect.stacked(stacks) },
+            Self::RegularEffect { ref effect } => Self::RegularEffect { effect: effect.stacked(stacks) },
+        }
+    }
+
+    /// This effect without the progress it has made towards its threshold
+    fn without_progress(&self) -> Self
+    {
+        match *self
+        {
+            Self::EveryXDamageEffect { damage_threshold, which_actor, ref effect, .. } =>
+                Self::EveryXDamageEffect { accumulated_damage: 0.0, damage_threshold, which_actor, effect: effect.clone() },
+            Self::EveryXHealedEffect { healing_threshold, which_actor, ref effect, .. } =>
+                Self::EveryXHealedEffect { accumulated_healing: 0.0, healing_threshold, which_actor, effect: effect.clone() },
+            ref other => other.clone(),
+        }
+    }
+}
+
+impl SerializedKillEffect
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        match self
+        {
+            Self::RegularEffect { effect } => Self::RegularEffect { effect: effect.stacked(stacks) },
+        }
+    }
+}
+
+impl SerializedDeathEffect
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        match self
+        {
+            Self::RegularEffect { effect } => Self::RegularEffect { effect: effect.stacked(stacks) },
+        }
+    }
+}
+
+impl SerializedOnHitEffect
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        match self
+        {
+            Self::SpawnEffectAtHitLocation { spawn_type } => Self::SpawnEffectAtHitLocation { spawn_type: spawn_type.stacked(stacks) },
+            Self::RegularEffect { effect } => Self::RegularEffect { effect: effect.stacked(stacks) },
+        }
+    }
+}
+
+impl SerializedAbilityEndEffect
+{
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        match self
+        {
+            Self::SpawnEffectAtEndLocation { spawn_type } => Self::SpawnEffectAtEndLocation { spawn_type: spawn_type.stacked(stacks) },
+            Self::RegularEffect { effect } => Self::RegularEffect { effect: effect.stacked(stacks) },
+        }
+    }
+}
+
+impl SerializedEffectTrigger
+{
+    /// This effect with its magnitude scaled as if it had been gained `stacks` times
+    pub fn stacked(&self, stacks: u32) -> Self
+    {
+        match *self
+        {
+            Self::OnKill(ref effect) => Self::OnKill(effect.stacked(stacks)),
+            Self::OnDeath(ref effect) => Self::OnDeath(effect.stacked(stacks)),
+            Self::Periodically { remaining_period, period, ref effect } => Self::Periodically { remaining_period, period, effect: effect.stacked(stacks) },
+            Self::OnDoDamage(effect) => Self::OnDoDamage(effect.stacked(stacks)),
+            Self::OnDamageDone(ref effect) => Self::OnDamageDone(effect.stacked(stacks)),
+            Self::OnReceiveDamage(effect) => Self::OnReceiveDamage(effect.stacked(stacks)),
+            Self::OnDamageReceived(ref effect) => Self::OnDamageReceived(effect.stacked(stacks)),
+            Self::OnAbilityCast { ability_type, ref effect } => Self::OnAbilityCast { ability_type, effect: effect.stacked(stacks) },
+            Self::OnAbilityHit { ability_type, ref effect } => Self::OnAbilityHit { ability_type, effect: effect.stacked(stacks) },
+            Self::OnAbilityEnd { ability_type, ref effect } => Self::OnAbilityEnd { ability_type, effect: effect.stacked(stacks) },
+            Self::ModifyProjectiles { ability_type, modifiers } => Self::ModifyProjectiles { ability_type, modifiers: modifiers.stacked(stacks) },
+        }
+    }
+
+    /// Whether two effects are the same, ignoring any progress they have made while applied to an actor (timers, accumulated damage, etc.)
+    pub fn same_as(&self, other: &Self) -> bool
+    {
+        self.without_progress() == other.without_progress()
+    }
+
+    fn without_progress(&self) -> Self
+    {
+        match *self
+        {
+            Self::Periodically { period, ref effect, .. } => Self::Periodically { remaining_period: 0.0, period, effect: effect.clone() },
+            Self::OnDamageDone(ref effect) => Self::OnDamageDone(effect.without_progress()),
+            Self::OnDamageReceived(ref effect) => Self::OnDamageReceived(effect.without_progress()),
+            ref other => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use super::super::Stat;
+
+    #[test]
+    fn test_stacking_scales_amounts_and_factors()
+    {
+        let add = SerializedEffectTrigger::OnDoDamage(SerializedDamageChangeEffect::AddDamageEffect { amount: 1.5 });
+        assert_eq!(add.stacked(3), SerializedEffectTrigger::OnDoDamage(SerializedDamageChangeEffect::AddDamageEffect { amount: 4.5 }));
+
+        let multiply = SerializedEffectTrigger::OnDoDamage(SerializedDamageChangeEffect::MultiplyDamageEffect { factor: 2.0 });
+        assert_eq!(multiply.stacked(3), SerializedEffectTrigger::OnDoDamage(SerializedDamageChangeEffect::MultiplyDamageEffect { factor: 8.0 }));
+
+        let explosion = SerializedEffectTrigger::OnAbilityHit
+        {
+            ability_type: ChildType::Projectile,
+            effect: SerializedOnHitEffect::SpawnEffectAtHitLocation { spawn_type: SpawnType::Explosion { radius: 50.0, damage: 2.0, knockback_strength: 10.0 } },
+        };
+        assert_eq!(explosion.stacked(2), SerializedEffectTrigger::OnAbilityHit
+        {
+            ability_type: ChildType::Projectile,
+            effect: SerializedOnHitEffect::SpawnEffectAtHitLocation { spawn_type: SpawnType::Explosion { radius: 50.0, damage: 4.0, knockback_strength: 10.0 } },
+        });
+
+        let speed = StatusEffect { timeout: None, stat: Stat::MovementSpeed, modification: StatModification::Multiply { factor: 1.1 } };
+        let StatModification::Multiply { factor } = speed.stacked(2).modification else { panic!("Stacking changed the modification type"); };
+        assert!((factor - 1.21).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_same_as_ignores_progress()
+    {
+        let effect = SerializedEffectTrigger::OnDamageDone(SerializedDamageViewEffect::EveryXDamageEffect
+        {
+            accumulated_damage: 0.0,
+            damage_threshold: 50.0,
+            which_actor: DamageActor::Instigator,
+            effect: SerializedActorEffect::AffectHealth(10.0),
+        });
+        let SerializedEffectTrigger::OnDamageDone(SerializedDamageViewEffect::EveryXDamageEffect { damage_threshold, which_actor, effect: ref inner, .. }) = effect else { unreachable!() };
+        let progressed = SerializedEffectTrigger::OnDamageDone(SerializedDamageViewEffect::EveryXDamageEffect { accumulated_damage: 32.0, damage_threshold, which_actor, effect: inner.clone() });
+
+        assert!(effect.same_as(&progressed));
+        assert!(!effect.same_as(&effect.stacked(2)));
+    }
+}
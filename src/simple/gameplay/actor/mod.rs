@@ -1,7 +1,10 @@
 mod damage;
 mod dead;
 mod effect_descriptions;
+mod effect_stacking;
 mod simple_effects;
+mod resources;
+mod shield;
 mod stats;
 
 pub mod effect;
@@ -10,6 +13,9 @@ pub use effect::ChildType;
 
 pub use damage::{Damage, DamageKnockback, DamageSource, DamageEvent};
 pub use stats::{Stat, StatusEffect, StatModification, SerializedStat};
+pub use resources::{ActorResources, ResourceCost, ResourceKind};
+pub use dead::s_destroy_dead_things;
+pub use shield::grant_shield;
 
 // Struct that contains all the data useful to an 'affectable' entity
 #[derive(Component, Default, Serialize, Deserialize, Reflect)]
@@ -28,17 +34,6 @@ impl ActorContext
         self.stats.get(stat).map(|x| { *x })
     }
 
-    pub fn get_or_create_stat(&mut self, stat: Stat) -> f32
-    {
-        if let Some(val) = self.stats.get(&stat)
-        {
-            return *val;
-        }
-
-        self.stats.insert(stat, 0.0);
-        0.0
-    }
-
     pub fn set_stat(&mut self, stat: Stat, value: f32)
     {
         self.stats.insert(stat, value);
@@ -53,6 +48,36 @@ pub struct ActorChild // TODO: rename to ActorAbility? Is there a use case for a
     pub ability_type: ChildType
 }
 
+/// Marks an ability object spawned by another ability ending (eg. the bullets of a cluster grenade).
+/// Fragments do not trigger ability end effects, so a cluster can not keep splitting forever
+#[derive(Component)]
+pub struct Fragment;
+
+/// The maximum number of summoners followed when finding the actor responsible for a summon
+const MAX_SUMMON_DEPTH: usize = 8;
+
+/// Finds the actor responsible for the actions of an entity, following summoned actors (`ChildType::ChildActor`) up to their summoner.
+/// Kill credit and on-hit effects of summons go to the actor that summoned them.
+/// `summoner_of` should return the summoner of a summoned actor, and `None` for anything else
+pub fn responsible_actor(entity: Entity, summoner_of: impl Fn(Entity) -> Option<Entity>) -> Entity
+{
+    let mut actor = entity;
+    for _ in 0..MAX_SUMMON_DEPTH
+    {
+        let Some(summoner) = summoner_of(actor) else { break; };
+        actor = summoner;
+    }
+    actor
+}
+
+/// The summoner of an entity, if it is a summoned actor
+pub fn summoner_of(children: &Query<&ActorChild, With<ActorContext>>, entity: Entity) -> Option<Entity>
+{
+    children.get(entity).ok()
+        .filter(|child| child.ability_type == ChildType::ChildActor)
+        .map(|child| child.parent_actor)
+}
+
 /// This component stores the sensor entities (using bevy_rapier sensors) for an actor.
 /// Expected to be attached to the same entity as a ActorContext.
 #[derive(Component, Reflect)]
@@ -76,13 +101,55 @@ impl Plugin for ActorPlugin
             .add_event::<DamageEvent>()
             .replicate::<ActorContext>()
             .replicate::<Damage>()
+            .replicate::<ActorResources>()
             .add_systems(FixedUpdate, (
                 dead::s_destroy_dead_things,
             ).in_set(state::AuthoritySystems).in_set(state::FightingSystems))
             .add_systems(FixedUpdate, (
                 damage::s_do_damage_events,
+                resources::s_gain_resources_on_hit,
             ).in_set(state::AuthoritySystems))
+            .add_systems(FixedUpdate, (
+                resources::s_update_resources,
+                shield::s_update_shields,
+            ).in_set(state::AuthoritySystems).in_set(state::FightingSystems))
             ;
     }
 }
 
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_responsible_actor_follows_summoners()
+    {
+        let player = Entity::from_raw(1);
+        let turret = Entity::from_raw(2);
+        let drone = Entity::from_raw(3);
+        let summoner = |entity: Entity| match entity.index()
+        {
+            2 => Some(player),
+            3 => Some(turret),
+            _ => None,
+        };
+
+        assert_eq!(responsible_actor(player, summoner), player);
+        assert_eq!(responsible_actor(turret, summoner), player);
+        assert_eq!(responsible_actor(drone, summoner), player);
+    }
+
+    #[test]
+    fn test_responsible_actor_stops_on_summon_cycles()
+    {
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+        let summoner = |entity: Entity| Some(if entity == a { b } else { a });
+
+        // Does not loop forever
+        let actor = responsible_actor(a, summoner);
+        assert!(actor == a || actor == b);
+    }
+}
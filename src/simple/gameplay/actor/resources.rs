@@ -0,0 +1,304 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::simple::consts::AMMO_RELOAD_DURATION;
+
+use super::{ActorContext, DamageEvent, Stat};
+
+
+/// The kinds of resource an actor can have, an actor only has a resource if it has the matching [`Stat::MaxResource`] stat
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Hash, PartialEq, Eq, Reflect)]
+pub enum ResourceKind
+{
+    Mana, // Spent by abilities, regenerates over time
+    Ammo, // Spent by abilities, refilled by reloading
+    Heat, // Built up by abilities, abilities can not be used if they would overheat
+    Rage, // Built up by hitting things, spent by abilities
+}
+
+impl ResourceKind
+{
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Mana => "Mana",
+            Self::Ammo => "Ammo",
+            Self::Heat => "Heat",
+            Self::Rage => "Rage",
+        }
+    }
+
+    /// Whether using this resource increases it (up to its max) instead of decreasing it
+    pub fn fills_when_used(&self) -> bool
+    {
+        matches!(self, Self::Heat)
+    }
+
+    /// Whether an actor gains this resource already full
+    pub fn starts_full(&self) -> bool
+    {
+        matches!(self, Self::Mana | Self::Ammo)
+    }
+}
+
+/// The amount of a resource an ability uses
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ResourceCost
+{
+    pub kind: ResourceKind,
+    pub amount: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ResourcePool
+{
+    pub current: f32,
+    pub max: f32,
+    /// Time until a reload finishes, nothing can be spent while reloading
+    pub reload_remaining: Option<f32>,
+}
+
+/// The resources (mana, ammo, etc.) of an actor
+/// Updated on the server, predicted by the local player when they use abilities
+#[derive(Component, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ActorResources
+{
+    pub pools: HashMap<ResourceKind, ResourcePool>,
+}
+
+impl ActorResources
+{
+    pub fn can_afford(&self, cost: &ResourceCost) -> bool
+    {
+        let Some(pool) = self.pools.get(&cost.kind) else { return false; };
+        if pool.reload_remaining.is_some()
+        {
+            return false;
+        }
+        match cost.kind.fills_when_used()
+        {
+            true => pool.current + cost.amount <= pool.max,
+            false => pool.current >= cost.amount,
+        }
+    }
+
+    /// Spends the cost if it can be afforded, returning whether it was
+    pub fn try_spend(&mut self, cost: &ResourceCost) -> bool
+    {
+        if !self.can_afford(cost)
+        {
+            return false;
+        }
+        let Some(pool) = self.pools.get_mut(&cost.kind) else { return false; };
+        match cost.kind.fills_when_used()
+        {
+            true => pool.current += cost.amount,
+            false => pool.current -= cost.amount,
+        }
+        true
+    }
+
+    /// Used by the local player when using an ability: returns whether the cost can be afforded, and spends it if predicting
+    pub fn predict_spend(&mut self, cost: &ResourceCost, should_predict: bool) -> bool
+    {
+        match should_predict
+        {
+            true => self.try_spend(cost),
+            false => self.can_afford(cost),
+        }
+    }
+
+    pub fn gain(&mut self, kind: ResourceKind, amount: f32)
+    {
+        let Some(pool) = self.pools.get_mut(&kind) else { return; };
+        pool.current = (pool.current + amount).clamp(0.0, pool.max);
+    }
+
+    /// Starts reloading the given resource, returns false if it is already reloading or full
+    pub fn start_reload(&mut self, kind: ResourceKind) -> bool
+    {
+        let Some(pool) = self.pools.get_mut(&kind) else { return false; };
+        if pool.reload_remaining.is_some() || pool.current >= pool.max
+        {
+            return false;
+        }
+        pool.reload_remaining = Some(AMMO_RELOAD_DURATION);
+        true
+    }
+
+    /// Adds and removes pools to match the actor's MaxResource stats, new pools start full or empty depending on their kind
+    pub fn sync_with_stats(&mut self, actor: &ActorContext)
+    {
+        self.pools.retain(|kind, _| actor.stats.contains_key(&Stat::MaxResource(*kind)));
+        for (stat, value) in &actor.stats
+        {
+            let Stat::MaxResource(kind) = stat else { continue; };
+            let pool = self.pools.entry(*kind).or_insert_with(|| ResourcePool
+            {
+                current: if kind.starts_full() { *value } else { 0.0 },
+                max: *value,
+                reload_remaining: None,
+            });
+            pool.max = *value;
+            pool.current = pool.current.min(pool.max);
+        }
+    }
+
+    /// Regenerates (or decays) resources and progresses reloads. Ammo automatically reloads once empty
+    pub fn tick(&mut self, actor: &ActorContext, delta_seconds: f32)
+    {
+        for (kind, pool) in &mut self.pools
+        {
+            if let Some(remaining) = &mut pool.reload_remaining
+            {
+                *remaining -= delta_seconds;
+                if *remaining <= 0.0
+                {
+                    pool.reload_remaining = None;
+                    pool.current = pool.max;
+                }
+                continue;
+            }
+            let regen = actor.get_stat(&Stat::ResourceRegen(*kind)).unwrap_or(0.0);
+            pool.current = (pool.current + regen * delta_seconds).clamp(0.0, pool.max);
+
+            if *kind == ResourceKind::Ammo && pool.current <= 0.0
+            {
+                pool.reload_remaining = Some(AMMO_RELOAD_DURATION);
+            }
+        }
+    }
+
+    /// A short summary of every resource, for displaying in the HUD
+    pub fn describe(&self) -> String
+    {
+        let mut pools: Vec<_> = self.pools.iter().collect();
+        pools.sort_by_key(|(kind, _)| kind.name());
+        pools.iter()
+            .map(|(kind, pool)| match pool.reload_remaining
+            {
+                Some(remaining) => format!("{}: reloading ({remaining:.1}s)", kind.name()),
+                None => format!("{}: {:.0}/{:.0}", kind.name(), pool.current, pool.max),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// This system (Authority only) keeps actor resources in line with their stats, and regenerates them
+pub fn s_update_resources(
+    mut actors: Query<(&ActorContext, &mut ActorResources)>,
+    time: Res<Time>,
+) {
+    for (actor, mut resources) in &mut actors
+    {
+        resources.sync_with_stats(actor);
+        resources.tick(actor, time.delta_seconds());
+    }
+}
+
+/// This system (Authority only) gives actors the resources they earn from damaging other actors
+pub fn s_gain_resources_on_hit(
+    mut damage_events: EventReader<DamageEvent>,
+    mut actors: Query<(&ActorContext, &mut ActorResources)>,
+) {
+    for DamageEvent { instigator, victim, damage } in damage_events.read()
+    {
+        if instigator == victim || *damage <= 0.0
+        {
+            continue;
+        }
+        let Ok((actor, mut resources)) = actors.get_mut(*instigator) else { continue; };
+        for (stat, amount) in &actor.stats
+        {
+            let Stat::ResourceOnHit(kind) = stat else { continue; };
+            resources.gain(*kind, *amount);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn actor_with(stats: &[(Stat, f32)]) -> ActorContext
+    {
+        let mut actor = ActorContext::default();
+        for (stat, value) in stats
+        {
+            actor.set_stat(*stat, *value);
+        }
+        actor
+    }
+
+    #[test]
+    fn test_pools_follow_stats()
+    {
+        let actor = actor_with(&[(Stat::MaxResource(ResourceKind::Ammo), 10.0), (Stat::MaxResource(ResourceKind::Heat), 100.0)]);
+        let mut resources = ActorResources::default();
+        resources.sync_with_stats(&actor);
+
+        assert_eq!(resources.pools.get(&ResourceKind::Ammo).map(|p| p.current), Some(10.0));
+        assert_eq!(resources.pools.get(&ResourceKind::Heat).map(|p| p.current), Some(0.0));
+        assert!(resources.pools.get(&ResourceKind::Mana).is_none());
+
+        resources.sync_with_stats(&ActorContext::default());
+        assert!(resources.pools.is_empty());
+    }
+
+    #[test]
+    fn test_spending()
+    {
+        let actor = actor_with(&[(Stat::MaxResource(ResourceKind::Mana), 10.0), (Stat::MaxResource(ResourceKind::Heat), 10.0)]);
+        let mut resources = ActorResources::default();
+        resources.sync_with_stats(&actor);
+
+        assert!(resources.try_spend(&ResourceCost { kind: ResourceKind::Mana, amount: 6.0 }));
+        assert!(!resources.try_spend(&ResourceCost { kind: ResourceKind::Mana, amount: 6.0 }));
+        assert!(resources.try_spend(&ResourceCost { kind: ResourceKind::Heat, amount: 6.0 }));
+        assert!(!resources.try_spend(&ResourceCost { kind: ResourceKind::Heat, amount: 6.0 }));
+        assert!(!resources.try_spend(&ResourceCost { kind: ResourceKind::Rage, amount: 1.0 }));
+    }
+
+    #[test]
+    fn test_regen_and_decay()
+    {
+        let actor = actor_with(&[
+            (Stat::MaxResource(ResourceKind::Mana), 10.0), (Stat::ResourceRegen(ResourceKind::Mana), 2.0),
+            (Stat::MaxResource(ResourceKind::Heat), 10.0), (Stat::ResourceRegen(ResourceKind::Heat), -5.0),
+        ]);
+        let mut resources = ActorResources::default();
+        resources.sync_with_stats(&actor);
+        resources.try_spend(&ResourceCost { kind: ResourceKind::Mana, amount: 10.0 });
+        resources.try_spend(&ResourceCost { kind: ResourceKind::Heat, amount: 10.0 });
+
+        resources.tick(&actor, 1.0);
+        assert_eq!(resources.pools.get(&ResourceKind::Mana).map(|p| p.current), Some(2.0));
+        assert_eq!(resources.pools.get(&ResourceKind::Heat).map(|p| p.current), Some(5.0));
+
+        resources.tick(&actor, 10.0);
+        assert_eq!(resources.pools.get(&ResourceKind::Mana).map(|p| p.current), Some(10.0));
+        assert_eq!(resources.pools.get(&ResourceKind::Heat).map(|p| p.current), Some(0.0));
+    }
+
+    #[test]
+    fn test_ammo_reloads_when_empty()
+    {
+        let actor = actor_with(&[(Stat::MaxResource(ResourceKind::Ammo), 2.0)]);
+        let mut resources = ActorResources::default();
+        resources.sync_with_stats(&actor);
+        let shot = ResourceCost { kind: ResourceKind::Ammo, amount: 1.0 };
+
+        assert!(resources.try_spend(&shot));
+        assert!(resources.try_spend(&shot));
+        resources.tick(&actor, 0.0);
+        assert!(resources.pools.get(&ResourceKind::Ammo).is_some_and(|p| p.reload_remaining.is_some()));
+        assert!(!resources.can_afford(&shot));
+
+        resources.tick(&actor, AMMO_RELOAD_DURATION);
+        assert_eq!(resources.pools.get(&ResourceKind::Ammo).map(|p| p.current), Some(2.0));
+        assert!(resources.can_afford(&shot));
+    }
+}
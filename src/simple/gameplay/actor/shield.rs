@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+
+use super::{ActorContext, Stat};
+
+
+/// Absorbs as much of some damage as the actor's shield can, returning the damage left over for their health.
+/// Healing (negative damage) passes straight through to health
+pub fn absorb_damage(actor: &mut ActorContext, damage: f32) -> f32
+{
+    if damage <= 0.0
+    {
+        return damage;
+    }
+    let shield = actor.get_stat(&Stat::Shield).unwrap_or(0.0);
+    if shield <= 0.0
+    {
+        return damage;
+    }
+    let absorbed = shield.min(damage);
+    actor.set_stat(Stat::Shield, shield - absorbed);
+    damage - absorbed
+}
+
+/// Gives an actor some shield, which can go beyond their max shield (decaying back down to it)
+pub fn grant_shield(actor: &mut ActorContext, amount: f32)
+{
+    let shield = actor.get_stat(&Stat::Shield).unwrap_or(0.0);
+    actor.set_stat(Stat::Shield, (shield + amount).max(0.0));
+}
+
+/// The shield an actor should have after some time, regenerating up to their max shield or decaying down to it.
+/// None if their shield does not change
+pub fn ticked_shield(actor: &ActorContext, delta_seconds: f32) -> Option<f32>
+{
+    let max_shield = actor.get_stat(&Stat::MaxShield).unwrap_or(0.0);
+    // Actors with a max shield start with it full
+    let Some(shield) = actor.get_stat(&Stat::Shield) else { return (max_shield > 0.0).then_some(max_shield); };
+
+    let new_shield = if shield > max_shield
+    {
+        let decay = actor.get_stat(&Stat::ShieldDecay).unwrap_or(0.0);
+        (shield - decay * delta_seconds).max(max_shield)
+    }
+    else
+    {
+        let regen = actor.get_stat(&Stat::ShieldRegen).unwrap_or(0.0);
+        (shield + regen * delta_seconds).min(max_shield)
+    };
+    (new_shield != shield).then_some(new_shield)
+}
+
+/// This system (Authority only) regenerates and decays the shields of actors
+pub fn s_update_shields(
+    mut actors: Query<&mut ActorContext>,
+    time: Res<Time>,
+) {
+    for mut actor in &mut actors
+    {
+        // Only mutably access actors whose shield changes, so other actors are not needlessly re-replicated
+        let Some(new_shield) = ticked_shield(&actor, time.delta_seconds()) else { continue; };
+        actor.set_stat(Stat::Shield, new_shield);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_shield_absorbs_damage_before_health()
+    {
+        let mut actor = ActorContext::default();
+        assert_eq!(absorb_damage(&mut actor, 5.0), 5.0);
+
+        grant_shield(&mut actor, 3.0);
+        assert_eq!(absorb_damage(&mut actor, 2.0), 0.0);
+        assert_eq!(actor.get_stat(&Stat::Shield), Some(1.0));
+        assert_eq!(absorb_damage(&mut actor, 4.0), 3.0);
+        assert_eq!(actor.get_stat(&Stat::Shield), Some(0.0));
+
+        // Healing is not absorbed
+        grant_shield(&mut actor, 3.0);
+        assert_eq!(absorb_damage(&mut actor, -2.0), -2.0);
+        assert_eq!(actor.get_stat(&Stat::Shield), Some(3.0));
+    }
+
+    fn tick_shield(actor: &mut ActorContext, delta_seconds: f32)
+    {
+        if let Some(new_shield) = ticked_shield(actor, delta_seconds)
+        {
+            actor.set_stat(Stat::Shield, new_shield);
+        }
+    }
+
+    #[test]
+    fn test_shield_regenerates_to_max_and_decays_above_it()
+    {
+        let mut actor = ActorContext::default();
+        assert_eq!(ticked_shield(&actor, 1.0), None);
+        actor.set_stat(Stat::MaxShield, 10.0);
+        actor.set_stat(Stat::ShieldRegen, 4.0);
+        actor.set_stat(Stat::ShieldDecay, 2.0);
+
+        tick_shield(&mut actor, 1.0);
+        assert_eq!(actor.get_stat(&Stat::Shield), Some(10.0));
+
+        absorb_damage(&mut actor, 9.0);
+        tick_shield(&mut actor, 1.0);
+        assert_eq!(actor.get_stat(&Stat::Shield), Some(5.0));
+        tick_shield(&mut actor, 10.0);
+        assert_eq!(actor.get_stat(&Stat::Shield), Some(10.0));
+
+        grant_shield(&mut actor, 5.0);
+        tick_shield(&mut actor, 1.0);
+        assert_eq!(actor.get_stat(&Stat::Shield), Some(13.0));
+        tick_shield(&mut actor, 10.0);
+        assert_eq!(actor.get_stat(&Stat::Shield), Some(10.0));
+    }
+}
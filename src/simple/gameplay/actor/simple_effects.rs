@@ -1,12 +1,19 @@
 use bevy::prelude::*;
 use bevy_rapier2d::geometry::CollisionGroups;
 
-use crate::simple::consts::{PLAYER_PROJECTILE_FILTER, PLAYER_PROJECTILE_GROUP, PLAYER_PROJECTILE_GROUPS, RANGED_MAX_MISSILE_ANGULAR_ACCELERATION};
+use crate::simple::consts::{
+    PLAYER_PROJECTILE_FILTER, PLAYER_PROJECTILE_GROUP, PLAYER_PROJECTILE_GROUPS,
+    RANGED_BULLET_COLOR, RANGED_BULLET_SIZE, BULLET_SPREAD_KNOCKBACK, BULLET_SPREAD_LIFETIME,
+};
 
 use super::{
+    ChildType,
     DamageKnockback,
     DamageEvent,
+    Fragment,
+    shield,
     effect_application::{
+        ActorAbilityEndEffectContext,
         ActorDamageEffectContext, 
         ActorEffectContext, 
         ActorOnHitEffectContext, 
@@ -15,6 +22,7 @@ use super::{
     },
     effect::{
         DamageActor,
+        SerializedAbilityEndEffect,
         SerializedActorEffect, 
         SerializedDamageChangeEffect, 
         SerializedDamageViewEffect,
@@ -22,12 +30,19 @@ use super::{
         SerializedDeathEffect,
         SerializedOnHitEffect, 
         SpawnType,
+        SummonKind,
+        ZoneTargets,
     }, 
     stats::StatusEffect,
     super::objects::{
         ExplosionReplicationBundle, 
         Missile, 
         MissileReplicationBundle,
+        Projectile,
+        ProjectileReplicationBundle,
+        SummonReplicationBundle,
+        split_velocities,
+        ZoneReplicationBundle,
     }, 
 };
 
@@ -48,8 +63,11 @@ impl SerializedActorEffect
             Self::InflictStatusEffect(s) => {
                 context.actor.context.status_effects.push(*s);
             },
+            Self::GrantShield(amount) => {
+                shield::grant_shield(context.actor.context, *amount);
+            },
             Self::SpawnEffect(spawn_type, _pos) => {
-                do_spawn_object(context.world_access.commands, *spawn_type, context.actor.location.0, context.actor.entity);
+                do_spawn_object(context.world_access.commands, spawn_type.clone(), SpawnOrigin::at(context.actor.location.0), context.actor.entity);
             }
         }
     }
@@ -59,6 +77,7 @@ impl SerializedActorEffect
         {
             Self::AffectHealth(h) => describe_affect_health_effect(*h),
             Self::InflictStatusEffect(s) => describe_inflict_status_effect(s),
+            Self::GrantShield(amount) => format!("Grant a shield that absorbs {amount} damage"),
             Self::SpawnEffect(spawn_type, _location) => describe_spawn_object(spawn_type),
         }
     }
@@ -142,7 +161,7 @@ impl SerializedDamageViewEffect
             {
                 trace!(obj_actor = ?which_actor, spawn_type = ?spawn_type, "Doing SpawnObjectAt effect");
                 let (world_access, actor) = context.actor_values(*which_actor);
-                do_spawn_object(world_access.commands, *spawn_type, actor.location.0, actor.entity);
+                do_spawn_object(world_access.commands, spawn_type.clone(), SpawnOrigin::at(actor.location.0), actor.entity);
             },
             Self::RegularEffect { effect } =>
             {
@@ -234,7 +253,7 @@ impl SerializedOnHitEffect
             Self::SpawnEffectAtHitLocation { spawn_type } =>
             {
                 trace!(spawn_type = ?spawn_type, "doing spawn effect at hit location");
-                do_spawn_object(context.world_access.commands, *spawn_type, context.hit_location, context.instigator.entity);
+                do_spawn_object(context.world_access.commands, spawn_type.clone(), SpawnOrigin::at(context.hit_location), context.instigator.entity);
             },
             Self::RegularEffect { effect } =>
             {
@@ -253,6 +272,37 @@ impl SerializedOnHitEffect
     }
 }
 
+impl SerializedAbilityEndEffect
+{
+    pub fn apply_effect(&mut self, context: &mut ActorAbilityEndEffectContext)
+    {
+        trace_span!("ability_end_effect");
+        trace!(effect = ?self, "doing ability end effect");
+        match self
+        {
+            Self::SpawnEffectAtEndLocation { spawn_type } =>
+            {
+                trace!(spawn_type = ?spawn_type, "doing spawn effect at end location");
+                let origin = SpawnOrigin { location: context.end_location, direction: context.end_direction, ability_type: Some(context.ability_type) };
+                do_spawn_object(context.world_access.commands, spawn_type.clone(), origin, context.actor.entity);
+            },
+            Self::RegularEffect { effect } =>
+            {
+                trace!(inner_effect = ?effect, "doing regular effect");
+                effect.apply_effect(&mut ActorEffectContext { world_access: context.world_access, actor: context.actor });
+            }
+        }
+    }
+    pub fn describe(&self) -> String
+    {
+        match self
+        {
+            Self::SpawnEffectAtEndLocation { spawn_type } => describe_spawn_object_at(spawn_type, " (where the ability ended)"),
+            Self::RegularEffect { effect } => effect.describe(),
+        }
+    }
+}
+
 
 
 fn describe_damage_factor(factor: f32) -> String {
@@ -282,8 +332,27 @@ fn describe_affect_health_effect(amount: f32) -> String {
     }
 }
 
-pub fn do_spawn_object(commands: &mut Commands, spawn_type: SpawnType, location: Vec2, owner: Entity)
+/// Where (and facing which way) an object is spawned, and the ability that spawned it, if any
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpawnOrigin
+{
+    pub location: Vec2,
+    pub direction: Vec2,
+    pub ability_type: Option<ChildType>,
+}
+
+impl SpawnOrigin
 {
+    /// An origin that is not from an ability, facing right
+    pub fn at(location: Vec2) -> Self
+    {
+        Self { location, direction: Vec2::X, ability_type: None }
+    }
+}
+
+pub fn do_spawn_object(commands: &mut Commands, spawn_type: SpawnType, origin: SpawnOrigin, owner: Entity)
+{
+    let location = origin.location;
     match spawn_type
     {
         SpawnType::Explosion { radius, damage, knockback_strength } => 
@@ -301,10 +370,10 @@ pub fn do_spawn_object(commands: &mut Commands, spawn_type: SpawnType, location:
                 })
             ));
         },
-        SpawnType::Missile { damage, speed, acceleration, knockback_strength } => 
+        SpawnType::Missile { damage, speed, acceleration, knockback_strength, guidance, turn_rate } => 
         {
             commands.spawn(MissileReplicationBundle::new(
-                Missile { max_acceleration: acceleration, max_speed: speed, max_angular_acceleration: RANGED_MAX_MISSILE_ANGULAR_ACCELERATION, owner },
+                Missile { max_acceleration: acceleration, max_speed: speed, ..Missile::from_owner(owner) }.with_guidance(guidance).with_turn_rate(turn_rate),
                 location,
                 Vec2::ZERO,
                 damage,
@@ -312,32 +381,94 @@ pub fn do_spawn_object(commands: &mut Commands, spawn_type: SpawnType, location:
                 Some(DamageKnockback::RepulsionFromSelf { strength: knockback_strength }),
             ));
         },
+        SpawnType::Zone { radius, duration, tick_interval, effect, affects } =>
+        {
+            commands.spawn(ZoneReplicationBundle::new(owner, radius, duration, tick_interval, *effect, affects, location));
+        },
+        SpawnType::Summon { kind, health, duration, damage } =>
+        {
+            commands.spawn(SummonReplicationBundle::new(owner, kind, health, duration, damage, location));
+        },
+        SpawnType::BulletSpread { count, spread, speed, damage } =>
+        {
+            let bullet = Projectile
+            {
+                owner,
+                ability_type: origin.ability_type.unwrap_or(ChildType::Projectile),
+                size: RANGED_BULLET_SIZE,
+                color: RANGED_BULLET_COLOR,
+                lifetime: BULLET_SPREAD_LIFETIME,
+                damage,
+                knockback: BULLET_SPREAD_KNOCKBACK,
+                modifiers: default(),
+            };
+            for velocity in split_velocities(origin.direction.normalize_or_zero() * speed, count, spread)
+            {
+                let mut bullet_commands = commands.spawn(ProjectileReplicationBundle::new(bullet, location, velocity));
+                if origin.ability_type.is_some()
+                {
+                    // Stops bullets spawned by an ability ending from spawning more bullets when they end
+                    bullet_commands.insert(Fragment);
+                }
+            }
+        },
         SpawnType::Lightning {  } => todo!(),
     }
 }
 
 fn describe_spawn_object(spawn_type: &SpawnType) -> String {
-    match spawn_type
-    {
-        SpawnType::Explosion { radius, damage, knockback_strength } =>
-        {
-            format!("Spawns an Explosion with {radius} radius, {damage} damage, and {knockback_strength} knockback")
-        },
-        SpawnType::Lightning {  } => todo!("implement lightning spawning"),
-        SpawnType::Missile { damage, speed, acceleration, knockback_strength } => 
-            format!("Spawns a Missile with speed: {speed} u/s, acceleration: {acceleration} u/s, damage: {damage}, and knockback strength: {knockback_strength}"),
-    }
+    describe_spawn_object_at(spawn_type, "")
 }
 
 fn describe_spawn_at_hit(spawn_type: &SpawnType) -> String {
+    describe_spawn_object_at(spawn_type, " (at the hit point)")
+}
+
+/// Describes spawning an object, with where it is spawned (eg. " (at the hit point)") placed after the object's name
+fn describe_spawn_object_at(spawn_type: &SpawnType, at: &str) -> String {
     match spawn_type
     {
         SpawnType::Explosion { radius, damage, knockback_strength } =>
-            format!("Spawns an Explosion (at the hit point) with {radius} radius, {damage} damage, and {knockback_strength} knockback"),
+            format!("Spawns an Explosion{at} with {radius} radius, {damage} damage, and {knockback_strength} knockback"),
         SpawnType::Lightning {  } => 
             todo!("implement lightning spawning"),
-        SpawnType::Missile { speed, damage, acceleration, knockback_strength } => 
-            format!("Spawns a Missile (at the hit point) with speed: {speed} u/s, acceleration: {acceleration} u/s, damage: {damage}, and knockback strength: {knockback_strength}"),
+        SpawnType::Missile { speed, damage, acceleration, knockback_strength, .. } => 
+            format!("Spawns a Missile{at} with speed: {speed} u/s, acceleration: {acceleration} u/s, damage: {damage}, and knockback strength: {knockback_strength}"),
+        SpawnType::Zone { radius, duration, tick_interval, effect, affects } =>
+            format!("Spawns a Zone{at} with {radius} radius for {duration} seconds, that every {tick_interval} seconds applies an effect to {} inside it that: {}", describe_zone_targets(affects), effect.describe()),
+        SpawnType::Summon { kind, health, duration, damage } =>
+            format!("Summons a {}{at} with {health} health for {duration} seconds{}", describe_summon_kind(kind), describe_summon_damage(kind, *damage)),
+        SpawnType::BulletSpread { count, spread, speed, damage } =>
+            format!("Fires {count} bullets{at} spread over {:.0} degrees, with speed: {speed} u/s and damage: {damage}", spread.to_degrees()),
+    }
+}
+
+fn describe_zone_targets(targets: &ZoneTargets) -> &'static str
+{
+    match targets
+    {
+        ZoneTargets::Enemies => "enemies",
+        ZoneTargets::Allies => "allies",
+        ZoneTargets::Everyone => "everyone",
+    }
+}
+
+fn describe_summon_kind(kind: &SummonKind) -> &'static str
+{
+    match kind
+    {
+        SummonKind::Turret => "Turret",
+        SummonKind::Drone => "Drone",
+        SummonKind::Decoy => "Decoy",
+    }
+}
+
+fn describe_summon_damage(kind: &SummonKind, damage: f32) -> String
+{
+    match kind
+    {
+        SummonKind::Turret | SummonKind::Drone => format!(", that shoots nearby enemies for {damage} damage"),
+        SummonKind::Decoy => ", that draws the attention of nearby enemies".into(),
     }
 }
 
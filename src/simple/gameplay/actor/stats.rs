@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::resources::ResourceKind;
+
 
 // TODO: Confirm this design of stat
 // some alternatives could be: hashmap<str, f32> (stat name indexes a float values of the stats)
@@ -14,6 +16,13 @@ pub enum Stat
     Damage, // A damage stat that scales (almost) all damage
     MovementSpeed, // How many units an actor moves whilst walking per second
     CooldownRate, // How fast a cooldown finishes, total duration will be: normal_duration / CooldownRate
+    MaxResource(ResourceKind), // The max amount of a resource, actors only have resources they have a max for
+    ResourceRegen(ResourceKind), // How much of a resource is gained per second (negative values decay the resource)
+    ResourceOnHit(ResourceKind), // How much of a resource is gained whenever the actor damages something
+    Shield, // The current shield of the actor, which absorbs damage before health
+    MaxShield, // The shield the actor regenerates up to, shield granted beyond this decays
+    ShieldRegen, // How much shield is regenerated per second while below MaxShield
+    ShieldDecay, // How much shield is lost per second while above MaxShield
 }
 
 /// A modification to one of an actor's Stats.
@@ -43,16 +52,23 @@ pub struct SerializedStat
 
 impl Stat
 {
-    pub fn name(&self) -> &str
+    pub fn name(&self) -> String
     {
         match self
         {
-            Stat::Armor => "Armor",
-            Stat::Health => "Health",
-            Stat::CooldownRate => "Cooldown Speed",
-            Stat::MaxHealth => "Maximum Health",
-            Stat::MovementSpeed => "Movement speed",
-            Stat::Damage => "Base Damage",
+            Stat::Armor => "Armor".into(),
+            Stat::Health => "Health".into(),
+            Stat::CooldownRate => "Cooldown Speed".into(),
+            Stat::MaxHealth => "Maximum Health".into(),
+            Stat::MovementSpeed => "Movement speed".into(),
+            Stat::Damage => "Base Damage".into(),
+            Stat::MaxResource(kind) => format!("Maximum {}", kind.name()),
+            Stat::ResourceRegen(kind) => format!("{} Regeneration", kind.name()),
+            Stat::ResourceOnHit(kind) => format!("{} gained on hit", kind.name()),
+            Stat::Shield => "Shield".into(),
+            Stat::MaxShield => "Maximum Shield".into(),
+            Stat::ShieldRegen => "Shield Regeneration".into(),
+            Stat::ShieldDecay => "Shield Decay".into(),
         }
     }
 }
@@ -0,0 +1,554 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+use bevy_rapier2d::{pipeline::QueryFilter, plugin::RapierContext};
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::simple::{
+    gameplay::{
+        objects::{
+            Beam, BeamReplicationBundle, Boomerang, BoomerangReplicationBundle, Laser, LaserReplicationBundle, LocalBeamChannel,
+            MeleeAttackData, MeleeAttackType, MeleeReplicationBundle, Missile, MissileGuidance, MissileReplicationBundle,
+            Projectile, ProjectileBehaviours, ProjectileReplicationBundle,
+        },
+        actor::{
+            ActorContext, ActorResources, ChildType, DamageEvent, DamageKnockback, ResourceCost, ResourceKind,
+            effect_application::{apply_on_ability_cast_effects, ActorEffectContext, ActorReference, EffectContextWorldAccess},
+        },
+        Dead, Knockback, Position, VelocityDamping,
+    },
+    player::{LocalAim, LocalPlayer, LocalPlayerId, Player},
+    data::ClassBaseData,
+    consts::{CLIENT_STR, SERVER_STR, PLAYER_PROJECTILE_GROUPS},
+};
+
+use super::{
+    prediction::{map_prespawned, PredictionMode},
+    validation::{AbilityCheck, AbilityRejection, AbilityValidator},
+    ActorClass, ClassType, Classes, class::AbilityTrigger,
+};
+
+
+/// The object an ability creates when it is used
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum AbilityObject
+{
+    /// A bullet that flies in the aimed direction, optionally slowing down over time, with any other projectile behaviours (homing, splitting, etc.)
+    Bullet{ speed: f32, size: f32, lifetime: f32, color: Color, #[serde(default)] damping: Option<f32>, #[serde(default)] behaviours: ProjectileBehaviours },
+    /// A rectangular melee attack, centred a short distance in front of the caster and facing the aimed direction
+    Stab{ length: f32, width: f32 },
+    /// A circular melee attack around the caster
+    Circle{ radius: f32 },
+    /// A missile that seeks out nearby enemies (or the aimed point, depending on its guidance)
+    Missile{ initial_speed: f32, #[serde(default)] guidance: MissileGuidance, #[serde(default)] turn_rate: Option<f32> },
+    /// A beam channeled for as long as the ability is held (up to its duration), its damage is dealt per second to what it touches
+    Beam{ length: f32, width: f32, color: Color, duration: f32, #[serde(default)] pierce: bool },
+    /// A short lived line from the caster in the aimed direction, cut short at the first enemy in its way
+    Laser{ length: f32, color: Color },
+    /// A boomerang that slows down (deceleration is in u/s^2) until it stops, then flies back to the caster at its return speed
+    Boomerang{ speed: f32, size: f32, deceleration: f32, return_speed: f32 },
+    /// Starts reloading one of the caster's resources, spawns nothing
+    Reload(ResourceKind),
+    /// Switches the caster between their normal and alternate ability modes, spawns nothing
+    ToggleMode,
+    /// Spawns nothing, for abilities that only move the caster (see [`AbilityDefinition::self_knockback`])
+    Nothing,
+}
+
+impl AbilityObject
+{
+    /// The ability type the spawned objects trigger effects as.
+    /// Beams damage as their owner and some abilities spawn nothing, so these have none
+    pub fn ability_type(&self) -> Option<ChildType>
+    {
+        match self
+        {
+            Self::Bullet { .. } | Self::Laser { .. } => Some(ChildType::Projectile),
+            Self::Stab { .. } | Self::Circle { .. } => Some(ChildType::Melee),
+            Self::Missile { .. } => Some(ChildType::Missile),
+            Self::Boomerang { .. } => Some(ChildType::Boomerang),
+            Self::Beam { .. } | Self::Reload(_) | Self::ToggleMode | Self::Nothing => None,
+        }
+    }
+
+    /// Whether using the ability spawns anything, abilities that do not ignore their count
+    pub fn spawns_objects(&self) -> bool
+    {
+        !matches!(self, Self::Reload(_) | Self::ToggleMode | Self::Nothing)
+    }
+}
+
+/// Which mode a player's abilities are in, switched by [`AbilityObject::ToggleMode`] abilities (e.g. equipping the machine gun).
+/// Reset whenever the player changes class
+#[derive(Component, Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AbilityMode
+{
+    pub alternate: bool,
+}
+
+/// Which of the caster's ability modes an ability can be used in
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AbilityModeRequirement
+{
+    #[default]
+    Any,
+    Normal,
+    Alternate,
+}
+
+impl AbilityModeRequirement
+{
+    pub fn allows(&self, mode: AbilityMode) -> bool
+    {
+        match self
+        {
+            Self::Any => true,
+            Self::Normal => !mode.alternate,
+            Self::Alternate => mode.alternate,
+        }
+    }
+}
+
+/// Moves the caster when they use an ability
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SelfKnockback
+{
+    /// Speed along the aimed direction, negative speeds push the caster backwards (recoil)
+    pub speed: f32,
+    pub duration: f32,
+}
+
+impl SelfKnockback
+{
+    pub fn knockback(&self, aim_direction: Vec2) -> Knockback
+    {
+        Knockback::new(aim_direction * self.speed, self.duration, Knockback::DEFAULT_CONTROL_POINTS)
+    }
+}
+
+fn default_count() -> u32
+{
+    1
+}
+
+/// Describes an ability entirely in data, loaded as part of a class's [`ClassBaseData`]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AbilityDefinition
+{
+    pub name: String,
+    pub trigger: AbilityTrigger,
+    pub object: AbilityObject,
+    /// Damage dealt by each object, or per second for beams
+    pub damage: f32,
+    #[serde(default)]
+    pub knockback: f32,
+    /// How many objects to spawn each use
+    #[serde(default = "default_count")]
+    pub count: u32,
+    /// The total angle (in degrees) the objects are spread across, when count is above 1
+    #[serde(default)]
+    pub spread_degrees: f32,
+    /// Seconds before the ability can be used again, enforced by the server
+    #[serde(default)]
+    pub cooldown: f32,
+    /// The resource the ability uses, if any
+    #[serde(default)]
+    pub cost: Option<ResourceCost>,
+    /// Moves the caster when the ability is used (dashes, recoil)
+    #[serde(default)]
+    pub self_knockback: Option<SelfKnockback>,
+    /// Which ability mode the caster must be in to use the ability
+    #[serde(default)]
+    pub mode: AbilityModeRequirement,
+    /// The ability type cast effects (and the hit effects of bullets) trigger as, instead of the object's own (e.g. a bullet thrown as a grenade)
+    #[serde(default)]
+    pub ability_type: Option<ChildType>,
+}
+
+impl AbilityDefinition
+{
+    /// The ability type this ability's effects trigger as, if it has one
+    pub fn ability_type(&self) -> Option<ChildType>
+    {
+        self.ability_type.or(self.object.ability_type())
+    }
+
+    /// The direction of every object this ability spawns, evenly spread around the aimed direction
+    pub fn directions(&self, aim_direction: Vec2) -> Vec<Vec2>
+    {
+        if !self.object.spawns_objects()
+        {
+            return Vec::new();
+        }
+        if self.count <= 1
+        {
+            return vec![aim_direction];
+        }
+        let spread = self.spread_degrees.to_radians();
+        let step = spread / (self.count - 1) as f32;
+        (0..self.count)
+            .map(|index| (Quat::from_rotation_z(-spread * 0.5 + step * index as f32) * aim_direction.extend(0.0)).truncate())
+            .collect()
+    }
+
+    /// How many objects each use of the ability spawns
+    pub fn spawn_count(&self) -> usize
+    {
+        if self.object.spawns_objects() { self.count as usize } else { 0 }
+    }
+}
+
+/// Sent locally by the class input system when a data defined ability should be used
+#[derive(Event)]
+pub struct TriggerDataAbility
+{
+    pub ability: usize,
+}
+
+/// Sent from clients to the server to use the data defined ability at the given index of their class
+#[derive(Event, Serialize, Deserialize, Debug)]
+pub struct DataAbilityEvent
+{
+    pub ability: usize,
+    pub dir: Vec2,
+    pub prespawned: Vec<Entity>,
+}
+
+/// The cooldowns of the local player's abilities, keyed by ability name.
+/// This stops clients from sending (and predicting) abilities faster than they are meant to be used, e.g. when holding down a fast firing ability
+#[derive(Resource, Default)]
+pub struct LocalAbilityCooldowns
+{
+    ready_at: HashMap<String, f32>,
+}
+
+impl LocalAbilityCooldowns
+{
+    /// Starts the ability's cooldown if it is ready, returns false if it is still cooling down
+    pub fn try_use(&mut self, ability: &AbilityDefinition, now: f32) -> bool
+    {
+        if self.ready_at.get(&ability.name).is_some_and(|ready_at| *ready_at > now)
+        {
+            return false;
+        }
+        if ability.cooldown > 0.0
+        {
+            self.ready_at.insert(ability.name.clone(), now + ability.cooldown);
+        }
+        true
+    }
+}
+
+/// The classes and their loaded class data, for looking up the abilities of a class
+#[derive(SystemParam)]
+pub struct ClassAbilities<'w>
+{
+    classes: Res<'w, Classes>,
+    class_datas: Res<'w, Assets<ClassBaseData>>,
+}
+
+impl<'w> ClassAbilities<'w>
+{
+    pub fn get(&self, class: &ClassType) -> Option<&ClassBaseData>
+    {
+        self.classes.classes.get(class).and_then(|c| self.class_datas.get(&c.base_data))
+    }
+}
+
+/// Type alias for the parts of the local player that data defined abilities use
+type LocalCasterQuery<'w, 's> = Query<'w, 's, (&'static GlobalTransform, &'static ActorClass, &'static mut ActorResources, &'static mut Knockback, Option<&'static mut AbilityMode>), With<LocalPlayer>>;
+
+/// The local player, where they are aiming, what their abilities are aimed into, and when their abilities can next be used
+#[derive(SystemParam)]
+pub struct LocalCaster<'w, 's>
+{
+    player: LocalCasterQuery<'w, 's>,
+    id: Res<'w, LocalPlayerId>,
+    aim: Res<'w, LocalAim>,
+    rapier_context: Res<'w, RapierContext>,
+    cooldowns: ResMut<'w, LocalAbilityCooldowns>,
+    time: Res<'w, Time>,
+}
+
+/// Spawns every object of an ability, this is used by both client prediction and the server response
+pub fn spawn_ability_objects(
+    commands: &mut Commands,
+    rapier_context: &RapierContext,
+    ability: &AbilityDefinition,
+    owner: Entity,
+    owning_client: ClientId,
+    position: Vec2,
+    aim_direction: Vec2,
+) -> Vec<Entity> {
+    let mut entities = Vec::with_capacity(ability.spawn_count());
+    for direction in ability.directions(aim_direction)
+    {
+        let entity = match ability.object
+        {
+            AbilityObject::Bullet { speed, size, lifetime, color, damping, behaviours } =>
+            {
+                let projectile = Projectile
+                {
+                    owner,
+                    ability_type: ability.ability_type().unwrap_or(ChildType::Projectile),
+                    size,
+                    color,
+                    lifetime,
+                    damage: ability.damage,
+                    knockback: ability.knockback,
+                    modifiers: default(),
+                };
+                let mut ent_coms = commands.spawn(ProjectileReplicationBundle::new(projectile, position, direction * speed));
+                if let Some(damping) = damping
+                {
+                    ent_coms.insert(VelocityDamping(damping));
+                }
+                behaviours.insert(&mut ent_coms, owner, direction);
+                ent_coms.id()
+            },
+            AbilityObject::Stab { length, width } => commands.spawn(MeleeReplicationBundle::new(MeleeAttackData
+                {
+                    owning_client,
+                    owner,
+                    damage: ability.damage,
+                    position,
+                    direction,
+                    attack_type: MeleeAttackType::Stab { direction, position, length, width },
+                })).id(),
+            AbilityObject::Circle { radius } => commands.spawn(MeleeReplicationBundle::new(MeleeAttackData
+                {
+                    owning_client,
+                    owner,
+                    damage: ability.damage,
+                    position,
+                    direction: Vec2::ZERO,
+                    attack_type: MeleeAttackType::Circular { position, radius },
+                })).id(),
+            AbilityObject::Missile { initial_speed, guidance, turn_rate } => commands.spawn(MissileReplicationBundle::new(
+                    Missile::from_owner(owner).with_guidance(guidance).with_turn_rate(turn_rate),
+                    position,
+                    direction * initial_speed,
+                    ability.damage,
+                    PLAYER_PROJECTILE_GROUPS,
+                    Some(DamageKnockback::RepulsionFromSelf { strength: ability.knockback }),
+                )).id(),
+            AbilityObject::Beam { length, width, color, duration, pierce } => commands.spawn(BeamReplicationBundle::new(
+                    Beam { owner, owning_client, color, width, length, duration, damage_per_second: ability.damage, pierce, direction },
+                    position,
+                    PLAYER_PROJECTILE_GROUPS,
+                )).id(),
+            AbilityObject::Laser { length, color } =>
+            {
+                let filter = QueryFilter::new()
+                    .groups(PLAYER_PROJECTILE_GROUPS)
+                    .exclude_sensors();
+                let length = rapier_context.cast_ray(position, direction, length, true, filter).map_or(length, |(_, toi)| toi);
+                commands.spawn(LaserReplicationBundle::new(
+                    Laser { owner, color, length, direction, knockback: ability.knockback },
+                    position,
+                    ability.damage,
+                    PLAYER_PROJECTILE_GROUPS,
+                )).id()
+            },
+            AbilityObject::Boomerang { speed, size, deceleration, return_speed } => commands.spawn(BoomerangReplicationBundle::new(
+                    Boomerang { owner, size, damage: ability.damage, knockback: ability.knockback, deceleration, return_speed, returning: false },
+                    position,
+                    direction * speed,
+                )).id(),
+            AbilityObject::Reload(_) | AbilityObject::ToggleMode | AbilityObject::Nothing => continue,
+        };
+        entities.push(entity);
+    }
+    entities
+}
+
+/// Client side system that uses data defined abilities triggered by the class input system
+pub fn c_data_abilities(
+    mut commands: Commands,
+    mut triggers: EventReader<TriggerDataAbility>,
+    mut caster: LocalCaster,
+    class_abilities: ClassAbilities,
+    prediction: Res<PredictionMode>,
+    mut beam_channel: ResMut<LocalBeamChannel>,
+    mut ability_events: EventWriter<DataAbilityEvent>,
+) {
+    if triggers.is_empty()
+    {
+        return;
+    }
+    let Ok((player_trans, class, mut resources, mut knockback, mut mode)) = caster.player.get_single_mut() else { triggers.clear(); return; };
+    let Some(class_data) = class_abilities.get(class.get_class()) else { triggers.clear(); return; };
+    let player_pos = player_trans.translation().truncate();
+    let Some(dir) = caster.aim.direction else { triggers.clear(); return; };
+
+    for TriggerDataAbility { ability } in triggers.read()
+    {
+        let Some(definition) = class_data.abilities.get(*ability) else { continue; };
+        if !definition.mode.allows(mode.as_deref().copied().unwrap_or_default())
+        {
+            continue;
+        }
+        if !caster.cooldowns.try_use(definition, caster.time.elapsed_seconds())
+        {
+            continue;
+        }
+        if definition.cost.is_some_and(|cost| !resources.predict_spend(&cost, prediction.should_predict()))
+        {
+            debug!("{CLIENT_STR} Can not afford ability '{}'", definition.name);
+            continue;
+        }
+        if let AbilityObject::Reload(kind) = definition.object
+        {
+            if prediction.should_predict() && !resources.start_reload(kind)
+            {
+                continue;
+            }
+        }
+        info!("{CLIENT_STR} Using ability '{}'", definition.name);
+        if matches!(definition.object, AbilityObject::Beam { .. })
+        {
+            // The beam is channeled for as long as whatever used it is held
+            beam_channel.start(definition.trigger.action());
+        }
+
+        prediction.predict(|| {
+            if let Some(self_knockback) = definition.self_knockback
+            {
+                *knockback = self_knockback.knockback(dir);
+            }
+            if let (AbilityObject::ToggleMode, Some(mode)) = (definition.object, mode.as_mut())
+            {
+                mode.alternate = !mode.alternate;
+            }
+        });
+
+        let prespawned = prediction.predict_spawn(&mut commands, |commands| spawn_ability_objects(commands, &caster.rapier_context, definition, caster.id.entity, ClientId::new(caster.id.id), player_pos, dir))
+            .unwrap_or_default();
+
+        ability_events.send(DataAbilityEvent { ability: *ability, dir, prespawned });
+    }
+}
+
+/// Type alias for the living players that can use abilities
+type AbilityUsers<'w, 's> = Query<'w, 's, (Entity, &'static Player, &'static mut Position, &'static mut ActorContext, &'static ActorClass, &'static mut Knockback, &'static mut AbilityMode), Without<Dead>>;
+
+/// What the server needs to spawn the objects of abilities and apply their cast effects
+#[derive(SystemParam)]
+pub struct AbilityWorld<'w>
+{
+    rapier_context: Res<'w, RapierContext>,
+    damage_events: EventWriter<'w, DamageEvent>,
+}
+
+/// Server side system that responds to the data defined abilities used by clients, spawning their objects and applying them to the caster
+pub fn s_data_ability_response(
+    mut commands: Commands,
+    mut client_events: EventReader<FromClient<DataAbilityEvent>>,
+    mut client_map: ResMut<ClientEntityMap>,
+    mut players: AbilityUsers,
+    class_abilities: ClassAbilities,
+    mut world: AbilityWorld,
+    mut validator: AbilityValidator,
+) {
+    let mut damage_instances = Vec::new();
+    for FromClient { client_id, event } in client_events.read()
+    {
+        let Some((player_ent, _, mut position, mut actor, class, mut knockback, mut mode)) = players.iter_mut().find(|(_, player, ..)| player.0 == *client_id) else { continue; };
+        let Some(class_data) = class_abilities.get(class.get_class()) else { continue; };
+        let Some(definition) = class_data.abilities.get(event.ability) else
+        {
+            warn!("{SERVER_STR} Client '{}' used ability {} which their class ({}) does not have", client_id.get(), event.ability, class);
+            validator.reject(*client_id, "Data ability", AbilityRejection::WrongClass);
+            continue;
+        };
+        if !definition.mode.allows(*mode)
+        {
+            validator.reject(*client_id, &definition.name, AbilityRejection::WrongMode);
+            continue;
+        }
+        let check = AbilityCheck::new(&definition.name)
+            .with_direction(event.dir)
+            .with_cooldown(definition.cooldown)
+            .with_cost(definition.cost)
+            .with_prespawned(&event.prespawned, definition.spawn_count());
+        let Ok(dir) = validator.validate(*client_id, player_ent, check) else { continue; };
+        info!("{SERVER_STR} Client '{}' used ability '{}'", client_id.get(), definition.name);
+
+        if let Some(self_knockback) = definition.self_knockback
+        {
+            *knockback = self_knockback.knockback(dir);
+        }
+        match definition.object
+        {
+            AbilityObject::Reload(kind) =>
+            {
+                if let Some(mut resources) = validator.resources_mut(player_ent)
+                {
+                    resources.start_reload(kind);
+                }
+            },
+            AbilityObject::ToggleMode => mode.alternate = !mode.alternate,
+            _ => {},
+        }
+
+        let server_entities = spawn_ability_objects(&mut commands, &world.rapier_context, definition, player_ent, *client_id, position.0, dir);
+        map_prespawned(&mut client_map, *client_id, &server_entities, &event.prespawned);
+
+        if let Some(ability_type) = definition.ability_type()
+        {
+            apply_on_ability_cast_effects(ability_type, &mut ActorEffectContext {
+                world_access: &mut EffectContextWorldAccess { commands: &mut commands, damage_instances: &mut damage_instances },
+                actor: &mut ActorReference { entity: player_ent, context: &mut actor, location: &mut position },
+            });
+        }
+    }
+    world.damage_events.send_batch(damage_instances);
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn ability(object: &str, extra: &str) -> AbilityDefinition
+    {
+        ron::de::from_str(&format!("(name: \"Test\", trigger: JustPressed(Attack), object: {object}, damage: 1.0{extra})"))
+            .expect("could not deserialize an ability")
+    }
+
+    #[test]
+    fn test_abilities_that_spawn_nothing_ignore_count()
+    {
+        let missiles = ability("Missile(initial_speed: 50.0)", ", count: 4, spread_degrees: 90.0");
+        assert_eq!(missiles.spawn_count(), 4);
+        assert_eq!(missiles.directions(Vec2::X).len(), 4);
+
+        let dash = ability("Nothing", ", count: 4, self_knockback: Some((speed: 350.0, duration: 0.3))");
+        assert_eq!(dash.spawn_count(), 0);
+        assert!(dash.directions(Vec2::X).is_empty());
+        assert_eq!(dash.self_knockback, Some(SelfKnockback { speed: 350.0, duration: 0.3 }));
+    }
+
+    #[test]
+    fn test_mode_requirements()
+    {
+        let normal = AbilityMode::default();
+        let alternate = AbilityMode { alternate: true };
+        assert!(AbilityModeRequirement::Any.allows(normal) && AbilityModeRequirement::Any.allows(alternate));
+        assert!(AbilityModeRequirement::Normal.allows(normal) && !AbilityModeRequirement::Normal.allows(alternate));
+        assert!(!AbilityModeRequirement::Alternate.allows(normal) && AbilityModeRequirement::Alternate.allows(alternate));
+        assert_eq!(ability("ToggleMode", "").mode, AbilityModeRequirement::Any);
+    }
+
+    #[test]
+    fn test_local_cooldowns()
+    {
+        let mut cooldowns = LocalAbilityCooldowns::default();
+        let machine_gun = ability("Nothing", ", cooldown: 0.1");
+        let free = ability("Nothing", "");
+        assert!(cooldowns.try_use(&machine_gun, 1.0));
+        assert!(!cooldowns.try_use(&machine_gun, 1.05));
+        assert!(cooldowns.try_use(&machine_gun, 1.1));
+        assert!(cooldowns.try_use(&free, 1.1));
+        assert!(cooldowns.try_use(&free, 1.1));
+    }
+}
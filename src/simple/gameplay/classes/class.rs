@@ -1,59 +1,119 @@
-use std::{
-    error::Error, fmt::Display, sync::{Arc, Mutex}
-};
+use std::{borrow::Cow, fmt::Display};
 
-use bevy::{asset::{AssetLoader, AsyncReadExt}, ecs::system::SystemId, prelude::*, utils::HashMap};
+use bevy::{prelude::*, utils::HashMap};
 use serde::{Deserialize, Serialize};
 
 use crate::simple::{
     gameplay::actor::ActorContext,
-    data::ClassBaseData,
+    player::{ActionInput, SimpleAction},
+    data::{ClassBaseData, TalentTree},
 };
 
+use super::ability::AbilityMode;
+
 
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AbilityTrigger
 {
-    JustPressed(KeyCode),
-    HeldDown(KeyCode),
-    JustPressedOrReleased(KeyCode),
-    JustReleased(KeyCode),
+    JustPressed(SimpleAction),
+    HeldDown(SimpleAction),
+    JustPressedOrReleased(SimpleAction),
+    JustReleased(SimpleAction),
 }
 
-pub struct LabelledSystemId
+impl AbilityTrigger
 {
-    pub name: String,
-    pub system_id: SystemId
+    pub fn is_triggered(&self, input: &ActionInput) -> bool
+    {
+        match self
+        {
+            Self::JustPressed(action) => input.just_pressed(*action),
+            Self::HeldDown(action) => input.pressed(*action),
+            Self::JustPressedOrReleased(action) => input.just_pressed(*action) || input.just_released(*action),
+            Self::JustReleased(action) => input.just_released(*action),
+        }
+    }
+
+    /// The action this trigger watches
+    pub fn action(&self) -> SimpleAction
+    {
+        match self
+        {
+            Self::JustPressed(action) | Self::HeldDown(action) | Self::JustPressedOrReleased(action) | Self::JustReleased(action) => *action,
+        }
+    }
 }
 
-pub struct Class
+/// How a class is presented in the class select UI
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ClassUiMetadata
 {
-    pub setup_fn: Option<Arc<Mutex<dyn FnMut(&mut Commands, Entity) + Sync + Send>>>,
-    pub teardown_fn: Option<Arc<Mutex<dyn FnMut(&mut Commands, Entity) + Sync + Send>>>,
-    pub abilities: HashMap<AbilityTrigger, LabelledSystemId>,
-    pub base_data: Handle<ClassBaseData>,
+    /// Classes are shown in ascending order
+    #[serde(default)]
+    pub order: i32,
+    #[serde(default = "ClassUiMetadata::default_color")]
+    pub color: Color,
 }
 
-#[derive(PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Clone, Copy)]
-pub enum ClassType
+impl ClassUiMetadata
 {
-    DefaultClass,
-    MeleeClass,
-    RangedClass,
+    fn default_color() -> Color
+    {
+        Color::GRAY
+    }
 }
 
+impl Default for ClassUiMetadata
+{
+    fn default() -> Self {
+        Self { order: 0, color: Self::default_color() }
+    }
+}
+
+/// A class from the class manifest, everything about it (including its abilities) is in its data
+pub struct Class
+{
+    pub base_data: Handle<ClassBaseData>,
+    pub ui: ClassUiMetadata,
+    pub talents: Option<Handle<TalentTree>>,
+}
+
+/// Identifies a class, these are the ids in the class manifest (assets/classes.manifest)
+#[derive(PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Clone, PartialOrd, Ord, Reflect)]
+#[serde(transparent)]
+pub struct ClassType(Cow<'static, str>);
+
 #[derive(Component, Serialize, Deserialize)]
 pub struct ActorClass
 {
     class: ClassType,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct Classes
 {
     pub classes: HashMap<ClassType, Class>,
 }
 
+impl Classes
+{
+    /// Every class, in the order they should be shown to players
+    pub fn class_types(&self) -> Vec<ClassType>
+    {
+        let mut types: Vec<_> = self.classes.iter().map(|(class_type, class)| (class.ui.order, class_type.clone())).collect();
+        types.sort();
+        types.into_iter().map(|(_, class_type)| class_type).collect()
+    }
+
+    /// The class after this one in the order they are shown to players, wrapping around to the first
+    pub fn next_class(&self, class: &ClassType) -> Option<ClassType>
+    {
+        let class_cycle = self.class_types();
+        let index = class_cycle.iter().position(|x| x == class)?;
+        Some(class_cycle[(index + 1) % class_cycle.len()].clone())
+    }
+}
+
 impl Display for ActorClass { 
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(format!("{}", self.class).as_str())
@@ -62,20 +122,40 @@ impl Display for ActorClass {
 impl Display for ClassType
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self
-        {
-            ClassType::DefaultClass => f.write_str("Default"),
-            ClassType::MeleeClass => f.write_str("Melee"),
-            ClassType::RangedClass => f.write_str("Ranged"),
-        }
+        f.write_str(&self.0)
     }
 }
 
 impl ClassType
 {
-    pub fn all_class_types() -> [Self; 3]
+    pub const MELEE: Self = Self::from_static("melee");
+    #[cfg(test)]
+    pub const RANGED: Self = Self::from_static("ranged");
+
+    pub const fn from_static(id: &'static str) -> Self
     {
-        [ClassType::DefaultClass, ClassType::MeleeClass, ClassType::RangedClass]
+        Self(Cow::Borrowed(id))
+    }
+}
+
+/// Why a player's class could not be changed, their previous class is left as it was
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClassChangeError
+{
+    /// The class is not in the manifest
+    UnknownClass(ClassType),
+    /// The class's data has not loaded (or failed to)
+    DataNotLoaded(ClassType),
+}
+
+impl Display for ClassChangeError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            Self::UnknownClass(class) => write!(f, "unknown class '{class}'"),
+            Self::DataNotLoaded(class) => write!(f, "the data of class '{class}' is not loaded"),
+        }
     }
 }
 
@@ -88,66 +168,48 @@ impl ActorClass
         }
     }
 
-    pub fn get_class(&self) -> ClassType
+    pub fn get_class(&self) -> &ClassType
     {
-        self.class
+        &self.class
     }
 
-    pub fn set_class(&mut self, commands: &mut Commands, class_data: &Assets<ClassBaseData>, classes: &mut Classes, actor: &mut ActorContext, entity: Entity, new_class: ClassType)
+    /// Checks the new class can be used before changing anything, so a failed change leaves the actor as it was
+    pub fn set_class(&mut self, commands: &mut Commands, class_data: &Assets<ClassBaseData>, classes: &Classes, actor: &mut ActorContext, entity: Entity, new_class: ClassType) -> Result<(), ClassChangeError>
     {
-        self.set_class_internal(commands, class_data, classes, actor, entity, new_class, true);
-    }
+        let Some(new_class_base) = classes.classes.get(&new_class) else { return Err(ClassChangeError::UnknownClass(new_class)); };
+        let Some(dat) = class_data.get(&new_class_base.base_data) else { return Err(ClassChangeError::DataNotLoaded(new_class)); };
 
-    fn set_class_internal(&mut self, commands: &mut Commands, class_data: &Assets<ClassBaseData>, classes: &mut Classes, actor: &mut ActorContext, entity: Entity, new_class: ClassType, teardown: bool)
-    {
-        if teardown
-        {
-            if let Some(class_data) = classes.classes.get_mut(&self.class)
-            {
-                if let Some(tr_dwn) = &mut class_data.teardown_fn
-                {
-                    if let Ok(mut real_tr_dwn) = tr_dwn.lock()
-                    {
-                        real_tr_dwn(commands, entity);
-                    }
-                }
-            }
-        }
-        let Some(new_class_data) = classes.classes.get_mut(&new_class) else { error!("Can not change to class {:?}!", new_class); return; };
-
-        if let Some(setup_fn_mute) = &mut new_class_data.setup_fn
-        {
-            if let Ok(mut setup_fn) = setup_fn_mute.lock()
-            {
-                setup_fn(commands, entity);
-            }
-        }
+        commands.entity(entity).insert(AbilityMode::default());
 
         actor.stats.clear();
         actor.effects.clear();
-        let dat = class_data.get(&new_class_data.base_data).unwrap();
+        actor.status_effects.clear();
         for base_stat in &dat.stats
         {
             actor.stats.insert(base_stat.stat, base_stat.value);
         }
         for base_effect in &dat.effects
         {
-            actor.effects.push(*base_effect);
+            actor.effects.push(base_effect.clone());
         }
         self.class = new_class;
+        Ok(())
     }
 }
 
 pub fn s_setup_initial_class(
     mut commands: Commands,
     class_datas: Res<Assets<ClassBaseData>>,
-    mut classes: ResMut<Classes>,
+    classes: Res<Classes>,
     mut new_ents: Query<(Entity, &mut ActorContext, &mut ActorClass), Added<ActorClass>>,
 ) {
     for (entity, mut actor, mut class) in &mut new_ents
     {
-        let c = class.class;
-        class.set_class_internal(&mut commands, &class_datas, &mut classes, &mut actor, entity, c, false);
+        let c = class.class.clone();
+        if let Err(e) = class.set_class(&mut commands, &class_datas, &classes, &mut actor, entity, c)
+        {
+            error!("Could not set up the initial class of {entity:?}: {e}");
+        }
     }
 }
 
@@ -172,7 +234,12 @@ mod tests
 
     use crate::simple::gameplay::actor::{effect::{SerializedEffectTrigger, SerializedDamageChangeEffect}, Stat, SerializedStat};
 
-    use super::ClassBaseData;
+    use crate::simple::player::SimpleAction;
+
+    use crate::simple::{data::ClassManifest, consts::CLASS_MANIFEST_PATH};
+
+    use super::{AbilityTrigger, ClassBaseData, ClassType, ClassUiMetadata};
+    use super::super::ability::{AbilityDefinition, AbilityObject};
 
     const TEST_FILE_PATH: &str = "cargo_test_file.cbd";
 
@@ -185,6 +252,21 @@ mod tests
             stats: vec![SerializedStat{ stat: Stat::Health, value: 100.0 }],
             description: "Test Stuff".into(),
             name: "Test Class".into(),
+            abilities: vec![AbilityDefinition 
+            { 
+                name: "Test Ability".into(), 
+                trigger: AbilityTrigger::JustPressed(SimpleAction::Attack), 
+                object: AbilityObject::Stab { length: 15.0, width: 5.0 }, 
+                damage: 1.0, 
+                knockback: 0.0, 
+                count: 1, 
+                spread_degrees: 0.0,
+                cooldown: 0.0,
+                cost: None,
+                self_knockback: None,
+                mode: Default::default(),
+                ability_type: None,
+            }],
         };
 
         let mut f = File::create(TEST_FILE_PATH).unwrap();
@@ -214,6 +296,7 @@ mod tests
             stats: vec![SerializedStat{ stat: Stat::Health, value: 100.0 }, SerializedStat{ stat: Stat::MaxHealth, value: 100.0 }],
             description: "Test Stuff".into(),
             name: "Test Class".into(),
+            abilities: Vec::new(),
         };
 
         let f = File::create("melee_data_out.cbd").unwrap();
@@ -237,4 +320,22 @@ mod tests
 
         ron::ser::to_writer(&f, &base_data).expect("failed to serialize melee data");
     }
+
+    #[test]
+    fn test_class_manifest()
+    {
+        let contents = std::fs::read_to_string(format!("assets/{CLASS_MANIFEST_PATH}")).expect("could not read the class manifest");
+        let manifest = ron::de::from_str::<ClassManifest>(&contents).expect("could not deserialize the class manifest");
+
+        // More classes can be dropped into the manifest, but the shipped ones must stay
+        for id in [ClassType::from_static("default"), ClassType::MELEE, ClassType::RANGED]
+        {
+            assert!(manifest.classes.iter().any(|entry| entry.id == id), "{id} is missing from the manifest");
+        }
+
+        // Entries only need an id and data, so a modded class can be as small as this
+        let modded = ron::de::from_str::<ClassManifest>(r#"(classes: [(id: "necromancer", data: "mods/necromancer.cbd")])"#).expect("could not deserialize a minimal manifest");
+        assert_eq!(modded.classes[0].id, ClassType::from_static("necromancer"));
+        assert_eq!(modded.classes[0].ui, ClassUiMetadata::default());
+    }
 }
@@ -1,17 +1,19 @@
 
+mod ability;
 mod class;
-mod default_class;
-mod melee_class;
-mod ranged_class;
+mod prediction;
 mod setup;
+mod validation;
 
-pub use class::{ActorClass, Class, ClassType, Classes};
-pub use setup::setup_classes;
+pub use ability::{AbilityDefinition, AbilityObject};
+pub use class::{ActorClass, ClassType, ClassUiMetadata, Classes, s_setup_initial_class};
+pub use prediction::PredictionMode;
+pub use setup::{build_classes_from_manifest, setup_classes};
 
 use bevy::prelude::*;
 use bevy_replicon::prelude::*;
 
-use crate::simple::state;
+use crate::simple::{player, state};
 
 pub struct ClassesPlugin;
 
@@ -20,23 +22,29 @@ impl Plugin for ClassesPlugin
     fn build(&self, app: &mut App) {
         app
             // Data v
-            .replicate::<ranged_class::RangedClassData>()
+            .init_resource::<PredictionMode>()
+            .init_resource::<ability::LocalAbilityCooldowns>()
+            .replicate::<ability::AbilityMode>()
             .replicate::<ActorClass>()
 
             // Events v
-            .add_client_event::<default_class::DefaultClassAbility>(ChannelKind::Ordered)
-            .add_client_event::<melee_class::MeleeClassEvent>(ChannelKind::Ordered)
-            .add_client_event::<ranged_class::RangedClassEvent>(ChannelKind::Ordered)
+            .add_event::<ability::TriggerDataAbility>()
+            .add_client_event::<ability::DataAbilityEvent>(ChannelKind::Ordered)
+            .init_resource::<validation::AbilityRejections>()
             // Systems v
+            .add_systems(Update, setup::build_classes_from_manifest.in_set(state::SetupSystems))
             //  Authority systems v
             .add_systems(FixedUpdate, (
-                default_class::s_default_class_ability_response,
-                melee_class::s_melee_class_ability_response,
-                ranged_class::s_ranged_class_response,
+                validation::s_add_ability_limiters,
+                validation::s_tick_ability_limiters,
+            ).chain().in_set(state::AuthoritySystems))
+            .add_systems(FixedUpdate, (
+                ability::s_data_ability_response,
                 class::s_setup_initial_class,
-            ).in_set(state::AuthoritySystems))
+            ).after(validation::s_tick_ability_limiters).in_set(state::AuthoritySystems))
             .add_systems(FixedUpdate, (
-                setup::c_class_input_system,
+                setup::c_class_input_system.after(player::c_update_local_aim),
+                ability::c_data_abilities.after(setup::c_class_input_system),
             ).in_set(state::HostAndClientSystems).in_set(state::FightingSystems))
             ;
     }
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+
+use crate::simple::consts::SERVER_STR;
+
+
+/// How this game relates to the server, which decides whether the local player's abilities are predicted.
+/// Every client ability should go through this instead of checking whether it is the host itself
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PredictionMode
+{
+    /// Singleplayer, the local player is the authority and there is no one else to map entities for
+    #[default]
+    Host,
+    /// Hosting a server while playing, the local player is the authority and their events arrive as [`ClientId::SERVER`]
+    ListenServer,
+    /// Connected to a remote server, abilities are predicted and reconciled with the server's entities once it responds
+    RemoteClient,
+}
+
+impl PredictionMode
+{
+    /// Whether the local player's abilities should be predicted (spawning entities, spending resources, etc.) before the server responds
+    pub fn should_predict(&self) -> bool
+    {
+        matches!(self, Self::RemoteClient)
+    }
+
+    /// Spawns the predicted entities of an ability if predicting, the result should be sent to the server as the ability's prespawned entities.
+    /// The spawned entities should have a [`DestroyIfNoMatchWithin`](crate::simple::gameplay::DestroyIfNoMatchWithin), so they are destroyed if the server never maps them
+    pub fn predict_spawn<T>(&self, commands: &mut Commands, spawn: impl FnOnce(&mut Commands) -> T) -> Option<T>
+    {
+        self.should_predict().then(|| spawn(commands))
+    }
+
+    /// Applies a predicted change to the local player (knockback, toggling state, etc.), the authority applies it when responding to the event instead
+    pub fn predict(&self, apply: impl FnOnce())
+    {
+        if self.should_predict()
+        {
+            apply();
+        }
+    }
+}
+
+/// The mappings between a client's prespawned entities and the entities the server spawned in response.
+/// The server's own events are never predicted, and mismatched entity counts are not mapped at all
+pub fn prespawn_mappings(client_id: ClientId, server_entities: &[Entity], prespawned: &[Entity]) -> Vec<ClientMapping>
+{
+    if client_id == ClientId::SERVER || prespawned.is_empty()
+    {
+        return Vec::new();
+    }
+    if server_entities.len() != prespawned.len()
+    {
+        warn!("{SERVER_STR} Client '{}' prespawned {} entities but {} were spawned, not mapping them", client_id.get(), prespawned.len(), server_entities.len());
+        return Vec::new();
+    }
+    server_entities.iter().zip(prespawned)
+        .map(|(server_entity, client_entity)| ClientMapping { server_entity: *server_entity, client_entity: *client_entity })
+        .collect()
+}
+
+/// Server side: maps a client's prespawned entities onto the entities spawned for them, so the client reuses its predicted entities
+pub fn map_prespawned(client_map: &mut ClientEntityMap, client_id: ClientId, server_entities: &[Entity], prespawned: &[Entity])
+{
+    for mapping in prespawn_mappings(client_id, server_entities, prespawned)
+    {
+        client_map.insert(client_id, mapping);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use bevy::ecs::system::CommandQueue;
+
+    use super::*;
+
+    /// Runs a predicted spawn of `count` entities, returning what would be sent to the server and how many entities now exist
+    fn predict_spawn_count(mode: PredictionMode, count: usize) -> (Option<Vec<Entity>>, usize)
+    {
+        let mut world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let prespawned = mode.predict_spawn(&mut commands, |commands| (0..count).map(|_| commands.spawn_empty().id()).collect());
+        queue.apply(&mut world);
+        (prespawned, world.entities().len() as usize)
+    }
+
+    #[test]
+    fn test_host_does_not_predict()
+    {
+        let (prespawned, spawned) = predict_spawn_count(PredictionMode::Host, 3);
+        assert_eq!(prespawned, None);
+        assert_eq!(spawned, 0);
+
+        let mut predicted = false;
+        PredictionMode::Host.predict(|| predicted = true);
+        assert!(!predicted);
+    }
+
+    #[test]
+    fn test_listen_server_maps_remote_clients_only()
+    {
+        let (prespawned, spawned) = predict_spawn_count(PredictionMode::ListenServer, 3);
+        assert_eq!(prespawned, None);
+        assert_eq!(spawned, 0);
+
+        let server_entities = [Entity::from_raw(10), Entity::from_raw(11)];
+        let client_entities = [Entity::from_raw(1), Entity::from_raw(2)];
+        assert!(prespawn_mappings(ClientId::SERVER, &server_entities, &client_entities).is_empty());
+        assert!(prespawn_mappings(ClientId::SERVER, &server_entities, &[]).is_empty());
+        assert_eq!(prespawn_mappings(ClientId::new(5), &server_entities, &client_entities).len(), 2);
+    }
+
+    #[test]
+    fn test_remote_client_predicts_and_is_mapped()
+    {
+        let (prespawned, spawned) = predict_spawn_count(PredictionMode::RemoteClient, 3);
+        let prespawned = prespawned.expect("remote clients should predict");
+        assert_eq!(prespawned.len(), 3);
+        assert_eq!(spawned, 3);
+
+        let mut predicted = false;
+        PredictionMode::RemoteClient.predict(|| predicted = true);
+        assert!(predicted);
+
+        let server_entities = [Entity::from_raw(10), Entity::from_raw(11), Entity::from_raw(12)];
+        let mappings = prespawn_mappings(ClientId::new(5), &server_entities, &prespawned);
+        assert_eq!(mappings.len(), 3);
+        for (mapping, (server_entity, client_entity)) in mappings.iter().zip(server_entities.iter().zip(&prespawned))
+        {
+            assert_eq!(mapping.server_entity, *server_entity);
+            assert_eq!(mapping.client_entity, *client_entity);
+        }
+
+        // A mismatch is left unmapped, so the predicted entities are destroyed instead of being mapped to the wrong things
+        assert!(prespawn_mappings(ClientId::new(5), &server_entities[..2], &prespawned).is_empty());
+    }
+}
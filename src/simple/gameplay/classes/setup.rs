@@ -1,133 +1,91 @@
-use std::sync::{Arc, Mutex};
-use bevy::{prelude::*, utils::HashMap};
+use bevy::prelude::*;
 
 use super::{
-    class::{AbilityTrigger, ActorClass, Class, ClassType, Classes, LabelledSystemId}, 
-    melee_class::{c_big_swing, c_dash, c_normal_attack, c_slicing_projectile, c_spin_attack}, 
-    ranged_class::{
-        c_basic_grenade_ability, 
-        c_basic_gun_ability, 
-        c_equipmachine_gun_ability, 
-        c_machine_gun_shoot_ability, 
-        c_missile_ability, 
-        c_shotgun_ability, 
-        s_ranged_class_setup, 
-        s_ranged_class_teardown
-    }
+    ability::{ClassAbilities, TriggerDataAbility},
+    class::{ActorClass, Class, Classes},
 };
 
-use crate::simple::player::LocalPlayer;
-use super::default_class::*;
+use crate::simple::{
+    data::{ClassBaseData, ClassManifest, TalentTree, WaitingHandles},
+    player::{ActionInput, LocalPlayer},
+    consts::CLASS_MANIFEST_PATH,
+};
 
 
-pub struct PlayerBulletColor1;
-impl GetColor for PlayerBulletColor1
+/// The class manifest being loaded, classes are built from it once it has loaded
+#[derive(Resource)]
+pub struct ClassManifestHandle
 {
-    fn get_color() -> Color {
-        Color::rgb(0.8, 0.2, 0.2)
-    }
-}
-
-pub struct PlayerBulletColor2;
-impl GetColor for PlayerBulletColor2
-{
-    fn get_color() -> Color {
-        Color::rgb(0.2, 0.8, 0.2)
-    }
-}
-
-pub fn add_ability<S, M>(world: &mut World, abilities: &mut HashMap<AbilityTrigger, LabelledSystemId>, trigger: AbilityTrigger, name: &str, system: S)
-    where S: IntoSystem<(), (), M> + 'static
-{
-    let system_id = world.register_system(system);
-    abilities.insert(trigger, LabelledSystemId{ name: name.into(), system_id });
+    pub manifest: Handle<ClassManifest>,
+    pub built: bool,
 }
 
+/// Starts loading the class manifest, the classes themselves are built by [`build_classes_from_manifest`]
 pub fn setup_classes(
     world: &mut World,
 ) -> Vec<UntypedHandle> {
     info!("Setting up player classes");
-    let asset_server = world.resource::<AssetServer>();
-    let ranged_data = asset_server.load("ranged_class_data.cbd");
-    let melee_data = asset_server.load("melee_class_data.cbd");
-    let default_data = asset_server.load("default_class_data.cbd");
-
-    let mut abilities = HashMap::with_capacity(1);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::Space), "Melee attack", c_melee_ability);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::Enter), "Gun attack", c_shoot_ability::<PlayerBulletColor1>);
-
-    let default_class = Class {
-        setup_fn: None,
-        teardown_fn: None,
-        abilities,
-        base_data: default_data.clone()
-    };
-
-    let mut abilities = HashMap::with_capacity(5);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::Space), "Base attack", c_normal_attack);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::Enter), "Big swing", c_big_swing);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::KeyQ), "Slicing projectile", c_slicing_projectile);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::KeyE), "Spin attack", c_spin_attack);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::KeyT), "Dash", c_dash);
 
-    let melee_class = Class {
-        setup_fn: None,
-        teardown_fn: None,
-        abilities,
-        base_data: melee_data.clone(),
-    };
+    let manifest = world.resource::<AssetServer>().load(CLASS_MANIFEST_PATH);
+    world.insert_resource(ClassManifestHandle { manifest: manifest.clone(), built: false });
+    world.init_resource::<Classes>();
 
-    let mut abilities = HashMap::with_capacity(7);
-
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::Space), "Basic gun attack", c_basic_gun_ability);
-    add_ability(world, &mut abilities, AbilityTrigger::HeldDown(KeyCode::Space), "Machine gun fire", c_machine_gun_shoot_ability);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::Enter), "Grenade throw", c_basic_grenade_ability);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::KeyR), "Shotgun blast", c_shotgun_ability);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::KeyG), "Equip machinegun", c_equipmachine_gun_ability);
-    add_ability(world, &mut abilities, AbilityTrigger::JustPressed(KeyCode::KeyF), "Fire missiiles", c_missile_ability);
-
-    let ranged_class = Class {
-        setup_fn: Some(Arc::new(Mutex::new(s_ranged_class_setup))),
-        teardown_fn: Some(Arc::new(Mutex::new(s_ranged_class_teardown))),
-        abilities,
-        base_data: ranged_data.clone(),
-    };
+    vec![manifest.untyped()]
+}
 
-    let mut classes = HashMap::with_capacity(2);
-    classes.insert(ClassType::DefaultClass, default_class);
-    classes.insert(ClassType::MeleeClass, melee_class);
-    classes.insert(ClassType::RangedClass, ranged_class);
+/// Builds every class in the class manifest once it has loaded, and waits for their class data to load too
+pub fn build_classes_from_manifest(
+    mut manifest_handle: ResMut<ClassManifestHandle>,
+    manifests: Res<Assets<ClassManifest>>,
+    asset_server: Res<AssetServer>,
+    mut classes: ResMut<Classes>,
+    mut waiting_handles: ResMut<WaitingHandles>,
+) {
+    if manifest_handle.built
+    {
+        return;
+    }
+    let Some(manifest) = manifests.get(&manifest_handle.manifest) else { return; };
+    manifest_handle.built = true;
 
-    world.insert_resource(Classes
+    for entry in &manifest.classes
     {
-        classes,
-    });
+        let base_data: Handle<ClassBaseData> = asset_server.load(entry.data.clone());
+        waiting_handles.handles.push(base_data.clone().untyped());
+        let talents: Option<Handle<TalentTree>> = entry.talents.as_ref().map(|path| asset_server.load(path.clone()));
+        if let Some(talents) = &talents
+        {
+            waiting_handles.handles.push(talents.clone().untyped());
+        }
+
+        if classes.classes.insert(entry.id.clone(), Class {
+            base_data,
+            ui: entry.ui.clone(),
+            talents,
+        }).is_some()
+        {
+            warn!("Class '{}' is listed more than once in the class manifest, only the last entry is used", entry.id);
+        }
+    }
 
-    info!("Successfully set up player classes");
-    vec![default_data.untyped(), melee_data.untyped(), ranged_data.untyped()]
+    info!("Successfully set up {} player classes", classes.classes.len());
 }
 
-/// Client side system responsible for reading input, and running the appropriate 'ability system'
+/// Client side system responsible for reading input, and triggering the local player's class abilities via the TriggerDataAbility event
 pub fn c_class_input_system(
-    mut commands: Commands,
     player: Query<&ActorClass, With<LocalPlayer>>,
-    classes: Res<Classes>,
-    input: Res<ButtonInput<KeyCode>>,
+    class_abilities: ClassAbilities,
+    input: ActionInput,
+    mut data_ability_triggers: EventWriter<TriggerDataAbility>,
 ) {
     let Ok(class) = player.get_single() else { return; };
-
-    for (trigger, labelled_system) in &classes.classes[&class.get_class()].abilities
+    let Some(class_data) = class_abilities.get(class.get_class()) else { return; };
+    for (index, ability) in class_data.abilities.iter().enumerate()
     {
-        let (run_condition, _keycode) = match trigger {
-            AbilityTrigger::JustPressed(keycode) => (input.just_pressed(*keycode), *keycode),
-            AbilityTrigger::HeldDown(keycode) => (input.pressed(*keycode), *keycode),
-            AbilityTrigger::JustPressedOrReleased(keycode) => (input.just_pressed(*keycode) || input.just_released(*keycode), *keycode),
-            AbilityTrigger::JustReleased(keycode) => (input.just_released(*keycode), *keycode),
-        };
-        if run_condition
+        if ability.trigger.is_triggered(&input)
         {
-            debug!("Running {} class {} system", class, labelled_system.name);
-            commands.run_system(labelled_system.system_id);
+            debug!("Triggering {} class {} ability", class, ability.name);
+            data_ability_triggers.send(TriggerDataAbility { ability: index });
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,346 @@
+use std::fmt::Display;
+
+use bevy::{ecs::system::SystemParam, prelude::*, utils::{HashMap, HashSet}};
+use bevy_replicon::prelude::*;
+
+use crate::simple::{
+    gameplay::actor::{ActorResources, ResourceCost},
+    player::Player,
+    consts::{SERVER_STR, ABILITY_COOLDOWN_TOLERANCE, ABILITY_RATE_LIMIT_WINDOW, ABILITY_RATE_LIMIT_MAX_EVENTS},
+};
+
+
+/// Why the server refused to act on an ability event sent by a client
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AbilityRejection
+{
+    /// The client does not have a player entity (or it is missing its limiter)
+    UnknownPlayer,
+    /// The ability belongs to a class the player is not
+    WrongClass,
+    /// The ability can not be used in the player's current ability mode
+    WrongMode,
+    /// The direction was not finite, or was too small to normalise
+    InvalidDirection,
+    /// The ability was used again before its cooldown finished
+    OnCooldown,
+    /// The client sent too many ability events in too short a time
+    RateLimited,
+    /// The prespawned entities did not match what the ability spawns
+    InvalidPrespawn,
+    /// The player could not afford the ability's resource cost
+    InsufficientResource,
+}
+
+impl Display for AbilityRejection
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            Self::UnknownPlayer => f.write_str("unknown player"),
+            Self::WrongClass => f.write_str("wrong class"),
+            Self::WrongMode => f.write_str("wrong ability mode"),
+            Self::InvalidDirection => f.write_str("invalid direction"),
+            Self::OnCooldown => f.write_str("on cooldown"),
+            Self::RateLimited => f.write_str("rate limited"),
+            Self::InvalidPrespawn => f.write_str("invalid prespawned entities"),
+            Self::InsufficientResource => f.write_str("insufficient resources"),
+        }
+    }
+}
+
+/// Server-only component tracking the cooldowns and recent ability usage of a player
+#[derive(Component, Default)]
+pub struct AbilityLimiter
+{
+    cooldowns: HashMap<String, f32>,
+    window_remaining: f32,
+    events_in_window: u32,
+}
+
+impl AbilityLimiter
+{
+    pub fn tick(&mut self, delta_seconds: f32)
+    {
+        self.cooldowns.retain(|_, remaining| { *remaining -= delta_seconds; *remaining > 0.0 });
+        self.window_remaining -= delta_seconds;
+        if self.window_remaining <= 0.0
+        {
+            self.window_remaining = ABILITY_RATE_LIMIT_WINDOW;
+            self.events_in_window = 0;
+        }
+    }
+}
+
+/// Everything the server needs to know to validate a single ability event
+pub struct AbilityCheck<'a>
+{
+    name: &'a str,
+    direction: Option<Vec2>,
+    cooldown: f32,
+    cost: Option<ResourceCost>,
+    prespawned: &'a [Entity],
+    expected_prespawns: usize,
+}
+
+impl<'a> AbilityCheck<'a>
+{
+    pub fn new(name: &'a str) -> Self
+    {
+        Self { name, direction: None, cooldown: 0.0, cost: None, prespawned: &[], expected_prespawns: 0 }
+    }
+
+    pub fn with_direction(mut self, direction: Vec2) -> Self
+    {
+        self.direction = Some(direction);
+        self
+    }
+
+    pub fn with_cooldown(mut self, cooldown: f32) -> Self
+    {
+        self.cooldown = cooldown;
+        self
+    }
+
+    pub fn with_cost(mut self, cost: Option<ResourceCost>) -> Self
+    {
+        self.cost = cost;
+        self
+    }
+
+    /// The entities the client predicted, and how many the ability will actually spawn
+    pub fn with_prespawned(mut self, prespawned: &'a [Entity], expected: usize) -> Self
+    {
+        self.prespawned = prespawned;
+        self.expected_prespawns = expected;
+        self
+    }
+
+    /// Returns the normalised direction of the ability (zero if it has none) if it passes every check.
+    /// Only successful checks count towards cooldowns and rate limits, and spend the ability's cost
+    pub fn check(&self, limiter: &mut AbilityLimiter, resources: Option<&mut ActorResources>) -> Result<Vec2, AbilityRejection>
+    {
+        let direction = match self.direction
+        {
+            Some(direction) => validate_direction(direction)?,
+            None => Vec2::ZERO,
+        };
+        validate_prespawned(self.prespawned, self.expected_prespawns)?;
+
+        if limiter.events_in_window >= ABILITY_RATE_LIMIT_MAX_EVENTS
+        {
+            return Err(AbilityRejection::RateLimited);
+        }
+        if limiter.cooldowns.get(self.name).is_some_and(|remaining| *remaining > ABILITY_COOLDOWN_TOLERANCE)
+        {
+            return Err(AbilityRejection::OnCooldown);
+        }
+        if let Some(cost) = &self.cost
+        {
+            let Some(resources) = resources else { return Err(AbilityRejection::InsufficientResource); };
+            if !resources.try_spend(cost)
+            {
+                return Err(AbilityRejection::InsufficientResource);
+            }
+        }
+
+        limiter.events_in_window += 1;
+        if self.cooldown > 0.0
+        {
+            limiter.cooldowns.insert(self.name.to_string(), self.cooldown);
+        }
+        Ok(direction)
+    }
+}
+
+/// Normalises a direction sent by a client, rejecting non-finite or (near) zero directions
+pub fn validate_direction(direction: Vec2) -> Result<Vec2, AbilityRejection>
+{
+    direction.try_normalize().ok_or(AbilityRejection::InvalidDirection)
+}
+
+/// Clients that do not predict send no entities, otherwise they must send exactly one unique entity per spawned object
+pub fn validate_prespawned(prespawned: &[Entity], expected: usize) -> Result<(), AbilityRejection>
+{
+    if prespawned.is_empty()
+    {
+        return Ok(());
+    }
+    if prespawned.len() != expected || prespawned.contains(&Entity::PLACEHOLDER)
+    {
+        return Err(AbilityRejection::InvalidPrespawn);
+    }
+    let mut unique = HashSet::with_capacity(prespawned.len());
+    if !prespawned.iter().all(|entity| unique.insert(*entity))
+    {
+        return Err(AbilityRejection::InvalidPrespawn);
+    }
+    Ok(())
+}
+
+/// Server-only resource counting how many ability events were rejected for each client, keyed by their client id
+#[derive(Resource, Default)]
+pub struct AbilityRejections
+{
+    pub per_client: HashMap<u64, HashMap<AbilityRejection, u32>>,
+}
+
+impl AbilityRejections
+{
+    pub fn record(&mut self, client_id: ClientId, rejection: AbilityRejection)
+    {
+        *self.per_client.entry(client_id.get()).or_default().entry(rejection).or_default() += 1;
+    }
+
+    pub fn total(&self, client_id: ClientId) -> u32
+    {
+        self.per_client.get(&client_id.get()).map_or(0, |counts| counts.values().sum())
+    }
+}
+
+/// Validates ability events inside the server responders, logging and counting every rejection
+#[derive(SystemParam)]
+pub struct AbilityValidator<'w, 's>
+{
+    rejections: ResMut<'w, AbilityRejections>,
+    limiters: Query<'w, 's, (&'static mut AbilityLimiter, Option<&'static mut ActorResources>)>,
+}
+
+impl<'w, 's> AbilityValidator<'w, 's>
+{
+    pub fn validate(&mut self, client_id: ClientId, player: Entity, check: AbilityCheck) -> Result<Vec2, AbilityRejection>
+    {
+        let result = match self.limiters.get_mut(player)
+        {
+            Ok((mut limiter, resources)) => check.check(&mut limiter, resources.map(|r| r.into_inner())),
+            Err(_) => Err(AbilityRejection::UnknownPlayer),
+        };
+        if let Err(rejection) = result
+        {
+            self.reject(client_id, check.name, rejection);
+        }
+        result
+    }
+
+    /// The resources of a player, for abilities that affect resources beyond their cost
+    pub fn resources_mut(&mut self, player: Entity) -> Option<Mut<'_, ActorResources>>
+    {
+        self.limiters.get_mut(player).ok().and_then(|(_, resources)| resources)
+    }
+
+    /// Records a rejection found outside of an [`AbilityCheck`]
+    pub fn reject(&mut self, client_id: ClientId, ability: &str, rejection: AbilityRejection)
+    {
+        self.rejections.record(client_id, rejection);
+        warn!("{SERVER_STR} Rejected ability '{ability}' from client '{}': {rejection} ({} rejections total)", client_id.get(), self.rejections.total(client_id));
+    }
+}
+
+/// This system (Authority only) gives every player an ability limiter
+pub fn s_add_ability_limiters(
+    mut commands: Commands,
+    new_players: Query<Entity, (With<Player>, Without<AbilityLimiter>)>,
+) {
+    for entity in &new_players
+    {
+        commands.entity(entity).insert(AbilityLimiter::default());
+    }
+}
+
+pub fn s_tick_ability_limiters(
+    mut limiters: Query<&mut AbilityLimiter>,
+    time: Res<Time>,
+) {
+    for mut limiter in &mut limiters
+    {
+        limiter.tick(time.delta_seconds());
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::simple::gameplay::actor::{ActorContext, ResourceKind, Stat};
+
+    #[test]
+    fn test_direction_is_normalised()
+    {
+        assert_eq!(validate_direction(Vec2::new(10.0, 0.0)), Ok(Vec2::X));
+        assert_eq!(validate_direction(Vec2::ZERO), Err(AbilityRejection::InvalidDirection));
+        assert_eq!(validate_direction(Vec2::new(f32::NAN, 1.0)), Err(AbilityRejection::InvalidDirection));
+        assert_eq!(validate_direction(Vec2::new(f32::INFINITY, 0.0)), Err(AbilityRejection::InvalidDirection));
+    }
+
+    #[test]
+    fn test_prespawned_sanity()
+    {
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+        assert_eq!(validate_prespawned(&[], 4), Ok(()));
+        assert_eq!(validate_prespawned(&[a, b], 2), Ok(()));
+        assert_eq!(validate_prespawned(&[a], 2), Err(AbilityRejection::InvalidPrespawn));
+        assert_eq!(validate_prespawned(&[a, a], 2), Err(AbilityRejection::InvalidPrespawn));
+        assert_eq!(validate_prespawned(&[a, Entity::PLACEHOLDER], 2), Err(AbilityRejection::InvalidPrespawn));
+    }
+
+    #[test]
+    fn test_cooldown()
+    {
+        let mut limiter = AbilityLimiter::default();
+        let check = AbilityCheck::new("Shotgun").with_cooldown(1.0);
+        assert!(check.check(&mut limiter, None).is_ok());
+        assert_eq!(check.check(&mut limiter, None), Err(AbilityRejection::OnCooldown));
+        assert!(AbilityCheck::new("Missiles").check(&mut limiter, None).is_ok());
+
+        limiter.tick(1.0 - ABILITY_COOLDOWN_TOLERANCE * 0.5);
+        assert!(check.check(&mut limiter, None).is_ok());
+    }
+
+    #[test]
+    fn test_rate_limit()
+    {
+        let mut limiter = AbilityLimiter { window_remaining: ABILITY_RATE_LIMIT_WINDOW, ..default() };
+        let check = AbilityCheck::new("Basic gun");
+        for _ in 0..ABILITY_RATE_LIMIT_MAX_EVENTS
+        {
+            assert!(check.check(&mut limiter, None).is_ok());
+        }
+        assert_eq!(check.check(&mut limiter, None), Err(AbilityRejection::RateLimited));
+
+        limiter.tick(ABILITY_RATE_LIMIT_WINDOW);
+        assert!(check.check(&mut limiter, None).is_ok());
+    }
+
+    #[test]
+    fn test_cost_is_spent_only_when_valid()
+    {
+        let mut limiter = AbilityLimiter::default();
+        let mut actor = ActorContext::default();
+        actor.set_stat(Stat::MaxResource(ResourceKind::Mana), 10.0);
+        let mut resources = ActorResources::default();
+        resources.sync_with_stats(&actor);
+        let cost = Some(ResourceCost { kind: ResourceKind::Mana, amount: 6.0 });
+
+        let no_direction = AbilityCheck::new("Fireball").with_direction(Vec2::ZERO).with_cost(cost);
+        assert_eq!(no_direction.check(&mut limiter, Some(&mut resources)), Err(AbilityRejection::InvalidDirection));
+        assert_eq!(resources.pools.get(&ResourceKind::Mana).map(|p| p.current), Some(10.0));
+
+        let check = AbilityCheck::new("Fireball").with_cost(cost);
+        assert!(check.check(&mut limiter, Some(&mut resources)).is_ok());
+        assert_eq!(check.check(&mut limiter, Some(&mut resources)), Err(AbilityRejection::InsufficientResource));
+        assert_eq!(check.check(&mut limiter, None), Err(AbilityRejection::InsufficientResource));
+    }
+
+    #[test]
+    fn test_rejections_are_counted_per_client()
+    {
+        let mut rejections = AbilityRejections::default();
+        rejections.record(ClientId::new(1), AbilityRejection::OnCooldown);
+        rejections.record(ClientId::new(1), AbilityRejection::WrongClass);
+        rejections.record(ClientId::new(2), AbilityRejection::OnCooldown);
+        assert_eq!(rejections.total(ClientId::new(1)), 2);
+        assert_eq!(rejections.total(ClientId::new(2)), 1);
+        assert_eq!(rejections.total(ClientId::new(3)), 0);
+    }
+}
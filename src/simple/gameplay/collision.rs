@@ -1,10 +1,10 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_rapier2d::{geometry::Collider, plugin::RapierContext};
 
 use super::{
     actor::{
         Damage, DamageKnockback, ActorChild, ActorContext, ActorSensors,
-        DamageEvent, ChildType,
+        DamageEvent, ChildType, responsible_actor, summoner_of,
         effect_application::{
             ActorOnHitEffectContext, EffectContextWorldAccess, 
             apply_on_ability_hit_effects, ActorReference,
@@ -14,11 +14,12 @@ use super::{
 };
 
 
+/// Marks an actor as able to be hit by damaging objects.
+/// Repeated hits are limited per damaging object (see `Damage::rehit_interval`), invulnerability here blocks every source (eg. spawn protection)
 #[derive(Component)]
 pub struct Damageable
 {
     pub invulnerability_remaining: f32,
-    pub invulnerability_duration: f32,
 }
 
 fn do_collision_logic(
@@ -51,44 +52,68 @@ fn do_collision_logic(
 }
 
 
+/// Builds a lookup from each actor sensor entity to the actor that owns it.
+/// Entities that are not in the map are assumed to be the actor themselves.
+fn sensor_owner_lookup<'a>(sensors: impl Iterator<Item = (Entity, &'a ActorSensors)>) -> HashMap<Entity, Entity>
+{
+    let mut owners = HashMap::new();
+    for (actor_entity, actor_sensors) in sensors
+    {
+        for sensor in &actor_sensors.sensors
+        {
+            owners.insert(*sensor, actor_entity);
+        }
+    }
+    owners
+}
+
 pub fn s_collision_projectiles_damage(
     mut commands: Commands,
     rapier_context: Res<RapierContext>,
     mut undamageable_actors: Query<(Entity, &mut ActorContext, &mut Position), (Without<Damageable>, Without<ActorChild>)>,
     mut actor_query: Query<(Entity, &mut ActorContext, &mut Damageable, &mut Position, &ActorSensors, &mut Knockback, &Name), Without<Damage>>,
     mut actor_projectiles: Query<(Entity, &mut Damage, &GlobalTransform, &ActorChild, &Name), (Without<ActorContext>, With<Collider>)>,
+    summons: Query<&ActorChild, With<ActorContext>>,
     mut damage_events: EventWriter<DamageEvent>,
 ) {
     let mut ability_hits: Vec<(Entity, ChildType, Vec2)> = Vec::new();
+    let sensor_owners = sensor_owner_lookup(actor_query.iter().map(|(entity, _, _, _, sensors, _, _)| (entity, sensors)));
     // Do direct damage to non-actors (and trigger ability hits), do actor damage to other actors
-    // info!("There are {} projectiles currently", actor_projectiles.iter().count());
-    // info!("There are {} damageable actors currently", actor_query.iter().count());
+    // Only the pairs rapier reports as overlapping are visited, so the cost scales with actual overlaps
     for (projectile_entity, mut proj, proj_trans, child, proj_name) in &mut actor_projectiles
     {
-        if proj.deal_damage_once && proj.did_damage
+        if !proj.can_hit_anything()
         {
             continue;
         }
-        for (target_entity, _, mut target_damageable, target_position, sensors, mut target_knckbk, actor_name) in &mut actor_query
+        for (collider_1, collider_2, intersecting) in rapier_context.intersection_pairs_with(projectile_entity)
         {
-            if target_damageable.invulnerability_remaining > 0.0
+            if !intersecting
             {
                 continue;
             }
-            if !sensors.sensors.iter().chain(std::iter::once(&target_entity)).any(|sensor_ent| rapier_context.intersection_pair(projectile_entity, *sensor_ent) == Some(true))
+            let other_collider = if collider_1 == projectile_entity { collider_2 } else { collider_1 };
+            let target_entity = *sensor_owners.get(&other_collider).unwrap_or(&other_collider);
+            if target_entity == child.parent_actor || !proj.can_hit(target_entity)
             {
                 continue;
             }
+            let Ok((_, _, target_damageable, target_position, _, mut target_knckbk, actor_name)) = actor_query.get_mut(target_entity) else { continue; };
+            if target_damageable.invulnerability_remaining > 0.0
+            {
+                continue;
+            }
+            proj.register_hit(target_entity);
             info!("Projectile entity '{}' collision with entity '{}', proj at {}, entity at: {}", proj_name, actor_name, proj_trans.translation(), target_position.0);
 
             let dmg_to_do = do_collision_logic(&mut commands, projectile_entity, &mut proj, proj_trans.translation().truncate(), &target_position, &mut target_knckbk);
 
-            ability_hits.push((child.parent_actor, child.ability_type, proj_trans.translation().truncate()));
+            // On-hit effects of abilities used by summons are those of their summoner
+            let hitting_actor = responsible_actor(child.parent_actor, |entity| summoner_of(&summons, entity));
+            ability_hits.push((hitting_actor, child.ability_type, proj_trans.translation().truncate()));
             damage_events.send(DamageEvent { instigator: child.parent_actor, victim: target_entity, damage: dmg_to_do });
-            proj.did_damage = true;
-            target_damageable.invulnerability_remaining = target_damageable.invulnerability_duration;
 
-            if proj.deal_damage_once
+            if !proj.can_hit_anything()
             {
                 break;
             }
@@ -130,3 +155,17 @@ pub fn s_tick_damageable(
         dmg.invulnerability_remaining = (dmg.invulnerability_remaining - time.delta_seconds()).max(0.0);
     }
 }
+
+pub fn s_tick_damage_hits(
+    mut damagers: Query<&mut Damage>,
+    time: Res<Time>,
+) {
+    for mut damage in &mut damagers
+    {
+        if damage.hits.is_empty()
+        {
+            continue;
+        }
+        damage.tick_hits(time.delta_seconds());
+    }
+}
@@ -1,12 +1,19 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::Velocity;
 
+use crate::simple::consts::SUMMON_DECOY_TAUNT_RANGE;
+
 use super::{
     Enemy,
-    super::{Position, super::player::Player}
+    super::{
+        Position,
+        actor::effect::SummonKind,
+        objects::Summon,
+        super::player::Player,
+    }
 };
 
-/// Adjusts the velocity of enemies to try move towards the nearest player
+/// Adjusts the velocity of enemies to try move towards the nearest player, or the nearest decoy if one is close enough
 /// This is required on the server to have enemies actually move, but can also be run on the client to 'predict' 
 /// the movement of enemies (if velocity movement is also predicted).
 /// 
@@ -15,6 +22,7 @@ use super::{
 pub fn cs_move_enemies(
     mut enemies: Query<(&Enemy, &Position, &mut Velocity), Without<Player>>,
     players: Query<&Position, (With<Player>, Without<Enemy>)>,
+    summons: Query<(&Summon, &Position), Without<Enemy>>,
     time: Res<Time>,
 ) {
     const MAX_ACCELERATION: f32 = 125.0;
@@ -32,6 +40,7 @@ pub fn cs_move_enemies(
 
         let mut nearest_player_pos: Option<Vec2> = None;
         let mut nearest_player_distance_squared = f32::MAX;
+        let mut is_decoy = false;
 
         for player_position in &players
         {
@@ -43,6 +52,22 @@ pub fn cs_move_enemies(
             }
         }
 
+        for (summon, decoy_position) in &summons
+        {
+            if summon.kind != SummonKind::Decoy
+            {
+                continue;
+            }
+            // Decoys within taunt range are always chased over players
+            let distance_sq = (position.0 - decoy_position.0).length_squared();
+            if distance_sq <= SUMMON_DECOY_TAUNT_RANGE * SUMMON_DECOY_TAUNT_RANGE && (distance_sq < nearest_player_distance_squared || !is_decoy)
+            {
+                nearest_player_pos = Some(decoy_position.0);
+                nearest_player_distance_squared = distance_sq;
+                is_decoy = true;
+            }
+        }
+
         let Some(nearest_player_pos) = nearest_player_pos else { return };
 
         let direction = (nearest_player_pos - position.0).normalize_or_zero();
@@ -51,7 +51,7 @@ impl EnemyAuthorityBundle
         {
             enemy: Enemy { speed },
             actor,
-            damage: Damageable { invulnerability_duration: 0.25, invulnerability_remaining: 0.5 },
+            damage: Damageable { invulnerability_remaining: 0.5 },
             position: Position(position),
             replication: Replication,
             transform: TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
@@ -95,11 +95,14 @@ impl EnemyExtrasBundle
 
 fn spawn_enemy(commands: &mut Commands, position: Vec2, actor: ActorContext)
 {
+    let mut contact_damage = Damage::new(5.0, false, false, Some(DamageKnockback::RepulsionFromSelf { strength: 300.0 }));
+    contact_damage.with_rehit_interval(Some(ENEMY_CONTACT_REHIT_INTERVAL));
+
     let sensor_id = commands.spawn((
         TransformBundle::IDENTITY,
         Sensor,
         Collider::ball(40.0 / 2.0),
-        Damage { damage: 5.0, destroy_on_damage: false, deal_damage_once: false, knockback: Some(DamageKnockback::RepulsionFromSelf { strength: 300.0 }), did_damage: false },
+        contact_damage,
         CollisionGroups { memberships: ENEMY_GROUP, filters: ENEMY_SENSOR_FILTER },
         ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC,
         Name::new("Enemy attack hitbox"),
@@ -0,0 +1,302 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier2d::prelude::*;
+use bevy_replicon::network_event::server_event::{SendMode, ToClients};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::simple::{
+    gameplay::{
+        actor::{ActorChild, ActorContext, ChildType, DamageSource, Stat},
+        Dead, Position,
+    },
+    player::Player,
+    data::{Bounds, WorldBounds},
+    consts::*,
+};
+
+
+/// How players are allowed to interact with each other, chosen by the server at startup
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum MatchMode
+{
+    /// Players fight enemy waves together and can not hurt each other
+    #[default]
+    Cooperative,
+    /// Players fight enemy waves together, but can hurt each other
+    FriendlyFire,
+    /// No enemy waves, every player for themselves (players respawn)
+    FreeForAll,
+}
+
+impl MatchMode
+{
+    pub fn players_can_hurt_players(&self) -> bool
+    {
+        match self
+        {
+            Self::Cooperative => false,
+            Self::FriendlyFire | Self::FreeForAll => true,
+        }
+    }
+
+    pub fn has_enemy_waves(&self) -> bool
+    {
+        match self
+        {
+            Self::Cooperative | Self::FriendlyFire => true,
+            Self::FreeForAll => false,
+        }
+    }
+
+    pub fn players_respawn(&self) -> bool
+    {
+        match self
+        {
+            Self::Cooperative | Self::FriendlyFire => false,
+            Self::FreeForAll => true,
+        }
+    }
+
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Cooperative => "Co-op",
+            Self::FriendlyFire => "Friendly Fire",
+            Self::FreeForAll => "Free For All",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct MatchRules
+{
+    pub mode: MatchMode,
+}
+
+/// Server-only component giving each player a unique collision group, so their own abilities can exclude them.
+/// Summons share the group of the player that summoned them
+#[derive(Component)]
+pub struct PlayerFaction
+{
+    pub group: Group,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerScore
+{
+    pub kills: u32,
+    pub deaths: u32,
+}
+
+/// Kills and deaths of every player, keyed by their client id
+/// Updated on the server, and mirrored on clients via the ScoreboardEvent
+#[derive(Resource, Default)]
+pub struct Scoreboard
+{
+    pub mode: MatchMode,
+    pub scores: HashMap<u64, PlayerScore>,
+}
+
+/// Event sent to clients whenever the scoreboard changes
+#[derive(Clone, Debug, Event, Serialize, Deserialize)]
+pub struct ScoreboardEvent
+{
+    pub mode: MatchMode,
+    pub scores: Vec<(u64, PlayerScore)>,
+}
+
+/// Server-only component on players that are respawned after they die, instead of being despawned.
+/// Their entity (and so their class, talents, upgrades and effects) is kept, and stays [`Dead`] until they respawn
+#[derive(Component, Default)]
+pub struct Respawns
+{
+    pub until_respawn: f32,
+}
+
+pub fn player_faction_group(slot: u32) -> Group
+{
+    Group::from_bits_truncate(1 << (PLAYER_FACTION_FIRST_BIT + slot))
+}
+
+/// Run condition for systems that should only run when the match has enemy waves
+pub fn enemy_waves_enabled(rules: Res<MatchRules>) -> bool
+{
+    rules.mode.has_enemy_waves()
+}
+
+/// Type alias for players that have not been given a faction yet
+type FactionlessPlayers<'w, 's> = Query<'w, 's, (Entity, &'static mut CollisionGroups), (With<Player>, Without<PlayerFaction>)>;
+
+/// This system (Authority only) gives new players a faction group, and lets them be hit by other players' abilities
+/// (and respawn) if the match allows it
+pub fn s_assign_player_factions(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    factions: Query<&PlayerFaction>,
+    mut new_players: FactionlessPlayers,
+) {
+    let mut used_groups = factions.iter().fold(Group::NONE, |groups, faction| groups | faction.group);
+    for (entity, mut groups) in &mut new_players
+    {
+        if rules.mode.players_respawn()
+        {
+            commands.entity(entity).insert(Respawns::default());
+        }
+        let Some(slot) = (0..PLAYER_FACTION_COUNT).find(|slot| !used_groups.intersects(player_faction_group(*slot))) else
+        {
+            warn!("{SERVER_STR} Ran out of player faction groups, player {entity:?} will not be hittable by other players");
+            continue;
+        };
+        let group = player_faction_group(slot);
+        used_groups |= group;
+
+        if rules.mode.players_can_hurt_players()
+        {
+            groups.memberships |= group;
+            groups.filters |= PLAYER_PROJECTILE_GROUP;
+        }
+        commands.entity(entity).insert(PlayerFaction { group });
+    }
+}
+
+/// This system (Authority only) lets newly created player abilities hit every player except their owner, if the match allows it.
+/// Summons join their owner's faction, so they can be hit by every other player and their own abilities can not hit their owner
+pub fn s_apply_pvp_collision_groups(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    factions: Query<&PlayerFaction>,
+    mut new_children: Query<(Entity, &ActorChild, &mut CollisionGroups), Added<ActorChild>>,
+) {
+    if !rules.mode.players_can_hurt_players()
+    {
+        return;
+    }
+    for (entity, child, mut groups) in &mut new_children
+    {
+        let Ok(owner_faction) = factions.get(child.parent_actor) else { continue; };
+        if groups.memberships.intersects(PLAYER_PROJECTILE_GROUP)
+        {
+            groups.filters |= PLAYER_FACTION_GROUPS.difference(owner_faction.group);
+        }
+        else if child.ability_type == ChildType::ChildActor
+        {
+            groups.memberships |= owner_faction.group;
+            groups.filters |= PLAYER_PROJECTILE_GROUP;
+            commands.entity(entity).insert(PlayerFaction { group: owner_faction.group });
+        }
+    }
+}
+
+/// This system (Authority only) credits kills and deaths to players using the last source of damage on dying actors
+pub fn s_track_kills(
+    mut scoreboard: ResMut<Scoreboard>,
+    rules: Res<MatchRules>,
+    players: Query<&Player>,
+    dying: Query<(Entity, &ActorContext), Added<Dead>>,
+) {
+    if scoreboard.mode != rules.mode
+    {
+        scoreboard.mode = rules.mode;
+    }
+    for (entity, actor) in &dying
+    {
+        if let Ok(victim) = players.get(entity)
+        {
+            scoreboard.scores.entry(victim.0.get()).or_default().deaths += 1;
+        }
+        let Some(DamageSource::Actor(killer)) = actor.last_damage_source else { continue; };
+        if killer == entity
+        {
+            continue;
+        }
+        let Ok(killer) = players.get(killer) else { continue; };
+        scoreboard.scores.entry(killer.0.get()).or_default().kills += 1;
+    }
+}
+
+pub fn s_broadcast_scoreboard(
+    scoreboard: Res<Scoreboard>,
+    mut scoreboard_events: EventWriter<ToClients<ScoreboardEvent>>,
+) {
+    if !scoreboard.is_changed()
+    {
+        return;
+    }
+    let scores = scoreboard.scores.iter().map(|(id, score)| (*id, *score)).collect();
+    scoreboard_events.send(ToClients { mode: SendMode::Broadcast, event: ScoreboardEvent { mode: scoreboard.mode, scores } });
+}
+
+pub fn c_receive_scoreboard(
+    mut scoreboard: ResMut<Scoreboard>,
+    mut scoreboard_events: EventReader<ScoreboardEvent>,
+) {
+    for ScoreboardEvent { mode, scores } in scoreboard_events.read()
+    {
+        scoreboard.mode = *mode;
+        scoreboard.scores = scores.iter().cloned().collect();
+    }
+}
+
+/// This system (Authority only) starts the respawn timer of players that just died, if the match allows respawning
+pub fn s_queue_player_respawns(
+    mut dead_players: Query<(&Player, &mut Respawns), Added<Dead>>,
+) {
+    for (player, mut respawns) in &mut dead_players
+    {
+        info!("{SERVER_STR} Player '{}' will respawn in {PVP_RESPAWN_DELAY} seconds", player.0.get());
+        respawns.until_respawn = PVP_RESPAWN_DELAY;
+    }
+}
+
+/// Brings a dead actor back to life with full health
+fn revive(actor: &mut ActorContext)
+{
+    let max_health = actor.get_stat(&Stat::MaxHealth).unwrap_or(0.0);
+    actor.set_stat(Stat::Health, max_health);
+    actor.last_damage_source = None;
+}
+
+/// This system (Authority only) respawns dead players at a random point once their respawn timer runs out
+pub fn s_respawn_players(
+    mut commands: Commands,
+    mut dead_players: Query<(Entity, &Player, &mut Respawns, &mut ActorContext, &mut Position), With<Dead>>,
+    time: Res<Time>,
+    bounds: Res<WorldBounds>,
+    bound_assets: Res<Assets<Bounds>>,
+) {
+    let Some(bounds) = bound_assets.get(&bounds.bounds) else { error!("World Bounds were not loaded!"); return; };
+    let mut rng = thread_rng();
+    for (entity, player, mut respawns, mut actor, mut position) in &mut dead_players
+    {
+        respawns.until_respawn -= time.delta_seconds();
+        if respawns.until_respawn > 0.0
+        {
+            continue;
+        }
+
+        position.0 = Vec2::new(rng.gen_range(bounds.min.x..=bounds.max.x), rng.gen_range(bounds.min.y..=bounds.max.y));
+        info!("{SERVER_STR} Respawning player '{}' at {}", player.0.get(), position.0);
+        revive(&mut actor);
+        commands.entity(entity).remove::<Dead>();
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_revive_restores_full_health()
+    {
+        let mut actor = ActorContext::default();
+        actor.set_stat(Stat::MaxHealth, 10.0);
+        actor.set_stat(Stat::Health, -2.0);
+        actor.last_damage_source = Some(DamageSource::Actor(Entity::from_raw(1)));
+
+        revive(&mut actor);
+        assert_eq!(actor.get_stat(&Stat::Health), Some(10.0));
+        assert_eq!(actor.last_damage_source, None);
+    }
+}
@@ -2,21 +2,25 @@ mod bounds;
 mod collision;
 mod common;
 mod enemies;
-mod upgrade;
+mod match_rules;
+mod talent;
 
 pub mod actor;
 pub mod classes;
 pub mod objects;
+pub mod upgrade;
 
 pub use common::{Position, Velocity, Orientation, VelocityDamping, Dead, Lifetime, DestroyIfNoMatchWithin, Knockback};
 pub use collision::Damageable;
 pub use enemies::{Enemy, EnemySpawnType, WaveOverseer};
-pub use upgrade::{AvailablePlayerUpgrades, ChosenUpgrade, GeneratedAvailableUpgrades, Upgrade, UpgradeBehaviour};
+pub use match_rules::{MatchMode, MatchRules, Scoreboard};
+pub use talent::{ChosenTalent, ClassTalents, PlayerTalents, TalentNode, can_unlock};
+pub use upgrade::{ChosenUpgrade, GeneratedAvailableUpgrades, RerollUpgrades, Upgrade, UpgradeSeed};
 
 
 use bevy::prelude::*;
 use bevy_replicon::prelude::*;
-use crate::simple::state;
+use crate::simple::{setup, state};
 
 pub struct GameplayPlugin;
 
@@ -33,7 +37,7 @@ impl Plugin for GameplayPlugin
             .add_systems(FixedUpdate, (
                 enemies::spawning::s_tick_wave_overseer,
                 enemies::spawning::s_tick_next_wave,
-            ).in_set(state::AuthoritySystems).in_set(state::FightingSystems))
+            ).in_set(state::AuthoritySystems).in_set(state::FightingSystems).run_if(match_rules::enemy_waves_enabled))
             //   Client only systems v
             .add_systems(FixedUpdate, (
                 enemies::spawning::c_receive_next_wave,
@@ -77,17 +81,53 @@ impl Plugin for GameplayPlugin
             // Classes v
             .add_plugins(classes::ClassesPlugin)
             // Upgrade v
+            .init_resource::<upgrade::UpgradeSeed>()
             .add_client_event::<upgrade::ChosenUpgrade>(ChannelKind::Unordered)
+            .add_client_event::<upgrade::RerollUpgrades>(ChannelKind::Unordered)
             .add_server_event::<upgrade::GeneratedAvailableUpgrades>(ChannelKind::Unordered)
             .add_systems(FixedUpdate, (
+                upgrade::s_setup_player_upgrades,
                 upgrade::s_receive_chosen_upgrades,
+                upgrade::s_receive_upgrade_rerolls,
             ).in_set(state::AuthoritySystems))
+            .add_systems(FixedUpdate, upgrade::s_apply_upgrades_on_class_change.after(talent::s_apply_talents_on_class_change).in_set(state::AuthoritySystems))
             .add_systems(OnEnter(state::InGameState::Break), upgrade::s_generate_and_emit_available_upgrades.run_if(has_authority))
+            .add_systems(OnEnter(state::GameState::InGame), upgrade::s_warn_about_invalid_upgrades.run_if(has_authority))
+            // Talents v
+            .replicate::<talent::PlayerTalents>()
+            .add_client_event::<talent::ChosenTalent>(ChannelKind::Ordered)
+            .add_systems(FixedUpdate, talent::s_receive_chosen_talents.in_set(state::AuthoritySystems).in_set(state::BreakSystems))
+            .init_resource::<talent::TalentProgress>()
+            .add_systems(Startup, talent::s_load_talent_progress.run_if(setup::is_server_instance))
+            .add_systems(FixedUpdate, (
+                talent::s_restore_talent_progress.before(talent::s_apply_talents_on_class_change),
+                talent::s_save_talent_progress.after(talent::s_restore_talent_progress).after(talent::s_receive_chosen_talents),
+            ).in_set(state::AuthoritySystems))
+            .add_systems(FixedUpdate, talent::s_apply_talents_on_class_change.after(classes::s_setup_initial_class).in_set(state::AuthoritySystems))
+            .add_systems(OnEnter(state::InGameState::Break), talent::s_award_talent_points.run_if(has_authority))
             // Collision v
             .add_systems(FixedUpdate, (
                 collision::s_collision_projectiles_damage,
                 collision::s_tick_damageable,
+                collision::s_tick_damage_hits,
             ).in_set(state::AuthoritySystems).in_set(state::FightingSystems))
+            // Match rules v
+            .init_resource::<match_rules::MatchRules>()
+            .init_resource::<match_rules::Scoreboard>()
+            .add_server_event::<match_rules::ScoreboardEvent>(ChannelKind::Ordered)
+            .add_systems(FixedUpdate, (
+                match_rules::s_assign_player_factions,
+                match_rules::s_apply_pvp_collision_groups,
+                match_rules::s_broadcast_scoreboard,
+            ).in_set(state::AuthoritySystems))
+            .add_systems(FixedUpdate, (
+                match_rules::s_track_kills,
+                match_rules::s_queue_player_respawns,
+                match_rules::s_respawn_players,
+            ).in_set(state::AuthoritySystems).in_set(state::FightingSystems))
+            .add_systems(FixedUpdate, (
+                match_rules::c_receive_scoreboard,
+            ).in_set(state::ClientSystems))
             // Bounds v
             .add_systems(FixedUpdate, (
                 bounds::cs_restrict_players_to_bounds,
@@ -0,0 +1,345 @@
+use bevy::{prelude::*, sprite::Anchor};
+use bevy_rapier2d::{geometry::CollisionGroups, pipeline::QueryFilter, plugin::RapierContext};
+use bevy_replicon::prelude::*;
+
+use serde::{Serialize, Deserialize};
+
+use crate::simple::{
+    gameplay::{Position, Lifetime, Dead, DestroyIfNoMatchWithin},
+    player::{ActionInput, LocalAim, LocalPlayerId, Player, SimpleAction},
+    consts::{BEAM_CHANNEL_TIMEOUT, BEAM_REPLICATION_TOLERANCE},
+};
+
+use super::{
+    ReplicatedObject,
+    super::actor::DamageEvent,
+};
+
+
+/// A laser that is channeled for as long as its owner holds the ability down, following their aim every tick.
+/// The beam's `Position` is where it starts, and [`BeamEnd`] is where it is cut off, both are only replicated when they move noticeably
+#[derive(Component, Serialize, Deserialize)]
+pub struct Beam
+{
+    pub owner: Entity, // DO NOT USE ON CLIENTS, THIS ENTITY DOES NOT GET MAPPED
+    /// The client channeling the beam, clients use this instead of the owner to find their own beams
+    pub owning_client: ClientId,
+    pub color: Color,
+    pub width: f32,
+    pub length: f32, // The furthest the beam can reach
+    pub duration: f32, // The longest the beam can be channeled for
+    pub damage_per_second: f32,
+    /// Whether the beam passes through everything it hits, instead of stopping at the first
+    pub pierce: bool,
+    /// The direction the beam was first aimed in
+    pub direction: Vec2,
+}
+
+/// Where a beam is cut off, either at its full length or the first thing blocking it
+#[derive(Component, Clone, Copy, Debug, Deref, DerefMut, PartialEq, Serialize, Deserialize)]
+pub struct BeamEnd(pub Vec2);
+
+/// Server side state of a beam being channeled
+#[derive(Component)]
+pub struct BeamChannel
+{
+    pub direction: Vec2,
+    /// Seconds since the owner last sent where they are aiming, the beam stops once this reaches BEAM_CHANNEL_TIMEOUT
+    pub since_aim: f32,
+}
+
+/// Sent from clients to the server every tick they are channeling a beam, with the direction they are aiming.
+/// There is no message for letting go, beams stop when these stop arriving so a dropped message can not leave one stuck on
+#[derive(Event, Serialize, Deserialize, Debug)]
+pub struct BeamAim
+{
+    pub direction: Vec2,
+}
+
+/// The action holding down the local player's beam, set when they use a beam ability and cleared when the channel ends
+#[derive(Resource, Default)]
+pub struct LocalBeamChannel
+{
+    pub action: Option<SimpleAction>,
+    /// Whether the local player's beam has existed since the channel started, so it disappearing ends the channel
+    seen_beam: bool,
+}
+
+impl LocalBeamChannel
+{
+    pub fn start(&mut self, action: SimpleAction)
+    {
+        self.action = Some(action);
+        self.seen_beam = false;
+    }
+
+    pub fn end(&mut self)
+    {
+        self.action = None;
+        self.seen_beam = false;
+    }
+}
+
+#[derive(Bundle)]
+pub struct BeamReplicationBundle
+{
+    pub beam: Beam,
+    pub position: Position,
+    pub end: BeamEnd,
+    pub groups: CollisionGroups,
+    pub replication: Replication,
+}
+
+#[derive(Bundle)]
+pub struct BeamAuthorityBundle
+{
+    pub transform: TransformBundle,
+    pub lifetime: Lifetime,
+    pub channel: BeamChannel,
+    pub name: Name,
+}
+
+#[derive(Bundle)]
+pub struct BeamExtrasBundle
+{
+    pub sprite_bundle: SpriteBundle,
+    pub validation: DestroyIfNoMatchWithin,
+}
+
+impl BeamReplicationBundle
+{
+    pub fn new(beam: Beam, position: Vec2, groups: CollisionGroups) -> Self
+    {
+        let end = position + beam.direction * beam.length;
+        Self
+        {
+            beam,
+            position: Position(position),
+            end: BeamEnd(end),
+            groups,
+            replication: Replication,
+        }
+    }
+}
+
+impl BeamAuthorityBundle
+{
+    pub fn new(beam: &Beam, position: Vec2) -> Self
+    {
+        Self
+        {
+            transform: TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            lifetime: Lifetime(beam.duration),
+            channel: BeamChannel { direction: beam.direction, since_aim: 0.0 },
+            name: format!("Beam of '{:?}'", beam.owner).into(),
+        }
+    }
+}
+
+impl BeamExtrasBundle
+{
+    pub fn new(beam: &Beam, position: Vec2) -> Self
+    {
+        Self
+        {
+            sprite_bundle: SpriteBundle
+            {
+                // Anchored at the start, so the sprite only needs rotating and stretching towards the end
+                sprite: Sprite { color: beam.color, custom_size: Some(Vec2::new(0.0, beam.width)), anchor: Anchor::CenterLeft, ..default() },
+                transform: Transform::from_translation(position.extend(0.0)),
+                ..default()
+            },
+            validation: DestroyIfNoMatchWithin::default(),
+        }
+    }
+}
+
+impl ReplicatedObject for Beam
+{
+    type AuthorityBundle = BeamAuthorityBundle;
+    type ExtrasBundle = BeamExtrasBundle;
+
+    fn authority_bundle(&self, position: Vec2) -> Self::AuthorityBundle {
+        BeamAuthorityBundle::new(self, position)
+    }
+
+    fn extras_bundle(&self, position: Vec2) -> Self::ExtrasBundle {
+        BeamExtrasBundle::new(self, position)
+    }
+}
+
+/// How far a beam reaches and what it hits, given everything along its full length (in any order) and their distances.
+/// A piercing beam reaches its full length and hits everything, otherwise it is cut off at the first hit
+pub fn beam_hits(mut hits: Vec<(Entity, f32)>, length: f32, pierce: bool) -> (f32, Vec<Entity>)
+{
+    hits.retain(|(_, distance)| *distance <= length);
+    hits.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    if pierce
+    {
+        return (length, hits.into_iter().map(|(entity, _)| entity).collect());
+    }
+    match hits.first()
+    {
+        Some((entity, distance)) => (*distance, vec![*entity]),
+        None => (length, Vec::new()),
+    }
+}
+
+/// Whether a replicated point has moved far enough from where clients last saw it to be worth replicating again
+pub fn moved_noticeably(replicated: Vec2, actual: Vec2) -> bool
+{
+    replicated.distance_squared(actual) > BEAM_REPLICATION_TOLERANCE * BEAM_REPLICATION_TOLERANCE
+}
+
+/// This system (Authority only) points beams where their owners are aiming
+pub fn s_receive_beam_aims(
+    mut aims: EventReader<FromClient<BeamAim>>,
+    players: Query<(Entity, &Player)>,
+    mut beams: Query<(&Beam, &mut BeamChannel)>,
+) {
+    for FromClient { client_id, event } in aims.read()
+    {
+        let Some((player_ent, _)) = players.iter().find(|(_, player)| &player.0 == client_id) else { continue; };
+        let direction = event.direction.normalize_or_zero();
+        if direction == Vec2::ZERO
+        {
+            continue;
+        }
+        for (beam, mut channel) in &mut beams
+        {
+            if beam.owner == player_ent
+            {
+                channel.direction = direction;
+                channel.since_aim = 0.0;
+            }
+        }
+    }
+}
+
+/// Type alias for the beams being channeled
+type ChanneledBeams<'w, 's> = Query<'w, 's, (Entity, &'static Beam, &'static CollisionGroups, &'static mut BeamChannel, &'static mut Position, &'static mut BeamEnd), Without<Dead>>;
+
+/// This system (Authority only) re-casts every beam from its owner, damaging what it hits and stopping beams that are no longer channeled
+pub fn s_update_beams(
+    mut commands: Commands,
+    mut beams: ChanneledBeams,
+    owners: Query<&Position, (Without<Beam>, Without<Dead>)>,
+    rapier_context: Res<RapierContext>,
+    mut damage_events: EventWriter<DamageEvent>,
+    time: Res<Time>,
+) {
+    for (entity, beam, groups, mut channel, mut position, mut end) in &mut beams
+    {
+        channel.since_aim += time.delta_seconds();
+        let Ok(owner_pos) = owners.get(beam.owner) else
+        {
+            commands.entity(entity).insert(Dead);
+            continue;
+        };
+        if channel.since_aim >= BEAM_CHANNEL_TIMEOUT
+        {
+            commands.entity(entity).insert(Dead);
+            continue;
+        }
+
+        let start = owner_pos.0;
+        let filter = QueryFilter::new()
+            .groups(*groups)
+            .exclude_sensors();
+        let mut candidates = Vec::new();
+        if beam.pierce
+        {
+            rapier_context.intersections_with_ray(start, channel.direction, beam.length, true, filter, |hit, intersection| {
+                candidates.push((hit, intersection.toi));
+                true
+            });
+        }
+        else
+        {
+            candidates.extend(rapier_context.cast_ray(start, channel.direction, beam.length, true, filter));
+        }
+        let (length, hits) = beam_hits(candidates, beam.length, beam.pierce);
+
+        for victim in hits
+        {
+            damage_events.send(DamageEvent { instigator: beam.owner, victim, damage: beam.damage_per_second * time.delta_seconds() });
+        }
+
+        // Only touching the endpoints when they move noticeably keeps a held still beam from being re-replicated every tick
+        let new_end = start + channel.direction * length;
+        if moved_noticeably(position.0, start)
+        {
+            position.0 = start;
+        }
+        if moved_noticeably(end.0, new_end)
+        {
+            end.0 = new_end;
+        }
+    }
+}
+
+/// Sends where the local player is aiming to the server while they hold down the ability channeling their beam.
+/// The channel ends when they let go, or when their beam is gone (it ran out, or the server rejected it)
+pub fn c_send_beam_aim(
+    aim: Res<LocalAim>,
+    local_player: Res<LocalPlayerId>,
+    mut channel: ResMut<LocalBeamChannel>,
+    input: ActionInput,
+    beams: Query<&Beam>,
+    mut beam_aims: EventWriter<BeamAim>,
+) {
+    let Some(action) = channel.action else { return; };
+    let owns_beam = beams.iter().any(|beam| beam.owning_client.get() == local_player.id);
+    if !input.pressed(action) || (channel.seen_beam && !owns_beam)
+    {
+        channel.end();
+        return;
+    }
+    if !owns_beam
+    {
+        // The beam has not been spawned (or replicated) yet
+        return;
+    }
+    channel.seen_beam = true;
+    let Some(direction) = aim.direction else { return; };
+    beam_aims.send(BeamAim { direction });
+}
+
+/// Type alias for the beam sprites that need updating
+type MovedBeamSprites<'w, 's> = Query<'w, 's, (&'static Position, &'static BeamEnd, &'static Beam, &'static mut Sprite, &'static mut Transform), Or<(Changed<Position>, Changed<BeamEnd>, Added<Sprite>)>>;
+
+/// Stretches and rotates beam sprites between their replicated endpoints
+pub fn c_update_beam_sprites(
+    mut beams: MovedBeamSprites,
+) {
+    for (position, end, beam, mut sprite, mut transform) in &mut beams
+    {
+        let offset = end.0 - position.0;
+        sprite.custom_size = Some(Vec2::new(offset.length(), beam.width));
+        transform.rotation = Quat::from_rotation_z(offset.y.atan2(offset.x));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_beam_stops_at_first_hit_unless_piercing()
+    {
+        let (near, far, beyond) = (Entity::from_raw(1), Entity::from_raw(2), Entity::from_raw(3));
+        let hits = vec![(far, 80.0), (near, 30.0), (beyond, 150.0)];
+
+        assert_eq!(beam_hits(hits.clone(), 100.0, false), (30.0, vec![near]));
+        assert_eq!(beam_hits(hits, 100.0, true), (100.0, vec![near, far]));
+        assert_eq!(beam_hits(Vec::new(), 100.0, false), (100.0, Vec::new()));
+    }
+
+    #[test]
+    fn test_moved_noticeably()
+    {
+        assert!(!moved_noticeably(Vec2::ZERO, Vec2::new(BEAM_REPLICATION_TOLERANCE * 0.5, 0.0)));
+        assert!(moved_noticeably(Vec2::ZERO, Vec2::new(0.0, BEAM_REPLICATION_TOLERANCE * 2.0)));
+    }
+}
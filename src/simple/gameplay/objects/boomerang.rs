@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use bevy_replicon::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use crate::simple::{
+    gameplay::{
+        actor::{ActorChild, ChildType, Damage, DamageKnockback},
+        Position, Velocity, Lifetime, DestroyIfNoMatchWithin, Dead,
+    },
+    consts::*,
+};
+
+use super::ReplicatedObject;
+
+
+/// A projectile that flies outwards, slows to a stop, then flies back to its owner.
+/// It can hit each enemy once on the way out and once on the way back
+#[derive(Component, Serialize, Deserialize)]
+pub struct Boomerang
+{
+    pub owner: Entity, // DO NOT USE ON CLIENTS, THIS ENTITY DOES NOT GET MAPPED
+    pub size: f32,
+    pub damage: f32,
+    pub knockback: f32,
+    pub deceleration: f32,
+    pub return_speed: f32,
+    pub returning: bool,
+}
+
+/// This bundle contains all the components a boomerang has that will be sent across the wire from server to clients.
+///
+/// This is the bundle to use to create a new boomerang, the other bundles are attached as appropriate (see [`ReplicatedObject`])
+#[derive(Bundle)]
+pub struct BoomerangReplicationBundle
+{
+    boomerang: Boomerang,
+    position: Position,
+    velocity: Velocity,
+    replication: Replication,
+}
+
+/// This bundle contains all the components a boomerang needs on the server to work properly
+///
+/// This bundle should only really be added by the object authority system
+#[derive(Bundle)]
+pub struct BoomerangAuthorityBundle
+{
+    transform: TransformBundle,
+    damage: Damage,
+    child: ActorChild,
+    lifetime: Lifetime,
+    collider: Collider,
+    group: CollisionGroups,
+    sensor: Sensor,
+    collision_types: ActiveCollisionTypes,
+    name: Name,
+}
+
+/// This bundle contains all the extra components created from the replication components
+///
+/// This bundle should only be added by the object extras system
+#[derive(Bundle)]
+pub struct BoomerangExtrasBundle
+{
+    sprite_bundle: SpriteBundle,
+    validation: DestroyIfNoMatchWithin,
+}
+
+impl BoomerangReplicationBundle
+{
+    pub fn new(boomerang: Boomerang, position: Vec2, velocity: Vec2) -> Self
+    {
+        Self
+        {
+            boomerang,
+            position: Position(position),
+            velocity: Velocity(velocity),
+            replication: Replication,
+        }
+    }
+}
+
+impl BoomerangAuthorityBundle
+{
+    pub fn new(position: Vec2, boomerang: &Boomerang) -> Self
+    {
+        Self
+        {
+            transform: TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            damage: Damage::new(boomerang.damage, false, false, Some(DamageKnockback::RepulsionFromSelf { strength: boomerang.knockback })),
+            child: ActorChild { ability_type: ChildType::Boomerang, parent_actor: boomerang.owner },
+            lifetime: Lifetime(RANGED_BOOMERANG_LIFETIME),
+            collider: Collider::ball(boomerang.size),
+            group: CollisionGroups { memberships: PLAYER_PROJECTILE_GROUP, filters: PLAYER_PROJECTILE_FILTER },
+            sensor: Sensor,
+            collision_types: ActiveCollisionTypes::STATIC_STATIC,
+            name: Name::new(format!("Boomerang of {:?}", boomerang.owner)),
+        }
+    }
+}
+
+impl BoomerangExtrasBundle
+{
+    pub fn new(position: Vec2, size: f32) -> Self
+    {
+        Self
+        {
+            sprite_bundle: SpriteBundle
+            {
+                sprite: Sprite { color: RANGED_BOOMERANG_COLOR, custom_size: Some(Vec2::splat(size * 2.0)), ..default() },
+                transform: Transform::from_translation(position.extend(0.0)),
+                ..default()
+            },
+            validation: DestroyIfNoMatchWithin::default(),
+        }
+    }
+}
+
+impl ReplicatedObject for Boomerang
+{
+    type AuthorityBundle = BoomerangAuthorityBundle;
+    type ExtrasBundle = BoomerangExtrasBundle;
+
+    fn authority_bundle(&self, position: Vec2) -> Self::AuthorityBundle {
+        BoomerangAuthorityBundle::new(position, self)
+    }
+
+    fn extras_bundle(&self, position: Vec2) -> Self::ExtrasBundle {
+        BoomerangExtrasBundle::new(position, self.size)
+    }
+}
+
+/// This system (Authority only) slows boomerangs down until they turn around, then flies them back to their owner
+/// Boomerangs forget who they have hit when turning around, and are destroyed once caught (or if their owner no longer exists)
+pub fn s_move_boomerangs(
+    mut commands: Commands,
+    mut boomerangs: Query<(Entity, &mut Boomerang, &Position, &mut Velocity, &mut Damage)>,
+    owners: Query<&Position, Without<Boomerang>>,
+    time: Res<Time>,
+) {
+    for (entity, mut boomerang, position, mut velocity, mut damage) in &mut boomerangs
+    {
+        let Ok(owner_position) = owners.get(boomerang.owner) else
+        {
+            commands.entity(entity).insert(Dead);
+            continue;
+        };
+
+        if !boomerang.returning
+        {
+            let speed = velocity.0.length();
+            let new_speed = speed - boomerang.deceleration * time.delta_seconds();
+            if new_speed > 0.0
+            {
+                velocity.0 *= new_speed / speed;
+                continue;
+            }
+            boomerang.returning = true;
+            damage.hits.clear();
+        }
+
+        let to_owner = owner_position.0 - position.0;
+        if to_owner.length_squared() <= RANGED_BOOMERANG_CATCH_RADIUS * RANGED_BOOMERANG_CATCH_RADIUS
+        {
+            commands.entity(entity).insert(Dead);
+            continue;
+        }
+        velocity.0 = to_owner.normalize_or_zero() * boomerang.return_speed;
+    }
+}
@@ -6,7 +6,10 @@ use serde::{Serialize, Deserialize};
 
 use crate::simple::gameplay::{Position, Lifetime, DestroyIfNoMatchWithin};
 
-use super::super::actor::{ActorChild, ChildType, Damage, DamageKnockback};
+use super::{
+    ReplicatedObject,
+    super::actor::{ActorChild, ChildType, Damage, DamageKnockback},
+};
 
 
 #[derive(Component, Serialize, Deserialize)]
@@ -99,27 +102,16 @@ impl ExplosionExtrasBundle
 }
 
 
-pub fn s_explosion_authority(
-    mut commands: Commands,
-    new_explosions: Query<(Entity, &Explosion, &Position), Added<Replication>>,
-) {
-    for (entity, expl, pos) in &new_explosions
-    {
-        let Some(mut ent_coms) = commands.get_entity(entity) else { continue; };
+impl ReplicatedObject for Explosion
+{
+    type AuthorityBundle = ExplosionAuthorityBundle;
+    type ExtrasBundle = ExplosionExtrasBundle;
 
-        ent_coms.insert(ExplosionAuthorityBundle::new(expl.owner, expl.radius, pos.0));
+    fn authority_bundle(&self, position: Vec2) -> Self::AuthorityBundle {
+        ExplosionAuthorityBundle::new(self.owner, self.radius, position)
     }
-}
 
-pub fn c_explosion_extras(
-    mut commands: Commands,
-    new_explosions: Query<(Entity, &Explosion, &Position), Added<Replication>>,
-) {
-    for (entity, expl, pos) in &new_explosions
-    {
-        let Some(mut ent_coms) = commands.get_entity(entity) else { continue; };
-
-        ent_coms.insert(ExplosionExtrasBundle::new(expl.radius, pos.0));
+    fn extras_bundle(&self, position: Vec2) -> Self::ExtrasBundle {
+        ExplosionExtrasBundle::new(self.radius, position)
     }
 }
-
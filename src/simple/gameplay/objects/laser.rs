@@ -1,13 +1,15 @@
 use bevy::prelude::*;
-use bevy_rapier2d::geometry::{CollisionGroups, Collider, ActiveCollisionTypes};
+use bevy_rapier2d::geometry::{CollisionGroups, Collider, ActiveCollisionTypes, Sensor};
 use bevy_replicon::core::replication_rules::Replication;
 use serde::{Serialize, Deserialize};
 
 use crate::simple::{
-    gameplay::{Position, Lifetime, DestroyIfNoMatchWithin, actor::{Damage, DamageKnockback}},
+    gameplay::{Position, Lifetime, DestroyIfNoMatchWithin, actor::{ActorChild, ChildType, Damage, DamageKnockback}},
     consts::DEFAULT_LASER_WIDTH
 };
 
+use super::ReplicatedObject;
+
 #[derive(Bundle)]
 pub struct LaserReplicationBundle
 {
@@ -30,13 +32,17 @@ pub struct LaserAuthorityBundle
 {
     pub transform: TransformBundle,
     pub lifetime: Lifetime,
+    pub child: ActorChild,
     pub collider: Collider,
+    pub sensor: Sensor,
     pub collision_types: ActiveCollisionTypes,
+    pub name: Name,
 }
 
 #[derive(Component, Serialize, Deserialize)]
 pub struct Laser
 {
+    pub owner: Entity, // DO NOT USE ON CLIENTS, THIS ENTITY DOES NOT GET MAPPED
     pub color: Color,
     pub length: f32,
     pub direction: Vec2,
@@ -45,14 +51,15 @@ pub struct Laser
 
 impl LaserReplicationBundle
 {
-    pub fn new(color: Color, length: f32, position: Vec2, direction: Vec2, damage: f32, knockback: f32, groups: CollisionGroups) -> Self
+    /// Lasers start at `position` and reach `laser.length` along its direction
+    pub fn new(laser: Laser, position: Vec2, damage: f32, groups: CollisionGroups) -> Self
     {
-        let real_pos = position + direction * length * 0.5;
+        let real_pos = position + laser.direction * laser.length * 0.5;
         Self { 
-            laser: Laser { color, length, direction, knockback }, 
+            damage: Damage::new(damage, false, false, Some(DamageKnockback::Impulse(laser.direction * laser.knockback))),
+            laser, 
             position: Position(real_pos),
             groups,
-            damage: Damage::new(damage, false, false, Some(DamageKnockback::Impulse(direction * knockback))),
             replication: Replication
         }
     }
@@ -60,16 +67,19 @@ impl LaserReplicationBundle
 
 impl LaserAuthorityBundle
 {
-    pub fn new(length: f32, direction: Vec2, position: Vec2, knockback: f32) -> Self
+    pub fn new(laser: &Laser, position: Vec2) -> Self
     {
         Self {
             transform: TransformBundle { 
-                local: Transform::from_translation(position.extend(0.0)) * Transform::from_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x))), 
+                local: Transform::from_translation(position.extend(0.0)) * Transform::from_rotation(Quat::from_rotation_z(laser.direction.y.atan2(laser.direction.x))), 
                 ..default()
             },
             lifetime: Lifetime(0.5),
-            collider: Collider::cuboid(length * 0.5, DEFAULT_LASER_WIDTH * 0.5),
+            child: ActorChild { ability_type: ChildType::Projectile, parent_actor: laser.owner },
+            collider: Collider::cuboid(laser.length * 0.5, DEFAULT_LASER_WIDTH * 0.5),
+            sensor: Sensor,
             collision_types: ActiveCollisionTypes::STATIC_STATIC,
+            name: Name::new(format!("Laser of {:?}", laser.owner)),
         }
     }
 }
@@ -90,27 +100,26 @@ impl LaserExtrasBundle
 }
 
 
-pub fn s_laser_authority(
-    mut commands: Commands,
-    new_lasers: Query<(Entity, &Laser, &Position), Added<Replication>>,
-) {
-    for (entity, laser, position) in &new_lasers
-    {
-        let Some(mut ent_coms) = commands.get_entity(entity) else { continue; };
+impl ReplicatedObject for Laser
+{
+    type AuthorityBundle = LaserAuthorityBundle;
+    type ExtrasBundle = LaserExtrasBundle;
 
-        ent_coms.insert(LaserAuthorityBundle::new(laser.length, laser.direction, position.0, laser.knockback));
+    fn authority_bundle(&self, position: Vec2) -> Self::AuthorityBundle {
+        LaserAuthorityBundle::new(self, position)
+    }
+
+    fn extras_bundle(&self, position: Vec2) -> Self::ExtrasBundle {
+        LaserExtrasBundle::new(self.color, self.length, self.direction, position)
     }
 }
 
-pub fn c_laser_extras(
-    mut commands: Commands,
-    new_lasers: Query<(Entity, &Laser, &Position), Added<Replication>>,
+/// This system resizes laser sprites when their length changes, e.g. when a laser predicted by a client was cut short on the server
+pub fn c_update_laser_sprites(
+    mut lasers: Query<(&Laser, &mut Sprite), Changed<Laser>>,
 ) {
-    for (entity, laser, position) in &new_lasers
+    for (laser, mut sprite) in &mut lasers
     {
-        let Some(mut ent_coms) = commands.get_entity(entity) else { continue; };
-
-        ent_coms.insert(LaserExtrasBundle::new(laser.color, laser.length, laser.direction, position.0));
+        sprite.custom_size = Some(Vec2::new(laser.length, DEFAULT_LASER_WIDTH));
     }
 }
-
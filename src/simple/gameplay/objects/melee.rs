@@ -4,8 +4,10 @@ use bevy_rapier2d::prelude::*;
 
 use serde::{Deserialize, Serialize};
 
-use crate::simple::gameplay::{Position, Lifetime, DestroyIfNoMatchWithin, actor::{Damage, DamageKnockback}};
-use crate::simple::consts::{PLAYER_PROJECTILE_GROUP, ENEMY_GROUP};
+use crate::simple::gameplay::{Position, Lifetime, DestroyIfNoMatchWithin, actor::{ActorChild, ChildType, Damage, DamageKnockback}};
+use crate::simple::consts::{PLAYER_PROJECTILE_GROUP, ENEMY_GROUP, MELEE_ATTACK_OFFSET};
+
+use super::ReplicatedObject;
 
 #[derive(Serialize, Deserialize)]
 pub enum MeleeAttackType
@@ -17,6 +19,7 @@ pub enum MeleeAttackType
 pub struct MeleeAttackData
 {
     pub owning_client: ClientId,
+    pub owner: Entity,
     pub damage: f32,
     pub position: Vec2,
     pub direction: Vec2,
@@ -27,6 +30,7 @@ pub struct MeleeAttackData
 pub struct MeleeAttack
 {
     pub owning_client: ClientId,
+    pub owner: Entity,
     pub damage: f32,
     pub direction: Vec2,
     pub attack_type: MeleeAttackType,
@@ -34,7 +38,7 @@ pub struct MeleeAttack
 
 /// This bundle should contain all the components a melee attack needs to send across the wire from server to clients.
 /// 
-/// This is the bundle to use to create a new melee attack. s_melee_authority and c_melee_extras will attach the other bundles as appropriate
+/// This is the bundle to use to create a new melee attack, the other bundles are attached as appropriate (see [`ReplicatedObject`])
 /// 
 /// Replication bundles contain the bare minimum required information.
 /// All other components/bundles required for a bullet should be creatable using components in this bundle
@@ -48,25 +52,28 @@ pub struct MeleeReplicationBundle
 
 /// This bullet bundle contains all the components a bullet needs on the server to work properly
 /// 
-/// This bundle should only really be added inside an authority_system
+/// This bundle should only really be added by the object authority system
 #[derive(Bundle)]
-struct MeleeAuthorityBundle
+pub struct MeleeAuthorityBundle
 {
     transform: TransformBundle, // e.g. a transform (but not sprite) bundle
     damage: Damage, 
+    child: ActorChild,
     lifetime: Lifetime,
     collider: Collider,
+    sensor: Sensor,
     group: CollisionGroups,
-    collision_types: ActiveCollisionTypes
+    collision_types: ActiveCollisionTypes,
+    name: Name,
 }
 
 /// This bundle should contain all the extra components needed for use on clients (and hosts)
 /// 
 /// All components should in this bundle should be sourceable from the replication bundle
 /// 
-/// This bundle should only be added inside an extras_system
+/// This bundle should only be added by the object extras system
 #[derive(Bundle)]
-struct MeleeExtrasBundle
+pub struct MeleeExtrasBundle
 {
     sprite_bundle: SpriteBundle,
     validation: DestroyIfNoMatchWithin,
@@ -81,40 +88,72 @@ impl MeleeReplicationBundle
             melee: MeleeAttack 
             { 
                 owning_client: melee_attack.owning_client, 
+                owner: melee_attack.owner,
                 damage: melee_attack.damage, 
                 direction: melee_attack.direction,
                 attack_type: melee_attack.attack_type
             },
-            position: Position(melee_attack.position + melee_attack.direction * 20.0),
+            position: Position(melee_attack.position + melee_attack.direction * MELEE_ATTACK_OFFSET),
             replication: Replication,
         }
     }
 }
 
+impl MeleeAttackType
+{
+    /// The size of the area this attack covers (before rotation)
+    pub fn size(&self) -> Vec2
+    {
+        match self
+        {
+            Self::Stab { length, width, .. } => Vec2::new(*length, *width),
+            Self::Circular { radius, .. } => Vec2::splat(*radius * 2.0),
+        }
+    }
+
+    pub fn rotation(&self) -> Quat
+    {
+        match self
+        {
+            Self::Stab { direction, .. } => Quat::from_rotation_z(direction.y.atan2(direction.x)),
+            Self::Circular { .. } => Quat::IDENTITY,
+        }
+    }
+}
+
 impl MeleeAuthorityBundle
 {
-    pub fn new(pos: Vec2, direction: Vec2, damage: f32, ) -> Self
+    pub fn new(pos: Vec2, direction: Vec2, damage: f32, attack_type: &MeleeAttackType, owner: Entity) -> Self
     {
+        let (collider, knockback) = match attack_type
+        {
+            MeleeAttackType::Stab { length, width, .. } => (Collider::cuboid(length * 0.5, width * 0.5), DamageKnockback::Impulse(direction * 350.0)),
+            MeleeAttackType::Circular { radius, .. } => (Collider::ball(*radius), DamageKnockback::RepulsionFromSelf { strength: 350.0 }),
+        };
         Self
         {
-            transform: TransformBundle { local: Transform::from_translation(pos.extend(0.0)), ..default() },
-            damage: Damage::new(damage, true, false, Some(DamageKnockback::Impulse(direction * 350.0))),
+            transform: TransformBundle { local: Transform::from_translation(pos.extend(0.0)).with_rotation(attack_type.rotation()), ..default() },
+            // Not destroyed on the first hit, so swings and spins hit every enemy in their area (each enemy at most once)
+            damage: Damage::new(damage, false, false, Some(knockback)),
+            child: ActorChild { ability_type: ChildType::Melee, parent_actor: owner },
             lifetime: Lifetime(0.15),
-            collider: Collider::ball(15.0),
+            collider,
+            sensor: Sensor,
             group: CollisionGroups { memberships: PLAYER_PROJECTILE_GROUP, filters: ENEMY_GROUP },
-            collision_types: ActiveCollisionTypes::STATIC_STATIC
+            collision_types: ActiveCollisionTypes::STATIC_STATIC,
+            name: Name::new(format!("Melee attack of {owner:?}")),
         }
     }
 }
 
 impl MeleeExtrasBundle
 {
-    pub fn new(pos: Vec2) -> Self
+    pub fn new(pos: Vec2, attack_type: &MeleeAttackType) -> Self
     {
         Self { 
             sprite_bundle: SpriteBundle { 
-                sprite: Sprite { color: Color::rgb(0.3, 0.3, 0.7), custom_size: Some(Vec2::new(15.0, 15.0)), ..default() }, 
-                transform: Transform::from_translation(pos.extend(0.0)), 
+                sprite: Sprite { color: Color::rgb(0.3, 0.3, 0.7), custom_size: Some(attack_type.size()), ..default() }, 
+                transform: Transform::from_translation(pos.extend(0.0)).with_rotation(attack_type.rotation()), 
                 ..default() 
             },
             validation: DestroyIfNoMatchWithin::default(),
@@ -122,31 +161,16 @@ impl MeleeExtrasBundle
     }
 }
 
-/// This melee authority system is responsible for adding the Authority bundle to a newly created entity
-/// This will only happen on the server
-pub fn s_melee_authority(
-    mut commands: Commands,
-    received_bullets: Query<(Entity, &MeleeAttack, &Position), Added<Replication>>
-) {
-    for (entity, melee, position) in &received_bullets
-    {
-        // SAFETY: entity is from a query and should never not exist
-        commands.entity(entity).insert(MeleeAuthorityBundle::new(position.0, melee.direction, melee.damage));
+impl ReplicatedObject for MeleeAttack
+{
+    type AuthorityBundle = MeleeAuthorityBundle;
+    type ExtrasBundle = MeleeExtrasBundle;
+
+    fn authority_bundle(&self, position: Vec2) -> Self::AuthorityBundle {
+        MeleeAuthorityBundle::new(position, self.direction, self.damage, &self.attack_type, self.owner)
     }
-}
 
-/// This template extras system is responsible for adding the Extras bundle to newly created/replicated entities
-/// This will happen on clients and hosts (not dedicated servers)
-pub fn c_melee_extras(
-    mut commands: Commands,
-    received_bullets: Query<(Entity, &Position), (With<MeleeAttack>, Added<Replication>)>,
-) {
-    for (entity,pos) in &received_bullets
-    {
-        // SAFETY: entity is from a query and should never not exist
-        commands.entity(entity).insert(MeleeExtrasBundle::new(pos.0));
+    fn extras_bundle(&self, position: Vec2) -> Self::ExtrasBundle {
+        MeleeExtrasBundle::new(position, &self.attack_type)
     }
 }
-
-
-
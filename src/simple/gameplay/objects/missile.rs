@@ -1,7 +1,7 @@
-use std::f32::consts::PI;
+use std::f32::consts::{PI, TAU};
 
 use bevy::prelude::*;
-use bevy_rapier2d::geometry::{ActiveCollisionTypes, Collider, CollisionGroups, Sensor};
+use bevy_rapier2d::{geometry::{ActiveCollisionTypes, Collider, CollisionGroups, Sensor}, dynamics::Velocity as RapierVelocity};
 use bevy_replicon::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -9,21 +9,58 @@ use crate::simple::{
     gameplay::{
         Position, Velocity, 
         VelocityDamping, Lifetime, 
-        Orientation, DestroyIfNoMatchWithin, 
+        Orientation, DestroyIfNoMatchWithin, Dead,
         actor::{ActorChild, ChildType, Damage, DamageKnockback}},
+    player::{LocalAim, LocalPlayerId, Player},
     consts::{
-        CLIENT_STR, RANGED_MAX_MISSILE_ACCELERATION, RANGED_MAX_MISSILE_ANGULAR_ACCELERATION, RANGED_MAX_MISSILE_SPEED, RANGED_MISSILE_COLOR, RANGED_MISSILE_LENGTH, RANGED_MISSILE_LIFETIME, RANGED_MISSILE_WIDTH
+        CLIENT_STR, MISSILE_NAVIGATION_CONSTANT, MISSILE_PURSUIT_GAIN, MISSILE_PURSUIT_MIN_ANGLE, RANGED_MAX_MISSILE_ACCELERATION, RANGED_MAX_MISSILE_ANGULAR_ACCELERATION, RANGED_MAX_MISSILE_SPEED, RANGED_MISSILE_COLOR, RANGED_MISSILE_LENGTH, RANGED_MISSILE_LIFETIME, RANGED_MISSILE_WIDTH
     }
 };
 
 
+/// How a missile picks what it steers towards
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+pub enum MissileGuidance
+{
+    /// Locks onto the nearest target at launch, and flies straight if that target dies
+    LockOn,
+    /// Locks onto the nearest target at launch, and picks the next nearest whenever its target dies
+    #[default]
+    Retarget,
+    /// Steers towards where its owner is aiming
+    CursorGuided,
+    /// Never steers
+    DumbFire,
+}
+
 #[derive(Component, Serialize, Deserialize)]
 pub struct Missile
 {
     pub max_speed: f32,
     pub max_acceleration: f32,
+    /// The fastest the missile can turn, in radians/s
     pub max_angular_acceleration: f32,
-    pub owner: Entity,
+    pub owner: Entity, // DO NOT USE ON CLIENTS, THIS ENTITY DOES NOT GET MAPPED
+    /// The client of the player that fired the missile, set by the server. None if it was not fired by a player
+    pub owning_client: Option<ClientId>,
+    pub guidance: MissileGuidance,
+}
+
+/// Server side state of what a missile is steering towards
+#[derive(Component, Default)]
+pub struct MissileSteering
+{
+    /// The entity being steered towards, None when not locked on to anything
+    pub target: Option<Entity>,
+    /// Where the owner is aiming, used by cursor guided missiles
+    pub guide_point: Option<Vec2>,
+}
+
+/// An event emitted from clients to the server with where they are aiming, which steers their cursor guided missiles
+#[derive(Event, Serialize, Deserialize)]
+pub struct MissileGuidePoint
+{
+    pub point: Vec2,
 }
 
 #[derive(Bundle)]
@@ -50,6 +87,7 @@ pub struct MissileAuthorityBundle
 {
     pub transform: TransformBundle,
     pub ability_owner: ActorChild,
+    pub steering: MissileSteering,
     pub lifetime: Lifetime,
     pub collider: Collider,
     pub sensor: Sensor,
@@ -66,8 +104,86 @@ impl Missile
             max_acceleration: RANGED_MAX_MISSILE_ACCELERATION,
             max_angular_acceleration: RANGED_MAX_MISSILE_ANGULAR_ACCELERATION,
             owner,
+            owning_client: None,
+            guidance: MissileGuidance::default(),
         }
     }
+
+    pub fn with_guidance(mut self, guidance: MissileGuidance) -> Self
+    {
+        self.guidance = guidance;
+        self
+    }
+
+    /// Whether the missile is steered by where the given client is aiming
+    pub fn is_guided_by(&self, client_id: ClientId) -> bool
+    {
+        self.guidance == MissileGuidance::CursorGuided && self.owning_client == Some(client_id)
+    }
+
+    /// Sets the fastest the missile can turn (radians/s), None keeps the default
+    pub fn with_turn_rate(mut self, turn_rate: Option<f32>) -> Self
+    {
+        if let Some(turn_rate) = turn_rate
+        {
+            self.max_angular_acceleration = turn_rate;
+        }
+        self
+    }
+}
+
+/// Wraps an angle (in radians) into the range [-PI, PI)
+pub fn wrap_angle(angle: f32) -> f32
+{
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+/// Whether something in `other` groups can be hit by something in `groups` (and vice versa)
+pub(super) fn groups_interact(groups: &CollisionGroups, other: &CollisionGroups) -> bool
+{
+    (groups.filters.bits() & other.memberships.bits()) != 0 &&
+    (other.filters.bits() & groups.memberships.bits()) != 0
+}
+
+/// The nearest of the candidates (entity, position) to the given position
+pub fn nearest_target(position: Vec2, candidates: impl Iterator<Item = (Entity, Vec2)>) -> Option<Entity>
+{
+    candidates
+        .map(|(entity, candidate_pos)| (entity, (candidate_pos - position).length_squared()))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+/// The nearest of the candidates (entity, position, groups) that something in `groups` at the given position can hit, optionally within a range.
+/// This is how missiles and homing projectiles pick what to steer towards
+pub fn nearest_hittable<'a>(position: Vec2, groups: &CollisionGroups, range: Option<f32>, candidates: impl Iterator<Item = (Entity, Vec2, &'a CollisionGroups)>) -> Option<Entity>
+{
+    nearest_target(position, candidates
+        .filter(|(_, candidate_pos, candidate_groups)|
+            groups_interact(groups, candidate_groups) &&
+            range.is_none_or(|range| candidate_pos.distance_squared(position) <= range * range)
+        )
+        .map(|(entity, candidate_pos, _)| (entity, candidate_pos))
+    )
+}
+
+/// The turn rate (radians/s) that steers a missile onto an intercept course with the target, using proportional navigation.
+/// The line of sight rate is scaled by the navigation constant, and a pursuit term turns the missile around when the target
+/// is far off its heading (e.g. behind it, where the line of sight barely rotates)
+pub fn proportional_navigation(missile_pos: Vec2, missile_vel: Vec2, orientation: f32, target_pos: Vec2, target_vel: Vec2) -> f32
+{
+    let relative_pos = target_pos - missile_pos;
+    let range_sq = relative_pos.length_squared();
+    if range_sq <= f32::EPSILON
+    {
+        return 0.0;
+    }
+    let relative_vel = target_vel - missile_vel;
+    let line_of_sight_rate = relative_pos.perp_dot(relative_vel) / range_sq;
+    let heading_error = wrap_angle(relative_pos.y.atan2(relative_pos.x) - orientation);
+    let pursuit = (heading_error.abs() - MISSILE_PURSUIT_MIN_ANGLE).max(0.0) * heading_error.signum();
+
+    MISSILE_NAVIGATION_CONSTANT * line_of_sight_rate + MISSILE_PURSUIT_GAIN * pursuit
 }
 
 impl MissileReplicationBundle
@@ -103,11 +219,12 @@ impl MissileExtrasBundle
 
 impl MissileAuthorityBundle
 {
-    pub fn new(transform: Transform, owning_actor: Entity) -> Self
+    pub fn new(transform: Transform, owning_actor: Entity, target: Option<Entity>) -> Self
     {
         Self {
             transform: TransformBundle::from_transform(transform),
             ability_owner: ActorChild{ ability_type: ChildType::Missile, parent_actor: owning_actor },
+            steering: MissileSteering { target, guide_point: None },
             lifetime: Lifetime(RANGED_MISSILE_LIFETIME),
             sensor: Sensor,
             damping: VelocityDamping(0.9),
@@ -119,21 +236,40 @@ impl MissileAuthorityBundle
 }
 
 
+/// Type alias for the things missiles can lock on to when they are fired
+type LockOnTargets<'w, 's> = Query<'w, 's, (Entity, &'static Position, &'static CollisionGroups), (Without<Missile>, Without<Dead>)>;
+
+/// This system (Authority only) adds the MissileAuthorityBundle to new missiles, locks them on to their first target,
+/// and records which client owns them so they can guide them.
+/// Missiles are not a [`ReplicatedObject`](super::ReplicatedObject): locking on needs the other actors, and the owning client
+/// needs the owner's [`Player`], which an object can not look up from itself
 pub fn s_missile_authority(
     mut commands: Commands,
-    new_missiles: Query<(Entity, &Position, &Orientation, &Missile), Added<Replication>>,
+    mut new_missiles: Query<(Entity, &Position, &Orientation, &CollisionGroups, &mut Missile), Added<Replication>>,
+    players: Query<&Player>,
+    targets: LockOnTargets,
 ) {
-    for (entity, position, orientation, missile) in &new_missiles
+    for (entity, position, orientation, groups, mut missile) in &mut new_missiles
     {
         let Some(mut ent_coms) = commands.get_entity(entity) else { continue; };
 
+        let target = match missile.guidance
+        {
+            MissileGuidance::LockOn | MissileGuidance::Retarget =>
+                nearest_hittable(position.0, groups, None, targets.iter().map(|(target, target_pos, target_groups)| (target, target_pos.0, target_groups))),
+            MissileGuidance::CursorGuided | MissileGuidance::DumbFire => None,
+        };
+        missile.owning_client = players.get(missile.owner).ok().map(|player| player.0);
+
         ent_coms.insert(MissileAuthorityBundle::new(
             Transform::from_translation(position.0.extend(0.0)) * Transform::from_rotation(Quat::from_rotation_z(orientation.0)),
-            missile.owner
+            missile.owner,
+            target,
         ));
     }
 }
 
+/// This system adds the extras bundle to newly spawned/replicated missiles, rotated to their orientation
 pub fn c_missile_extras(
     mut commands: Commands,
     new_missiles: Query<(Entity, &Position, &Orientation), (With<Missile>, Added<Replication>)>,
@@ -149,9 +285,12 @@ pub fn c_missile_extras(
     }
 }
 
+/// Type alias for the things missiles can steer towards
+type MissileTargets<'w, 's> = Query<'w, 's, (Entity, &'static Position, &'static CollisionGroups, Option<&'static Velocity>, Option<&'static RapierVelocity>), (Without<Missile>, Without<Dead>)>;
+
 pub fn s_move_missiles(
-    mut missiles: Query<(&Position, &mut Velocity, &mut Orientation, &CollisionGroups, &Missile)>,
-    stuff: Query<(&Position, &CollisionGroups), Without<Missile>>, 
+    mut missiles: Query<(&Position, &mut Velocity, &mut Orientation, &CollisionGroups, &Missile, &mut MissileSteering)>,
+    targets: MissileTargets,
     time: Res<Time>, 
 ) {
     for (
@@ -159,46 +298,39 @@ pub fn s_move_missiles(
         mut missile_vel, 
         mut missile_orientation, 
         missile_group, 
-        missile
+        missile,
+        mut steering
     ) in &mut missiles
     {
-        let mut nearest_dist_sq = f32::MAX;
-        let mut nearest_pos = Vec2::ZERO;
-        for (other_pos, other_group) in &stuff
+        // Targeting v
+        let target_alive = steering.target.is_some_and(|target| targets.contains(target));
+        if !target_alive
         {
-            if (missile_group.filters.bits() & other_group.memberships.bits()) == 0 ||
-               (other_group.filters.bits()   & missile_group.memberships.bits()) == 0
+            steering.target = match missile.guidance
             {
-                continue;
-            }
-
-            let dist_sq = (missile_pos.0 - other_pos.0).length_squared();
-            if dist_sq < nearest_dist_sq
-            {
-                nearest_dist_sq = dist_sq;
-                nearest_pos = other_pos.0;
-            }
+                MissileGuidance::Retarget =>
+                    nearest_hittable(missile_pos.0, missile_group, None, targets.iter().map(|(target, target_pos, target_groups, ..)| (target, target_pos.0, target_groups))),
+                _ => None,
+            };
         }
 
-        if nearest_dist_sq != f32::MAX
+        let steer_towards = match missile.guidance
         {
-            // Rotation v
-            let missile_to_pos = (nearest_pos - missile_pos.0).try_normalize().unwrap_or_default();
-            
-            let target_dir = missile_to_pos;
-            let target_orientation = target_dir.y.atan2(target_dir.x);
-            let angle_diff = (target_orientation - missile_orientation.0 + PI) % (2.0 * PI) - PI;
-            let angular_acceleration = missile.max_angular_acceleration * angle_diff.signum();
-            let mut increase = angular_acceleration * time.delta_seconds();
-            if increase.abs() > angle_diff.abs()
-            {
-                increase = angle_diff;
-            }
-            missile_orientation.0 += increase;
+            MissileGuidance::CursorGuided => steering.guide_point.map(|point| (point, Vec2::ZERO)),
+            MissileGuidance::DumbFire => None,
+            MissileGuidance::LockOn | MissileGuidance::Retarget => steering.target
+                .and_then(|target| targets.get(target).ok())
+                .map(|(_, target_pos, _, vel, rapier_vel)| (target_pos.0, vel.map(|v| v.0).or(rapier_vel.map(|v| v.linvel)).unwrap_or_default())),
+        };
+
+        // Rotation v
+        if let Some((target_pos, target_vel)) = steer_towards
+        {
+            let max_turn = missile.max_angular_acceleration;
+            let turn_rate = proportional_navigation(missile_pos.0, missile_vel.0, missile_orientation.0, target_pos, target_vel).clamp(-max_turn, max_turn);
+            missile_orientation.0 = wrap_angle(missile_orientation.0 + turn_rate * time.delta_seconds());
         }
 
-        
-        
         // Velocity v
         let cur_speed = missile_vel.0.length();
         let missile_forward = (Quat::from_rotation_z(missile_orientation.0) * Vec3::X).truncate();
@@ -207,3 +339,105 @@ pub fn s_move_missiles(
         missile_vel.0 = missile_forward * (cur_speed + acceleration * time.delta_seconds());
     }
 }
+
+/// This system (Authority only) points the cursor guided missiles of each client at where they are aiming
+pub fn s_receive_missile_guide_points(
+    mut guide_points: EventReader<FromClient<MissileGuidePoint>>,
+    mut missiles: Query<(&Missile, &mut MissileSteering)>,
+) {
+    for FromClient { client_id, event } in guide_points.read()
+    {
+        for (_, mut steering) in missiles.iter_mut().filter(|(missile, _)| missile.is_guided_by(*client_id))
+        {
+            steering.guide_point = Some(event.point);
+        }
+    }
+}
+
+/// Sends where the local player is aiming to the server while they own any cursor guided missiles
+pub fn c_send_missile_guide_point(
+    aim: Res<LocalAim>,
+    local_player: Res<LocalPlayerId>,
+    missiles: Query<&Missile>,
+    mut guide_points: EventWriter<MissileGuidePoint>,
+) {
+    if !missiles.iter().any(|missile| missile.is_guided_by(ClientId::new(local_player.id)))
+    {
+        return;
+    }
+    let Some(point) = aim.point else { return; };
+    guide_points.send(MissileGuidePoint { point });
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_wrap_angle()
+    {
+        assert!((wrap_angle(0.5) - 0.5).abs() < 1e-5);
+        assert!((wrap_angle(TAU + 0.5) - 0.5).abs() < 1e-5);
+        assert!((wrap_angle(-TAU - 0.5) + 0.5).abs() < 1e-5);
+        // Negative angles must wrap into range too, `%` keeps the sign of the dividend so can not be used
+        assert!((wrap_angle(-1.5 * PI) - 0.5 * PI).abs() < 1e-5);
+        assert!((wrap_angle(1.5 * PI) + 0.5 * PI).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_nearest_target()
+    {
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        assert_eq!(nearest_target(Vec2::ZERO, [(a, Vec2::new(100.0, 0.0)), (b, Vec2::new(0.0, -50.0))].into_iter()), Some(b));
+        assert_eq!(nearest_target(Vec2::ZERO, std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_nearest_hittable()
+    {
+        use bevy_rapier2d::geometry::Group;
+        let groups = CollisionGroups { memberships: Group::GROUP_1, filters: Group::GROUP_2 };
+        let (enemy, ally) = (CollisionGroups { memberships: Group::GROUP_2, filters: Group::GROUP_1 }, groups);
+        let (a, b, c) = (Entity::from_raw(1), Entity::from_raw(2), Entity::from_raw(3));
+        let candidates = [(a, Vec2::new(10.0, 0.0), &ally), (b, Vec2::new(50.0, 0.0), &enemy), (c, Vec2::new(200.0, 0.0), &enemy)];
+
+        assert_eq!(nearest_hittable(Vec2::ZERO, &groups, None, candidates.into_iter()), Some(b));
+        assert_eq!(nearest_hittable(Vec2::new(150.0, 0.0), &groups, Some(60.0), candidates.into_iter()), Some(c));
+        assert_eq!(nearest_hittable(Vec2::ZERO, &groups, Some(40.0), candidates.into_iter()), None);
+    }
+
+    #[test]
+    fn test_missile_guided_only_by_owning_client()
+    {
+        let owner = ClientId::new(1);
+        let missile = Missile { owning_client: Some(owner), ..Missile::from_owner(Entity::from_raw(1)) };
+        assert!(!missile.is_guided_by(owner));
+
+        let guided = missile.with_guidance(MissileGuidance::CursorGuided);
+        assert!(guided.is_guided_by(owner));
+        assert!(!guided.is_guided_by(ClientId::new(2)));
+    }
+
+    #[test]
+    fn test_navigation_turns_towards_target_behind()
+    {
+        // A stationary target directly behind has no line of sight rotation, the pursuit term must still turn the missile
+        let turn = proportional_navigation(Vec2::ZERO, Vec2::X * 100.0, 0.0, Vec2::new(-100.0, 1.0), Vec2::ZERO);
+        assert!(turn > 0.0);
+        let turn = proportional_navigation(Vec2::ZERO, Vec2::X * 100.0, 0.0, Vec2::new(-100.0, -1.0), Vec2::ZERO);
+        assert!(turn < 0.0);
+    }
+
+    #[test]
+    fn test_navigation_leads_crossing_target()
+    {
+        // Target dead ahead moving up, the missile should turn up (left) to lead it
+        let turn = proportional_navigation(Vec2::ZERO, Vec2::X * 100.0, 0.0, Vec2::new(200.0, 0.0), Vec2::Y * 50.0);
+        assert!(turn > 0.0);
+
+        // Already on a collision course (constant bearing), no correction is needed
+        let turn = proportional_navigation(Vec2::ZERO, Vec2::new(100.0, 50.0), 50.0_f32.atan2(100.0), Vec2::new(200.0, 0.0), Vec2::new(0.0, 50.0));
+        assert!(turn.abs() < 1e-4);
+    }
+}
@@ -1,19 +1,29 @@
-mod bullet;
 mod explosion;
 mod missile;
 mod laser;
+mod beam;
 mod melee;
+mod boomerang;
+mod zone;
+mod summon;
+mod projectile;
+mod replicated;
 
-pub use bullet::{Bullet, BulletReplicationBundle};
 pub use explosion::{Explosion, ExplosionReplicationBundle};
-pub use missile::{Missile, MissileReplicationBundle};
+pub use missile::{Missile, MissileGuidance, MissileReplicationBundle};
 pub use laser::{Laser, LaserReplicationBundle};
+pub use beam::{Beam, BeamEnd, BeamAim, BeamReplicationBundle, LocalBeamChannel};
 pub use melee::{MeleeAttack, MeleeAttackData, MeleeAttackType, MeleeReplicationBundle};
+pub use boomerang::{Boomerang, BoomerangReplicationBundle};
+pub use zone::{Zone, ZoneReplicationBundle};
+pub use summon::{Summon, SummonReplicationBundle};
+pub use projectile::{Projectile, ProjectileReplicationBundle, ProjectileBehaviours, split_velocities};
+pub use replicated::{ReplicatedObject, ReplicatedObjectAppExt};
 
 use bevy::prelude::*;
 use bevy_replicon::prelude::*;
 
-use crate::simple::state;
+use crate::simple::{gameplay::actor, player, state};
 
 pub struct GameplayObjectPlugin;
 
@@ -21,27 +31,50 @@ impl Plugin for GameplayObjectPlugin
 {
     fn build(&self, app: &mut App) {
         app
-            .replicate::<Bullet>()
             .replicate::<Missile>()
-            .replicate::<Explosion>()
-            .replicate::<MeleeAttack>()
+            .replicate::<Zone>()
+            .add_replicated_object::<MeleeAttack>()
+            .add_replicated_object::<Laser>()
+            .add_replicated_object::<Boomerang>()
+            .add_replicated_object::<Explosion>()
+            .add_replicated_object::<Summon>()
+            .add_replicated_object::<Projectile>()
+            .add_replicated_object::<Beam>()
+            .replicate::<BeamEnd>()
+            .init_resource::<LocalBeamChannel>()
+            .add_client_event::<missile::MissileGuidePoint>(ChannelKind::Unreliable)
+            .add_client_event::<BeamAim>(ChannelKind::Unreliable)
             .add_systems(FixedUpdate, (
-                bullet::s_bullet_authority,
                 missile::s_missile_authority,
-                melee::s_melee_authority,
-                explosion::s_explosion_authority,
-                laser::s_laser_authority,
+                zone::s_zone_authority,
+                projectile::s_apply_projectile_modifiers.before(replicated::s_object_authority::<Projectile>),
+                missile::s_receive_missile_guide_points,
+                beam::s_receive_beam_aims,
             ).in_set(state::AuthoritySystems))
             .add_systems(FixedUpdate, (
                 missile::s_move_missiles,
+                projectile::s_ricochet_projectiles,
+                projectile::s_bounce_projectiles,
+                boomerang::s_move_boomerangs,
+                zone::s_tick_zones,
+                summon::s_move_drones,
+                summon::s_summon_attacks,
+                projectile::s_home_projectiles,
+                projectile::s_apply_projectile_gravity,
+                projectile::s_orbit_projectiles,
+                projectile::s_split_projectiles.before(actor::s_destroy_dead_things),
+                beam::s_update_beams,
             ).in_set(state::AuthoritySystems).in_set(state::FightingSystems))
             .add_systems(FixedUpdate, (
-                bullet::c_bullet_extras,
-                melee::c_melee_extras,
-                laser::c_laser_extras,
-                explosion::c_explosion_extras,
                 missile::c_missile_extras,
+                zone::c_zone_extras,
+                beam::c_update_beam_sprites,
+                laser::c_update_laser_sprites,
             ).in_set(state::HostAndClientSystems))
+            .add_systems(FixedUpdate, (
+                missile::c_send_missile_guide_point,
+                beam::c_send_beam_aim,
+            ).after(player::c_update_local_aim).in_set(state::HostAndClientSystems).in_set(state::FightingSystems))
             ;
     }
 }
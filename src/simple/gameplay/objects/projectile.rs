@@ -0,0 +1,493 @@
+use std::f32::consts::TAU;
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_rapier2d::geometry::{ActiveCollisionTypes, Collider, CollisionGroups, Sensor};
+use bevy_replicon::prelude::*;
+
+use serde::{Serialize, Deserialize};
+
+use crate::simple::{
+    gameplay::{Position, Velocity, Lifetime, Dead, DestroyIfNoMatchWithin, Damageable},
+    data::{Bounds, WorldBounds},
+    consts::{PLAYER_PROJECTILE_GROUPS, PROJECTILE_RICOCHET_RANGE},
+    util::ReflectVecExt,
+};
+
+use super::{
+    ReplicatedObject,
+    missile::{nearest_hittable, nearest_target, wrap_angle},
+    super::actor::{
+        ActorChild, ActorContext, ChildType, Damage, DamageKnockback, Fragment,
+        effect::ProjectileModifiers, effect_application::get_projectile_modifiers,
+    },
+};
+
+
+/// A generic projectile (bullets, fragments, summon shots), whose movement is customised by adding behaviour components alongside it:
+/// [`Homing`], [`Gravity`], [`SplitOnDeath`], [`Orbit`], and [`VelocityDamping`](crate::simple::gameplay::VelocityDamping) for damping.
+/// Behaviours run on the server, their effects reach clients through the replicated `Position` and `Velocity`
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+pub struct Projectile
+{
+    pub owner: Entity, // DO NOT USE ON CLIENTS, THIS ENTITY DOES NOT GET MAPPED
+    pub ability_type: ChildType,
+    pub size: f32, // The radius of the collider, and the width of the sprite
+    pub color: Color,
+    pub lifetime: f32,
+    pub damage: f32,
+    pub knockback: f32,
+    /// Combined with the owner's projectile modifiers when the projectile is spawned
+    #[serde(default)]
+    pub modifiers: ProjectileModifiers,
+}
+
+/// Server-only component tracking how many ricochets and bounces a projectile has left
+#[derive(Component)]
+pub struct ProjectileRedirects
+{
+    pub ricochets_remaining: u32,
+    pub bounces_remaining: u32,
+    pub hits_handled: u32,
+}
+
+/// Steers the projectile towards the nearest thing it can hit within range
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Homing
+{
+    pub turn_rate: f32, // Radians/s
+    pub range: f32,
+}
+
+/// Constantly accelerates the projectile (u/s^2)
+#[derive(Component, Clone, Copy, Debug, Deref, DerefMut, PartialEq)]
+pub struct Gravity(pub Vec2);
+
+/// Splits the projectile into smaller projectiles, fanned out around its direction, when it dies
+#[derive(Component, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SplitOnDeath
+{
+    pub count: u32,
+    pub spread: f32, // Radians between the outermost fragments
+    pub size_factor: f32,
+    pub damage_factor: f32,
+}
+
+/// Moves the projectile in a circle around an entity instead of in a straight line
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Orbit
+{
+    pub center: Entity,
+    pub radius: f32,
+    pub angular_speed: f32, // Radians/s
+    pub angle: f32,
+}
+
+/// An orbit around the owner of a data defined projectile
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrbitOwner
+{
+    pub radius: f32,
+    pub angular_speed: f32, // Radians/s
+}
+
+/// The behaviours a data defined projectile is spawned with, see [`AbilityObject::Bullet`](crate::simple::gameplay::classes::AbilityObject)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectileBehaviours
+{
+    #[serde(default)]
+    pub homing: Option<Homing>,
+    #[serde(default)]
+    pub gravity: Option<Vec2>,
+    #[serde(default)]
+    pub split: Option<SplitOnDeath>,
+    /// Orbits the owner, starting in the direction the projectile was fired
+    #[serde(default)]
+    pub orbit: Option<OrbitOwner>,
+}
+
+impl ProjectileBehaviours
+{
+    /// Adds the behaviour components to a newly spawned projectile
+    pub fn insert(&self, ent_coms: &mut EntityCommands, owner: Entity, direction: Vec2)
+    {
+        if let Some(homing) = self.homing
+        {
+            ent_coms.insert(homing);
+        }
+        if let Some(gravity) = self.gravity
+        {
+            ent_coms.insert(Gravity(gravity));
+        }
+        if let Some(split) = self.split
+        {
+            ent_coms.insert(split);
+        }
+        if let Some(OrbitOwner { radius, angular_speed }) = self.orbit
+        {
+            ent_coms.insert(Orbit { center: owner, radius, angular_speed, angle: direction.y.atan2(direction.x) });
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct ProjectileReplicationBundle
+{
+    pub projectile: Projectile,
+    pub position: Position,
+    pub velocity: Velocity,
+    pub groups: CollisionGroups,
+    pub replication: Replication,
+}
+
+#[derive(Bundle)]
+pub struct ProjectileAuthorityBundle
+{
+    pub transform: TransformBundle,
+    pub damage: Damage,
+    pub child: ActorChild,
+    pub lifetime: Lifetime,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub collision_types: ActiveCollisionTypes,
+    pub redirects: ProjectileRedirects,
+    pub name: Name,
+}
+
+#[derive(Bundle)]
+pub struct ProjectileExtrasBundle
+{
+    pub sprite_bundle: SpriteBundle,
+    pub validation: DestroyIfNoMatchWithin,
+}
+
+impl ProjectileReplicationBundle
+{
+    pub fn new(projectile: Projectile, position: Vec2, velocity: Vec2) -> Self
+    {
+        Self
+        {
+            projectile,
+            position: Position(position),
+            velocity: Velocity(velocity),
+            groups: PLAYER_PROJECTILE_GROUPS,
+            replication: Replication,
+        }
+    }
+
+    pub fn with_groups(mut self, groups: CollisionGroups) -> Self
+    {
+        self.groups = groups;
+        self
+    }
+}
+
+impl ProjectileAuthorityBundle
+{
+    pub fn new(projectile: &Projectile, position: Vec2) -> Self
+    {
+        let mut damage = Damage::new(projectile.damage, true, false, Some(DamageKnockback::RepulsionFromSelf { strength: projectile.knockback }));
+        damage.with_max_hits(Some(projectile.modifiers.max_hits()));
+        Self
+        {
+            transform: TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            damage,
+            child: ActorChild { parent_actor: projectile.owner, ability_type: projectile.ability_type },
+            lifetime: Lifetime(projectile.lifetime),
+            collider: Collider::ball(projectile.size),
+            sensor: Sensor,
+            collision_types: ActiveCollisionTypes::STATIC_STATIC,
+            redirects: ProjectileRedirects { ricochets_remaining: projectile.modifiers.ricochet, bounces_remaining: projectile.modifiers.bounce, hits_handled: 0 },
+            name: format!("{} Projectile of '{:?}'", projectile.ability_type.name(), projectile.owner).into(),
+        }
+    }
+}
+
+impl ProjectileExtrasBundle
+{
+    pub fn new(projectile: &Projectile, position: Vec2) -> Self
+    {
+        Self
+        {
+            sprite_bundle: SpriteBundle
+            {
+                sprite: Sprite { color: projectile.color, custom_size: Some(Vec2::splat(projectile.size)), ..default() },
+                transform: Transform::from_translation(position.extend(0.0)),
+                ..default()
+            },
+            validation: DestroyIfNoMatchWithin::default(),
+        }
+    }
+}
+
+impl ReplicatedObject for Projectile
+{
+    type AuthorityBundle = ProjectileAuthorityBundle;
+    type ExtrasBundle = ProjectileExtrasBundle;
+
+    fn authority_bundle(&self, position: Vec2) -> Self::AuthorityBundle {
+        ProjectileAuthorityBundle::new(self, position)
+    }
+
+    fn extras_bundle(&self, position: Vec2) -> Self::ExtrasBundle {
+        ProjectileExtrasBundle::new(self, position)
+    }
+}
+
+/// Rotates a velocity towards a direction, by at most max_turn radians
+pub fn turn_towards(velocity: Vec2, direction: Vec2, max_turn: f32) -> Vec2
+{
+    if velocity == Vec2::ZERO || direction == Vec2::ZERO
+    {
+        return velocity;
+    }
+    let angle = velocity.angle_between(direction);
+    Vec2::from_angle(angle.clamp(-max_turn, max_turn)).rotate(velocity)
+}
+
+/// The velocities of the fragments a projectile splits into, evenly fanned out around its velocity
+pub fn split_velocities(velocity: Vec2, count: u32, spread: f32) -> Vec<Vec2>
+{
+    match count
+    {
+        0 => Vec::new(),
+        1 => vec![velocity],
+        // A full circle would put the first and last fragments on top of each other
+        _ if spread >= TAU => (0..count).map(|i| Vec2::from_angle(TAU * i as f32 / count as f32).rotate(velocity)).collect(),
+        _ => (0..count).map(|i| Vec2::from_angle(spread * (i as f32 / (count - 1) as f32 - 0.5)).rotate(velocity)).collect(),
+    }
+}
+
+/// The position on an orbit, and the velocity of something moving along it
+pub fn orbit_point(center: Vec2, radius: f32, angle: f32, angular_speed: f32) -> (Vec2, Vec2)
+{
+    let offset = Vec2::from_angle(angle) * radius;
+    (center + offset, offset.perp() * angular_speed)
+}
+
+/// This system (Authority only) combines the projectile modifiers of new projectiles' owners into the projectiles,
+/// before their authority bundle is created from them
+pub fn s_apply_projectile_modifiers(
+    mut new_projectiles: Query<&mut Projectile, Added<Replication>>,
+    owners: Query<&ActorContext>,
+) {
+    for mut projectile in &mut new_projectiles
+    {
+        let Ok(owner_context) = owners.get(projectile.owner) else { continue; };
+        projectile.modifiers = projectile.modifiers.combined(&get_projectile_modifiers(projectile.ability_type, owner_context));
+    }
+}
+
+/// This system (Authority only) redirects projectiles with ricochets remaining towards the nearest enemy they have not yet hit
+pub fn s_ricochet_projectiles(
+    mut projectiles: Query<(&mut ProjectileRedirects, &Damage, &Position, &mut Velocity, &CollisionGroups), With<Projectile>>,
+    targets: Query<(Entity, &Position, &CollisionGroups), With<Damageable>>,
+) {
+    for (mut redirects, damage, position, mut velocity, groups) in &mut projectiles
+    {
+        if damage.hit_count <= redirects.hits_handled
+        {
+            continue;
+        }
+        redirects.hits_handled = damage.hit_count;
+        if redirects.ricochets_remaining == 0 || !damage.can_hit_anything()
+        {
+            continue;
+        }
+
+        let candidates = targets.iter()
+            .filter(|(target_entity, target_position, target_groups)|
+                groups.filters.intersects(target_groups.memberships) &&
+                damage.can_hit(*target_entity) &&
+                target_position.0.distance_squared(position.0) <= PROJECTILE_RICOCHET_RANGE * PROJECTILE_RICOCHET_RANGE
+            )
+            .map(|(target_entity, target_position, _)| (target_entity, target_position.0));
+        let Some(target) = nearest_target(position.0, candidates) else { continue; };
+        let Ok((_, target_position, _)) = targets.get(target) else { continue; };
+
+        velocity.0 = (target_position.0 - position.0).normalize_or_zero() * velocity.0.length();
+        redirects.ricochets_remaining -= 1;
+    }
+}
+
+/// This system (Authority only) bounces projectiles with bounces remaining off the edges of the world
+pub fn s_bounce_projectiles(
+    mut projectiles: Query<(&mut ProjectileRedirects, &mut Position, &mut Velocity), With<Projectile>>,
+    bounds: Res<WorldBounds>,
+    bound_assets: Res<Assets<Bounds>>,
+) {
+    let Some(bounds) = bound_assets.get(&bounds.bounds) else { error!("World Bounds were not loaded!"); return; };
+    for (mut redirects, mut position, mut velocity) in &mut projectiles
+    {
+        if redirects.bounces_remaining == 0
+        {
+            continue;
+        }
+
+        let mut bounced = false;
+        for (outside, normal) in [
+            (position.0.x < bounds.min.x && velocity.0.x < 0.0, Vec2::X),
+            (position.0.x > bounds.max.x && velocity.0.x > 0.0, Vec2::NEG_X),
+            (position.0.y < bounds.min.y && velocity.0.y < 0.0, Vec2::Y),
+            (position.0.y > bounds.max.y && velocity.0.y > 0.0, Vec2::NEG_Y),
+        ]
+        {
+            if outside
+            {
+                velocity.0 = velocity.0.reflect_against(normal);
+                bounced = true;
+            }
+        }
+
+        if bounced
+        {
+            position.0 = position.0.clamp(bounds.min, bounds.max);
+            redirects.bounces_remaining -= 1;
+        }
+    }
+}
+
+/// Type alias for the things homing projectiles can steer towards
+type HomingTargets<'w, 's> = Query<'w, 's, (Entity, &'static Position, &'static CollisionGroups), (Without<Projectile>, Without<Dead>)>;
+
+/// This system (Authority only) steers homing projectiles towards the nearest thing they can hit, picked the same way missiles pick their targets.
+/// Unlike missiles, homing projectiles have no orientation or acceleration, they only turn their velocity
+pub fn s_home_projectiles(
+    mut projectiles: Query<(&Homing, &Position, &CollisionGroups, &mut Velocity), With<Projectile>>,
+    targets: HomingTargets,
+    time: Res<Time>,
+) {
+    for (homing, position, groups, mut velocity) in &mut projectiles
+    {
+        let candidates = targets.iter().map(|(entity, target_pos, target_groups)| (entity, target_pos.0, target_groups));
+        let Some(target) = nearest_hittable(position.0, groups, Some(homing.range), candidates) else { continue; };
+        let Ok((_, target_pos, _)) = targets.get(target) else { continue; };
+
+        velocity.0 = turn_towards(velocity.0, target_pos.0 - position.0, homing.turn_rate * time.delta_seconds());
+    }
+}
+
+/// This system (Authority only) accelerates projectiles affected by gravity
+pub fn s_apply_projectile_gravity(
+    mut projectiles: Query<(&Gravity, &mut Velocity), With<Projectile>>,
+    time: Res<Time>,
+) {
+    for (gravity, mut velocity) in &mut projectiles
+    {
+        velocity.0 += gravity.0 * time.delta_seconds();
+    }
+}
+
+/// This system (Authority only) moves orbiting projectiles around their center, destroying them if the center is gone
+pub fn s_orbit_projectiles(
+    mut commands: Commands,
+    mut projectiles: Query<(Entity, &mut Orbit, &mut Position, &mut Velocity), With<Projectile>>,
+    centers: Query<&Position, Without<Projectile>>,
+    time: Res<Time>,
+) {
+    for (entity, mut orbit, mut position, mut velocity) in &mut projectiles
+    {
+        let Ok(center) = centers.get(orbit.center) else
+        {
+            commands.entity(entity).insert(Dead);
+            continue;
+        };
+        orbit.angle = wrap_angle(orbit.angle + orbit.angular_speed * time.delta_seconds());
+        // The velocity is not used to move the projectile, but lets clients predict its movement
+        (position.0, velocity.0) = orbit_point(center.0, orbit.radius, orbit.angle, orbit.angular_speed);
+    }
+}
+
+/// Type alias for the dead projectiles that split
+type SplittingProjectiles<'w, 's> = Query<'w, 's, (Entity, &'static Projectile, &'static SplitOnDeath, &'static Position, &'static Velocity, &'static CollisionGroups), With<Dead>>;
+
+/// This system (Authority only) splits dead projectiles into fragments, it must run before the dead projectiles are destroyed
+pub fn s_split_projectiles(
+    mut commands: Commands,
+    projectiles: SplittingProjectiles,
+) {
+    for (entity, projectile, split, position, velocity, groups) in &projectiles
+    {
+        // Fragments do not split again
+        commands.entity(entity).remove::<SplitOnDeath>();
+        let fragment = Projectile
+        {
+            size: projectile.size * split.size_factor,
+            damage: projectile.damage * split.damage_factor,
+            // The owner's modifiers are combined in again when the fragments spawn
+            modifiers: ProjectileModifiers::default(),
+            ..*projectile
+        };
+        for fragment_velocity in split_velocities(velocity.0, split.count, split.spread)
+        {
+            // Fragments do not trigger ability end effects either
+            commands.spawn((ProjectileReplicationBundle::new(fragment, position.0, fragment_velocity).with_groups(*groups), Fragment));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    fn angle(v: Vec2) -> f32
+    {
+        v.y.atan2(v.x)
+    }
+
+    #[test]
+    fn test_turn_towards_is_limited_by_max_turn()
+    {
+        let turned = turn_towards(Vec2::new(10.0, 0.0), Vec2::new(0.0, 1.0), 0.1);
+        assert!((angle(turned) - 0.1).abs() < 0.0001);
+        assert!((turned.length() - 10.0).abs() < 0.0001);
+
+        // Turns the short way round
+        let turned = turn_towards(Vec2::new(10.0, 0.0), Vec2::new(0.0, -1.0), 0.1);
+        assert!((angle(turned) + 0.1).abs() < 0.0001);
+
+        let turned = turn_towards(Vec2::new(10.0, 0.0), Vec2::new(1.0, 0.05), 1.0);
+        assert!((angle(turned) - angle(Vec2::new(1.0, 0.05))).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_split_velocities_fan_out()
+    {
+        let velocity = Vec2::new(10.0, 0.0);
+        assert!(split_velocities(velocity, 0, 1.0).is_empty());
+        assert_eq!(split_velocities(velocity, 1, 1.0), vec![velocity]);
+
+        let fragments = split_velocities(velocity, 3, PI * 0.5);
+        assert_eq!(fragments.len(), 3);
+        assert!((angle(fragments[0]) + PI * 0.25).abs() < 0.0001);
+        assert!(fragments[1].abs_diff_eq(velocity, 0.0001));
+        assert!((angle(fragments[2]) - PI * 0.25).abs() < 0.0001);
+
+        let ring = split_velocities(velocity, 4, TAU);
+        assert!(ring[2].abs_diff_eq(-velocity, 0.0001));
+    }
+
+    #[test]
+    fn test_behaviours_are_optional_in_data()
+    {
+        let behaviours: ProjectileBehaviours = ron::de::from_str("(split: Some((count: 3, spread: 1.0, size_factor: 0.5, damage_factor: 0.5)), orbit: Some((radius: 40.0, angular_speed: 3.0)))")
+            .expect("could not deserialize projectile behaviours");
+        assert_eq!(behaviours.homing, None);
+        assert_eq!(behaviours.gravity, None);
+        assert_eq!(behaviours.split, Some(SplitOnDeath { count: 3, spread: 1.0, size_factor: 0.5, damage_factor: 0.5 }));
+        assert_eq!(behaviours.orbit, Some(OrbitOwner { radius: 40.0, angular_speed: 3.0 }));
+
+        let none: ProjectileBehaviours = ron::de::from_str("()").expect("could not deserialize empty projectile behaviours");
+        assert_eq!(none, ProjectileBehaviours::default());
+    }
+
+    #[test]
+    fn test_orbit_point()
+    {
+        let (position, velocity) = orbit_point(Vec2::new(5.0, 5.0), 10.0, PI * 0.5, 2.0);
+        assert!(position.abs_diff_eq(Vec2::new(5.0, 15.0), 0.0001));
+        assert!(velocity.abs_diff_eq(Vec2::new(-20.0, 0.0), 0.0001));
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::simple::{gameplay::Position, state};
+
+
+/// A gameplay object spawned on the server and replicated to clients.
+///
+/// Objects are spawned with a replication bundle, containing the bare minimum needed to recreate them: the object component,
+/// a [`Position`], [`Replication`], and any other replicated components.
+/// Everything else is created from the replicated components:
+/// - The authority bundle (colliders, lifetimes, damage, etc.) is added on the server by [`s_object_authority`]
+/// - The extras bundle (sprites, etc.) is added on clients and hosts by [`c_object_extras`]
+///
+/// Register objects with [`ReplicatedObjectAppExt::add_replicated_object`]
+pub trait ReplicatedObject: Component + Serialize + DeserializeOwned
+{
+    type AuthorityBundle: Bundle;
+    type ExtrasBundle: Bundle;
+
+    fn authority_bundle(&self, position: Vec2) -> Self::AuthorityBundle;
+    fn extras_bundle(&self, position: Vec2) -> Self::ExtrasBundle;
+}
+
+/// This system (Authority only) adds the authority bundle to newly created objects
+pub fn s_object_authority<T: ReplicatedObject>(
+    mut commands: Commands,
+    new_objects: Query<(Entity, &T, &Position), Added<Replication>>,
+) {
+    for (entity, object, position) in &new_objects
+    {
+        let Some(mut ent_coms) = commands.get_entity(entity) else { continue; };
+
+        ent_coms.insert(object.authority_bundle(position.0));
+    }
+}
+
+/// This system adds the extras bundle to newly created/replicated objects, on clients and hosts
+pub fn c_object_extras<T: ReplicatedObject>(
+    mut commands: Commands,
+    new_objects: Query<(Entity, &T, &Position), Added<Replication>>,
+) {
+    for (entity, object, position) in &new_objects
+    {
+        let Some(mut ent_coms) = commands.get_entity(entity) else { continue; };
+
+        ent_coms.insert(object.extras_bundle(position.0));
+    }
+}
+
+pub trait ReplicatedObjectAppExt
+{
+    /// Replicates the object component, and adds the systems that create its authority and extras bundles
+    fn add_replicated_object<T: ReplicatedObject>(&mut self) -> &mut Self;
+}
+
+impl ReplicatedObjectAppExt for App
+{
+    fn add_replicated_object<T: ReplicatedObject>(&mut self) -> &mut Self {
+        self
+            .replicate::<T>()
+            .add_systems(FixedUpdate, s_object_authority::<T>.in_set(state::AuthoritySystems))
+            .add_systems(FixedUpdate, c_object_extras::<T>.in_set(state::HostAndClientSystems))
+    }
+}
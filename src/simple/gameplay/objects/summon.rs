@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use bevy_rapier2d::geometry::{ActiveCollisionTypes, Collider, CollisionGroups, Sensor};
+use bevy_replicon::prelude::*;
+
+use serde::{Serialize, Deserialize};
+
+use crate::simple::{
+    gameplay::{Position, Lifetime, Dead, Knockback, Damageable, Enemy},
+    ui::HealthBar,
+    consts::{
+        PLAYER_GROUP, PLAYER_SENSOR_FILTER, RANGED_BULLET_SIZE,
+        SUMMON_BULLET_SPEED, SUMMON_COLOR, SUMMON_DRONE_FOLLOW_DISTANCE, SUMMON_DRONE_SPEED, SUMMON_FIRE_INTERVAL, SUMMON_RANGE, SUMMON_SIZE,
+    },
+};
+
+use super::{
+    Projectile, ProjectileReplicationBundle, ReplicatedObject,
+    super::actor::{
+        ActorChild, ActorContext, ActorSensors, ChildType, Stat,
+        effect::SummonKind,
+    },
+};
+
+
+/// An actor (turret, drone, decoy) that fights on behalf of the actor that summoned it.
+/// On the server summons are `ActorChild`ren of their summoner, so their kills and hits count as the summoner's
+#[derive(Component, Serialize, Deserialize)]
+pub struct Summon
+{
+    pub owner: Entity, // DO NOT USE ON CLIENTS, THIS ENTITY DOES NOT GET MAPPED
+    pub kind: SummonKind,
+    pub duration: f32,
+    pub damage: f32,
+}
+
+/// Server side timer until a summon can next attack
+#[derive(Component)]
+pub struct SummonAttack
+{
+    pub cooldown: f32,
+}
+
+#[derive(Bundle)]
+pub struct SummonReplicationBundle
+{
+    pub summon: Summon,
+    pub position: Position,
+    pub actor: ActorContext,
+    pub replication: Replication,
+}
+
+#[derive(Bundle)]
+pub struct SummonAuthorityBundle
+{
+    pub transform: TransformBundle,
+    pub lifetime: Lifetime,
+    pub child: ActorChild,
+    pub sensors: ActorSensors,
+    pub damageable: Damageable,
+    pub knockback: Knockback,
+    pub collider: Collider,
+    pub sensor: Sensor,
+    pub group: CollisionGroups,
+    pub collision_types: ActiveCollisionTypes,
+    pub attack: SummonAttack,
+    pub name: Name,
+}
+
+#[derive(Bundle)]
+pub struct SummonExtrasBundle
+{
+    pub sprite_bundle: SpriteBundle,
+    pub healthbar: HealthBar,
+}
+
+impl SummonReplicationBundle
+{
+    pub fn new(owner: Entity, kind: SummonKind, health: f32, duration: f32, damage: f32, position: Vec2) -> Self
+    {
+        let mut actor = ActorContext::default();
+        actor.set_stat(Stat::MaxHealth, health);
+        actor.set_stat(Stat::Health, health);
+        Self
+        {
+            summon: Summon { owner, kind, duration, damage },
+            position: Position(position),
+            actor,
+            replication: Replication,
+        }
+    }
+}
+
+impl SummonAuthorityBundle
+{
+    pub fn new(owner: Entity, kind: SummonKind, duration: f32, position: Vec2) -> Self
+    {
+        Self
+        {
+            transform: TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            lifetime: Lifetime(duration),
+            child: ActorChild { parent_actor: owner, ability_type: ChildType::ChildActor },
+            sensors: ActorSensors { sensors: Vec::new() },
+            damageable: Damageable { invulnerability_remaining: 0.0 },
+            knockback: Knockback::default(),
+            collider: Collider::ball(SUMMON_SIZE / 2.0),
+            sensor: Sensor,
+            // Summons are on the players' side, so are hit by enemies but not by players.
+            // With PvP they also join their owner's faction, see s_apply_pvp_collision_groups
+            group: CollisionGroups { memberships: PLAYER_GROUP, filters: PLAYER_SENSOR_FILTER },
+            collision_types: ActiveCollisionTypes::default() | ActiveCollisionTypes::STATIC_STATIC,
+            attack: SummonAttack { cooldown: SUMMON_FIRE_INTERVAL },
+            name: format!("{kind:?} of '{owner:?}'").into(),
+        }
+    }
+}
+
+impl SummonExtrasBundle
+{
+    pub fn new(kind: SummonKind, position: Vec2) -> Self
+    {
+        let color = match kind
+        {
+            SummonKind::Turret | SummonKind::Drone => SUMMON_COLOR,
+            // Decoys look like a faded player
+            SummonKind::Decoy => SUMMON_COLOR.with_a(0.5),
+        };
+        Self
+        {
+            sprite_bundle: SpriteBundle
+            {
+                sprite: Sprite { color, custom_size: Some(Vec2::splat(SUMMON_SIZE)), ..default() },
+                transform: Transform::from_translation(position.extend(0.0)),
+                ..default()
+            },
+            healthbar: HealthBar::default(),
+        }
+    }
+}
+
+impl SummonKind
+{
+    /// Whether this kind of summon shoots at enemies
+    pub fn attacks(&self) -> bool
+    {
+        matches!(self, Self::Turret | Self::Drone)
+    }
+}
+
+/// The position of the nearest target within range, if there is one
+pub fn nearest_in_range(position: Vec2, range: f32, targets: impl Iterator<Item = Vec2>) -> Option<Vec2>
+{
+    targets
+        .map(|target| (target, target.distance_squared(position)))
+        .filter(|(_, distance_sq)| *distance_sq <= range * range)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(target, _)| target)
+}
+
+/// Where a drone should move to this frame, following its summoner while keeping some distance from them
+pub fn drone_step(drone_pos: Vec2, owner_pos: Vec2, delta: f32) -> Vec2
+{
+    let offset = owner_pos - drone_pos;
+    let distance = offset.length();
+    if distance <= SUMMON_DRONE_FOLLOW_DISTANCE
+    {
+        return drone_pos;
+    }
+    let step = (SUMMON_DRONE_SPEED * delta).min(distance - SUMMON_DRONE_FOLLOW_DISTANCE);
+    drone_pos + offset / distance * step
+}
+
+impl ReplicatedObject for Summon
+{
+    type AuthorityBundle = SummonAuthorityBundle;
+    type ExtrasBundle = SummonExtrasBundle;
+
+    fn authority_bundle(&self, position: Vec2) -> Self::AuthorityBundle {
+        SummonAuthorityBundle::new(self.owner, self.kind, self.duration, position)
+    }
+
+    fn extras_bundle(&self, position: Vec2) -> Self::ExtrasBundle {
+        SummonExtrasBundle::new(self.kind, position)
+    }
+}
+
+/// This system (Authority only) moves drones towards their summoner
+pub fn s_move_drones(
+    mut summons: Query<(&Summon, &mut Position)>,
+    owners: Query<&Position, (Without<Summon>, Without<Dead>)>,
+    time: Res<Time>,
+) {
+    for (summon, mut position) in &mut summons
+    {
+        if summon.kind != SummonKind::Drone
+        {
+            continue;
+        }
+        let Ok(owner_pos) = owners.get(summon.owner) else { continue; };
+        position.0 = drone_step(position.0, owner_pos.0, time.delta_seconds());
+    }
+}
+
+/// This system (Authority only) has turrets and drones shoot at the nearest enemy in range
+pub fn s_summon_attacks(
+    mut commands: Commands,
+    mut summons: Query<(Entity, &Summon, &Position, &mut SummonAttack), Without<Dead>>,
+    enemies: Query<&Position, (With<Enemy>, Without<Dead>)>,
+    time: Res<Time>,
+) {
+    for (entity, summon, position, mut attack) in &mut summons
+    {
+        if !summon.kind.attacks()
+        {
+            continue;
+        }
+        attack.cooldown = (attack.cooldown - time.delta_seconds()).max(0.0);
+        if attack.cooldown > 0.0
+        {
+            continue;
+        }
+        let Some(target) = nearest_in_range(position.0, SUMMON_RANGE, enemies.iter().map(|pos| pos.0)) else { continue; };
+
+        attack.cooldown = SUMMON_FIRE_INTERVAL;
+        let direction = (target - position.0).normalize_or_zero();
+        // The bullet belongs to the summon, which passes its damage and hits on to the summoner
+        let bullet = Projectile
+        {
+            owner: entity,
+            ability_type: ChildType::Projectile,
+            size: RANGED_BULLET_SIZE,
+            color: SUMMON_COLOR,
+            lifetime: SUMMON_RANGE / SUMMON_BULLET_SPEED,
+            damage: summon.damage,
+            knockback: 100.0,
+            modifiers: default(),
+        };
+        commands.spawn(ProjectileReplicationBundle::new(bullet, position.0, direction * SUMMON_BULLET_SPEED));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_nearest_in_range()
+    {
+        let targets = [Vec2::new(100.0, 0.0), Vec2::new(-50.0, 0.0), Vec2::new(0.0, 500.0)];
+        assert_eq!(nearest_in_range(Vec2::ZERO, 200.0, targets.into_iter()), Some(Vec2::new(-50.0, 0.0)));
+        assert_eq!(nearest_in_range(Vec2::new(0.0, 450.0), 100.0, targets.into_iter()), Some(Vec2::new(0.0, 500.0)));
+        assert_eq!(nearest_in_range(Vec2::ZERO, 10.0, targets.into_iter()), None);
+    }
+
+    #[test]
+    fn test_drone_follows_owner_at_a_distance()
+    {
+        let owner = Vec2::new(1000.0, 0.0);
+        let moved = drone_step(Vec2::ZERO, owner, 0.5);
+        assert!((moved.x - SUMMON_DRONE_SPEED * 0.5).abs() < 0.001);
+        assert_eq!(moved.y, 0.0);
+
+        // Never gets closer than the follow distance
+        let close = Vec2::new(1000.0 - SUMMON_DRONE_FOLLOW_DISTANCE - 1.0, 0.0);
+        let moved = drone_step(close, owner, 10.0);
+        assert!((owner.distance(moved) - SUMMON_DRONE_FOLLOW_DISTANCE).abs() < 0.001);
+
+        let inside = Vec2::new(990.0, 0.0);
+        assert_eq!(drone_step(inside, owner, 1.0), inside);
+    }
+}
@@ -0,0 +1,332 @@
+use bevy::{prelude::*, sprite::{MaterialMesh2dBundle, Mesh2dHandle}, utils::HashSet};
+use bevy_rapier2d::geometry::{CollisionGroups, Group};
+use bevy_replicon::prelude::*;
+
+use serde::{Serialize, Deserialize};
+
+use crate::simple::{
+    gameplay::{Position, Lifetime, Dead},
+    consts::{ENEMY_GROUP, ENEMY_PROJECTILE_GROUP, PLAYER_FACTION_GROUPS, PLAYER_GROUP, PLAYER_PROJECTILE_FILTER, PLAYER_PROJECTILE_GROUP, ZONE_ALPHA},
+};
+
+use super::super::actor::{
+    ActorContext, DamageEvent, StatusEffect,
+    effect::{SerializedActorEffect, ZoneTargets},
+    grant_shield,
+};
+
+
+/// A lingering area (fire pools, slow fields, healing circles, etc.) that applies its effect to actors inside it every tick
+#[derive(Component, Serialize, Deserialize)]
+pub struct Zone
+{
+    pub owner: Entity, // DO NOT USE ON CLIENTS, THIS ENTITY DOES NOT GET MAPPED
+    pub radius: f32,
+    pub duration: f32,
+    pub tick_interval: f32,
+    pub effect: SerializedActorEffect,
+    pub affects: ZoneTargets,
+}
+
+/// Server side timer until a zone next applies its effect
+#[derive(Component)]
+pub struct ZoneTicker
+{
+    pub until_next_tick: f32,
+}
+
+/// Server side record of the actors a zone has given its status effect to, so it is refreshed rather than stacked every tick
+#[derive(Component, Default)]
+pub struct ZoneInflicted(HashSet<Entity>);
+
+#[derive(Bundle)]
+pub struct ZoneReplicationBundle
+{
+    pub zone: Zone,
+    pub position: Position,
+    pub groups: CollisionGroups,
+    pub replication: Replication,
+}
+
+#[derive(Bundle)]
+pub struct ZoneAuthorityBundle
+{
+    pub transform: TransformBundle,
+    pub lifetime: Lifetime,
+    pub ticker: ZoneTicker,
+    pub inflicted: ZoneInflicted,
+    pub name: Name,
+}
+
+impl ZoneTargets
+{
+    /// The groups a zone should be in (and affect), given the groups of the actor that owns it
+    pub fn groups(&self, owner: &CollisionGroups) -> CollisionGroups
+    {
+        let owner_faction = owner.memberships.intersection(PLAYER_FACTION_GROUPS);
+        let (memberships, enemies, allies) = if owner.memberships.intersects(ENEMY_GROUP)
+        {
+            (ENEMY_PROJECTILE_GROUP, PLAYER_GROUP, ENEMY_GROUP)
+        }
+        else if owner_faction.is_empty()
+        {
+            (PLAYER_PROJECTILE_GROUP, PLAYER_PROJECTILE_FILTER, PLAYER_GROUP)
+        }
+        else
+        {
+            // With PvP factions, every other player is an enemy and only the owner is an ally
+            (PLAYER_PROJECTILE_GROUP, PLAYER_PROJECTILE_FILTER.union(PLAYER_FACTION_GROUPS.difference(owner_faction)), owner_faction)
+        };
+
+        let filters = match self
+        {
+            Self::Enemies => enemies,
+            Self::Allies => allies,
+            Self::Everyone => enemies.union(allies),
+        };
+        CollisionGroups { memberships, filters }
+    }
+}
+
+impl Zone
+{
+    /// The colour the zone is drawn with, from its effect
+    pub fn color(&self) -> Color
+    {
+        match self.effect
+        {
+            SerializedActorEffect::AffectHealth(amount) if amount < 0.0 => Color::ORANGE_RED,
+            SerializedActorEffect::AffectHealth(_) => Color::LIME_GREEN,
+            SerializedActorEffect::InflictStatusEffect(_) => Color::CYAN,
+            SerializedActorEffect::GrantShield(_) => Color::GOLD,
+            SerializedActorEffect::SpawnEffect(..) => Color::GRAY,
+        }.with_a(ZONE_ALPHA)
+    }
+}
+
+impl ZoneTicker
+{
+    /// Advances the timer, returning how many times the zone's effect should be applied
+    pub fn tick(&mut self, delta: f32, interval: f32) -> u32
+    {
+        self.until_next_tick -= delta;
+        let mut ticks = 0;
+        while self.until_next_tick <= 0.0
+        {
+            self.until_next_tick += interval.max(f32::EPSILON);
+            ticks += 1;
+        }
+        ticks
+    }
+}
+
+impl ZoneInflicted
+{
+    /// Gives an actor the zone's status effect, or refreshes the one this zone already gave them
+    pub fn inflict(&mut self, actor: Entity, status_effects: &mut Vec<StatusEffect>, status: StatusEffect)
+    {
+        if self.0.contains(&actor)
+        {
+            let existing = status_effects.iter_mut()
+                .find(|effect| effect.stat == status.stat && effect.modification == status.modification);
+            if let Some(existing) = existing
+            {
+                existing.timeout = status.timeout;
+                return;
+            }
+        }
+        self.0.insert(actor);
+        status_effects.push(status);
+    }
+}
+
+impl ZoneReplicationBundle
+{
+    /// The zone's groups are set from its owner's groups when it is created on the server, by [`s_zone_authority`]
+    pub fn new(owner: Entity, radius: f32, duration: f32, tick_interval: f32, effect: SerializedActorEffect, affects: ZoneTargets, position: Vec2) -> Self
+    {
+        Self
+        {
+            zone: Zone { owner, radius, duration, tick_interval, effect, affects },
+            position: Position(position),
+            groups: CollisionGroups { memberships: PLAYER_PROJECTILE_GROUP, filters: Group::NONE },
+            replication: Replication,
+        }
+    }
+}
+
+impl ZoneAuthorityBundle
+{
+    pub fn new(owner: Entity, duration: f32, position: Vec2) -> Self
+    {
+        Self
+        {
+            transform: TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            lifetime: Lifetime(duration),
+            // The first tick happens straight away, so a zone always does something
+            ticker: ZoneTicker { until_next_tick: 0.0 },
+            inflicted: ZoneInflicted::default(),
+            name: format!("Zone of '{owner:?}'").into(),
+        }
+    }
+}
+
+/// Whether an actor (at a position, in some groups) is affected by a zone
+pub fn zone_affects(zone_pos: Vec2, radius: f32, zone_groups: &CollisionGroups, actor_pos: Vec2, actor_groups: &CollisionGroups) -> bool
+{
+    (zone_groups.filters.bits() & actor_groups.memberships.bits()) != 0 &&
+    zone_pos.distance_squared(actor_pos) <= radius * radius
+}
+
+/// This system (Authority only) adds the ZoneAuthorityBundle to new zones, and sets which groups they affect from their owner's groups.
+/// Zones are not a [`ReplicatedObject`](super::ReplicatedObject): their groups need the owner's groups, and their extras need mesh and material assets
+pub fn s_zone_authority(
+    mut commands: Commands,
+    mut new_zones: Query<(Entity, &Zone, &Position, &mut CollisionGroups), Added<Replication>>,
+    owners: Query<&CollisionGroups, Without<Zone>>,
+) {
+    for (entity, zone, position, mut groups) in &mut new_zones
+    {
+        let Some(mut ent_coms) = commands.get_entity(entity) else { continue; };
+
+        let owner_groups = owners.get(zone.owner).copied()
+            .unwrap_or(CollisionGroups { memberships: PLAYER_GROUP, filters: Group::ALL });
+        *groups = zone.affects.groups(&owner_groups);
+
+        ent_coms.insert(ZoneAuthorityBundle::new(zone.owner, zone.duration, position.0));
+    }
+}
+
+/// This system draws newly spawned/replicated zones as circles, beneath actors
+pub fn c_zone_extras(
+    mut commands: Commands,
+    new_zones: Query<(Entity, &Zone, &Position), Added<Replication>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (entity, zone, position) in &new_zones
+    {
+        let Some(mut ent_coms) = commands.get_entity(entity) else { continue; };
+
+        ent_coms.insert(MaterialMesh2dBundle
+        {
+            mesh: Mesh2dHandle(meshes.add(Circle::new(zone.radius))),
+            material: materials.add(zone.color()),
+            transform: Transform::from_translation(position.0.extend(-1.0)),
+            ..default()
+        });
+    }
+}
+
+/// Type alias for the actors zones can affect
+type ZoneActors<'w, 's> = Query<'w, 's, (Entity, &'static mut ActorContext, &'static Position, &'static CollisionGroups), (Without<Zone>, Without<Dead>)>;
+
+/// This system (Authority only) applies each zone's effect to the actors inside it, every tick interval
+pub fn s_tick_zones(
+    mut zones: Query<(&Zone, &Position, &CollisionGroups, &mut ZoneTicker, &mut ZoneInflicted)>,
+    mut actors: ZoneActors,
+    mut damage_events: EventWriter<DamageEvent>,
+    time: Res<Time>,
+) {
+    for (zone, zone_pos, zone_groups, mut ticker, mut inflicted) in &mut zones
+    {
+        let ticks = ticker.tick(time.delta_seconds(), zone.tick_interval);
+        if ticks == 0
+        {
+            continue;
+        }
+
+        for (actor_entity, mut actor, actor_pos, actor_groups) in &mut actors
+        {
+            if !zone_affects(zone_pos.0, zone.radius, zone_groups, actor_pos.0, actor_groups)
+            {
+                continue;
+            }
+            match zone.effect
+            {
+                // The zone's owner is the instigator, so they are credited with kills and on-damage effects
+                SerializedActorEffect::AffectHealth(amount) => for _ in 0..ticks
+                {
+                    damage_events.send(DamageEvent { instigator: zone.owner, victim: actor_entity, damage: -amount });
+                },
+                SerializedActorEffect::InflictStatusEffect(status) => inflicted.inflict(actor_entity, &mut actor.status_effects, status),
+                SerializedActorEffect::GrantShield(amount) => grant_shield(&mut actor, amount * ticks as f32),
+                // Rejected by upgrade validation, see SpawnType::nested_spawn
+                SerializedActorEffect::SpawnEffect(..) => {},
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::simple::gameplay::actor::{Stat, StatModification};
+
+    #[test]
+    fn test_zone_ticker()
+    {
+        let mut ticker = ZoneTicker { until_next_tick: 0.0 };
+        assert_eq!(ticker.tick(0.1, 0.5), 1);
+        assert_eq!(ticker.tick(0.3, 0.5), 0);
+        assert_eq!(ticker.tick(0.1, 0.5), 1);
+        // A long frame applies every tick that was missed
+        assert_eq!(ticker.tick(1.0, 0.5), 2);
+    }
+
+    #[test]
+    fn test_zone_affects_only_targeted_faction_in_radius()
+    {
+        let player = CollisionGroups { memberships: PLAYER_GROUP, filters: Group::ALL };
+        let enemy = CollisionGroups { memberships: ENEMY_GROUP, filters: Group::ALL };
+
+        let zone_groups = ZoneTargets::Enemies.groups(&player);
+        assert!(zone_affects(Vec2::ZERO, 50.0, &zone_groups, Vec2::new(30.0, 0.0), &enemy));
+        assert!(!zone_affects(Vec2::ZERO, 50.0, &zone_groups, Vec2::new(60.0, 0.0), &enemy));
+        assert!(!zone_affects(Vec2::ZERO, 50.0, &zone_groups, Vec2::new(30.0, 0.0), &player));
+
+        let healing_groups = ZoneTargets::Allies.groups(&player);
+        assert!(zone_affects(Vec2::ZERO, 50.0, &healing_groups, Vec2::new(30.0, 0.0), &player));
+        assert!(!zone_affects(Vec2::ZERO, 50.0, &healing_groups, Vec2::new(30.0, 0.0), &enemy));
+
+        // Zones owned by enemies affect the other side
+        let enemy_zone_groups = ZoneTargets::Enemies.groups(&enemy);
+        assert!(zone_affects(Vec2::ZERO, 50.0, &enemy_zone_groups, Vec2::new(30.0, 0.0), &player));
+        assert!(!zone_affects(Vec2::ZERO, 50.0, &enemy_zone_groups, Vec2::new(30.0, 0.0), &enemy));
+    }
+
+    #[test]
+    fn test_zone_groups_with_pvp_factions()
+    {
+        let faction = Group::GROUP_17;
+        let owner = CollisionGroups { memberships: PLAYER_GROUP | faction, filters: Group::ALL };
+        let other_player = CollisionGroups { memberships: PLAYER_GROUP | Group::GROUP_18, filters: Group::ALL };
+
+        let zone_groups = ZoneTargets::Enemies.groups(&owner);
+        assert!(zone_affects(Vec2::ZERO, 50.0, &zone_groups, Vec2::ZERO, &other_player));
+        assert!(!zone_affects(Vec2::ZERO, 50.0, &zone_groups, Vec2::ZERO, &owner));
+
+        let healing_groups = ZoneTargets::Allies.groups(&owner);
+        assert!(zone_affects(Vec2::ZERO, 50.0, &healing_groups, Vec2::ZERO, &owner));
+        assert!(!zone_affects(Vec2::ZERO, 50.0, &healing_groups, Vec2::ZERO, &other_player));
+    }
+
+    #[test]
+    fn test_zone_refreshes_its_status_effect()
+    {
+        let actor = Entity::from_raw(1);
+        let slow = StatusEffect { timeout: Some(2.0), stat: Stat::MovementSpeed, modification: StatModification::Multiply { factor: 0.5 } };
+        let mut status_effects = Vec::new();
+        let mut inflicted = ZoneInflicted::default();
+
+        inflicted.inflict(actor, &mut status_effects, slow);
+        status_effects[0].timeout = Some(0.5);
+        inflicted.inflict(actor, &mut status_effects, slow);
+        assert_eq!(status_effects, vec![slow]);
+
+        // A different zone stacks its own effect
+        ZoneInflicted::default().inflict(actor, &mut status_effects, slow);
+        assert_eq!(status_effects.len(), 2);
+    }
+}
@@ -0,0 +1,376 @@
+use std::{fmt::Display, fs, path::Path};
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::simple::{
+    gameplay::{
+        actor::{effect::SerializedEffectTrigger, ActorContext, StatusEffect},
+        classes::{ActorClass, ClassType, Classes},
+    },
+    data::TalentTree,
+    player::{Player, PlayerProfile},
+    consts::{SERVER_STR, TALENT_POINTS_PER_WAVE, TALENT_PROGRESS_PATH},
+};
+
+
+/// Something a talent gives the player's actor once unlocked
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum TalentGrant
+{
+    Effect(SerializedEffectTrigger),
+    StatusEffect(StatusEffect),
+}
+
+/// A single talent in a class's talent tree
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct TalentNode
+{
+    /// Unique within the tree, used by prerequisites and sent by clients when unlocking the talent
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default = "TalentNode::default_cost")]
+    pub cost: u32,
+    /// Ids of the talents that must be unlocked before this one
+    #[serde(default)]
+    pub prerequisites: Vec<String>,
+    pub grants: Vec<TalentGrant>,
+}
+
+impl TalentNode
+{
+    fn default_cost() -> u32
+    {
+        1
+    }
+}
+
+/// A player's progress in the talent tree of one class
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClassTalents
+{
+    /// Points that have been earned but not spent
+    pub points: u32,
+    pub unlocked: Vec<String>,
+}
+
+impl ClassTalents
+{
+    pub fn is_unlocked(&self, id: &str) -> bool
+    {
+        self.unlocked.iter().any(|unlocked| unlocked == id)
+    }
+}
+
+/// The talent progress of a player in every class they have played, so switching class does not lose progress.
+/// The server saves it in the TalentProgress store under the player's profile, so it is restored when they join again.
+/// Replicated so clients can show their talent trees
+#[derive(Component, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerTalents
+{
+    pub classes: HashMap<ClassType, ClassTalents>,
+}
+
+impl PlayerTalents
+{
+    pub fn class(&self, class: &ClassType) -> Option<&ClassTalents>
+    {
+        self.classes.get(class)
+    }
+}
+
+/// Every player's talent progress, keyed by their profile id, that the server saves to a file
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TalentProgress
+{
+    pub players: HashMap<u64, PlayerTalents>,
+}
+
+impl TalentProgress
+{
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String>
+    {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::de::from_str::<Self>(&contents).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String>
+    {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+/// Why the server refused to unlock a talent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TalentRejection
+{
+    /// The talent is not in the tree of the player's class
+    UnknownTalent,
+    AlreadyUnlocked,
+    MissingPrerequisite,
+    InsufficientPoints,
+}
+
+impl Display for TalentRejection
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            Self::UnknownTalent => f.write_str("unknown talent"),
+            Self::AlreadyUnlocked => f.write_str("already unlocked"),
+            Self::MissingPrerequisite => f.write_str("missing prerequisite"),
+            Self::InsufficientPoints => f.write_str("insufficient talent points"),
+        }
+    }
+}
+
+/// Checks whether a talent can be unlocked with the given progress, returning the talent if so
+pub fn can_unlock<'a>(tree: &'a TalentTree, talents: &ClassTalents, id: &str) -> Result<&'a TalentNode, TalentRejection>
+{
+    let node = tree.node(id).ok_or(TalentRejection::UnknownTalent)?;
+    if talents.is_unlocked(id)
+    {
+        return Err(TalentRejection::AlreadyUnlocked);
+    }
+    if !node.prerequisites.iter().all(|prerequisite| talents.is_unlocked(prerequisite))
+    {
+        return Err(TalentRejection::MissingPrerequisite);
+    }
+    if talents.points < node.cost
+    {
+        return Err(TalentRejection::InsufficientPoints);
+    }
+    Ok(node)
+}
+
+fn add_talent_to_actor(actor: &mut ActorContext, node: &TalentNode)
+{
+    for grant in &node.grants
+    {
+        match grant
+        {
+            TalentGrant::Effect(effect) => actor.effects.push(effect.clone()),
+            TalentGrant::StatusEffect(status) => actor.status_effects.push(*status),
+        }
+    }
+}
+
+/// An event emitted from clients to the server to unlock a talent in their current class's tree
+#[derive(Event, PartialEq, Serialize, Deserialize)]
+pub struct ChosenTalent
+{
+    pub talent: String,
+}
+
+/// Loads the saved talent progress of every player, if there is any
+pub fn s_load_talent_progress(
+    mut progress: ResMut<TalentProgress>,
+) {
+    if !Path::new(TALENT_PROGRESS_PATH).exists()
+    {
+        info!("{SERVER_STR} No saved talent progress found, it will be saved to '{TALENT_PROGRESS_PATH}'");
+        return;
+    }
+    match TalentProgress::load(TALENT_PROGRESS_PATH)
+    {
+        Ok(loaded) => *progress = loaded,
+        Err(e) => warn!("{SERVER_STR} Could not load talent progress from '{TALENT_PROGRESS_PATH}', starting from scratch: {e}"),
+    }
+}
+
+/// This system (Authority only) gives players who have just joined the talent progress saved for their profile
+pub fn s_restore_talent_progress(
+    progress: Res<TalentProgress>,
+    mut players: Query<(&Player, &PlayerProfile, &mut PlayerTalents), Added<PlayerProfile>>,
+) {
+    for (player, profile, mut talents) in &mut players
+    {
+        let Some(saved) = progress.players.get(&profile.id) else { continue; };
+        debug!("{SERVER_STR} Restoring the talent progress of client '{}'", player.0.get());
+        *talents = saved.clone();
+    }
+}
+
+/// This system (Authority only) saves players' talent progress whenever it changes
+pub fn s_save_talent_progress(
+    mut progress: ResMut<TalentProgress>,
+    players: Query<(&PlayerProfile, &PlayerTalents), Changed<PlayerTalents>>,
+) {
+    if players.is_empty()
+    {
+        return;
+    }
+    for (profile, talents) in &players
+    {
+        progress.players.insert(profile.id, talents.clone());
+    }
+    if let Err(e) = progress.save(TALENT_PROGRESS_PATH)
+    {
+        warn!("{SERVER_STR} Could not save talent progress to '{TALENT_PROGRESS_PATH}': {e}");
+    }
+}
+
+/// This system (Authority only) gives every player talent points in the class they are playing when a wave is completed
+pub fn s_award_talent_points(
+    mut players: Query<(&Player, &ActorClass, &mut PlayerTalents)>,
+) {
+    for (player, class, mut talents) in &mut players
+    {
+        let class_talents = talents.classes.entry(class.get_class().clone()).or_default();
+        class_talents.points += TALENT_POINTS_PER_WAVE;
+        debug!("{SERVER_STR} Client '{}' now has {} talent points as {}", player.0.get(), class_talents.points, class);
+    }
+}
+
+/// This system (Authority only, during a break) unlocks the talents clients choose, if they are valid for the player's class and progress
+pub fn s_receive_chosen_talents(
+    mut chosen_talents: EventReader<FromClient<ChosenTalent>>,
+    classes: Res<Classes>,
+    trees: Res<Assets<TalentTree>>,
+    mut players: Query<(&Player, &ActorClass, &mut ActorContext, &mut PlayerTalents)>,
+) {
+    for FromClient { client_id, event } in chosen_talents.read()
+    {
+        let Some((_, class, mut actor, mut talents)) = players.iter_mut().find(|(player, ..)| &player.0 == client_id) else
+        {
+            warn!("{SERVER_STR} Client '{}' chose a talent but has no player", client_id.get());
+            continue;
+        };
+        let Some(tree) = classes.classes.get(class.get_class()).and_then(|c| c.talents.as_ref()).and_then(|handle| trees.get(handle)) else
+        {
+            warn!("{SERVER_STR} Client '{}' chose a talent but {} has no talent tree", client_id.get(), class);
+            continue;
+        };
+
+        let class_talents = talents.classes.entry(class.get_class().clone()).or_default();
+        let node = match can_unlock(tree, class_talents, &event.talent)
+        {
+            Ok(node) => node,
+            Err(rejection) =>
+            {
+                warn!("{SERVER_STR} Rejected talent '{}' from client '{}': {rejection}", event.talent, client_id.get());
+                continue;
+            }
+        };
+
+        info!("{SERVER_STR} Client '{}' unlocked talent '{}'", client_id.get(), node.name);
+        class_talents.points -= node.cost;
+        class_talents.unlocked.push(node.id.clone());
+        add_talent_to_actor(&mut actor, node);
+    }
+}
+
+/// This system (Authority only) re-applies a player's unlocked talents whenever their class is set,
+/// as setting a class resets the actor to the class's base data
+pub fn s_apply_talents_on_class_change(
+    classes: Res<Classes>,
+    trees: Res<Assets<TalentTree>>,
+    mut players: Query<(&ActorClass, &mut ActorContext, &PlayerTalents), Changed<ActorClass>>,
+) {
+    for (class, mut actor, talents) in &mut players
+    {
+        let Some(class_talents) = talents.class(class.get_class()) else { continue; };
+        let Some(tree) = classes.classes.get(class.get_class()).and_then(|c| c.talents.as_ref()).and_then(|handle| trees.get(handle)) else { continue; };
+        for id in &class_talents.unlocked
+        {
+            let Some(node) = tree.node(id) else { warn!("{SERVER_STR} Unlocked talent '{id}' is no longer in the {} talent tree", class); continue; };
+            add_talent_to_actor(&mut actor, node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::simple::{
+        gameplay::actor::{Stat, StatModification},
+        data::ClassManifest,
+        consts::CLASS_MANIFEST_PATH,
+    };
+
+    use super::*;
+
+    fn test_tree() -> TalentTree
+    {
+        let grant = TalentGrant::StatusEffect(StatusEffect { timeout: None, stat: Stat::MovementSpeed, modification: StatModification::Multiply { factor: 1.1 } });
+        let node = |id: &str, cost: u32, prerequisites: &[&str]| TalentNode {
+            id: id.into(),
+            name: id.into(),
+            description: String::new(),
+            cost,
+            prerequisites: prerequisites.iter().map(|p| p.to_string()).collect(),
+            grants: vec![grant.clone()],
+        };
+        TalentTree { nodes: vec![node("root", 1, &[]), node("branch", 2, &["root"])] }
+    }
+
+    #[test]
+    fn test_can_unlock_checks_points_and_prerequisites()
+    {
+        let tree = test_tree();
+        let mut talents = ClassTalents { points: 1, unlocked: Vec::new() };
+
+        assert_eq!(can_unlock(&tree, &talents, "missing"), Err(TalentRejection::UnknownTalent));
+        assert_eq!(can_unlock(&tree, &talents, "branch"), Err(TalentRejection::MissingPrerequisite));
+        assert_eq!(can_unlock(&tree, &talents, "root").map(|node| node.id.as_str()), Ok("root"));
+
+        talents.unlocked.push("root".into());
+        assert_eq!(can_unlock(&tree, &talents, "root"), Err(TalentRejection::AlreadyUnlocked));
+        assert_eq!(can_unlock(&tree, &talents, "branch"), Err(TalentRejection::InsufficientPoints));
+
+        talents.points = 2;
+        assert!(can_unlock(&tree, &talents, "branch").is_ok());
+    }
+
+    #[test]
+    fn test_talent_grants_are_added_to_actor()
+    {
+        let tree = test_tree();
+        let mut actor = ActorContext::default();
+        add_talent_to_actor(&mut actor, tree.node("root").unwrap());
+        assert_eq!(actor.status_effects.len(), 1);
+        assert!(actor.effects.is_empty());
+    }
+
+    #[test]
+    fn test_talent_progress_save_load_round_trip()
+    {
+        let mut talents = PlayerTalents::default();
+        talents.classes.insert(ClassType::RANGED, ClassTalents { points: 2, unlocked: vec!["root".into()] });
+        talents.classes.insert(ClassType::MELEE, ClassTalents { points: 0, unlocked: vec!["root".into(), "branch".into()] });
+        let mut progress = TalentProgress::default();
+        progress.players.insert(42, talents);
+        progress.players.insert(PlayerProfile::HOST.id, PlayerTalents::default());
+
+        let path = std::env::temp_dir().join("cosmolio_test_talent_progress.ron");
+        progress.save(&path).expect("could not save talent progress");
+        let loaded = TalentProgress::load(&path).expect("could not load talent progress");
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded, progress);
+    }
+
+    #[test]
+    fn test_manifest_talent_trees()
+    {
+        let contents = std::fs::read_to_string(format!("assets/{CLASS_MANIFEST_PATH}")).expect("could not read the class manifest");
+        let manifest = ron::de::from_str::<ClassManifest>(&contents).expect("could not deserialize the class manifest");
+
+        for path in manifest.classes.iter().filter_map(|entry| entry.talents.as_ref())
+        {
+            let contents = std::fs::read_to_string(format!("assets/{path}")).unwrap_or_else(|_| panic!("could not read talent tree '{path}'"));
+            let tree = ron::de::from_str::<TalentTree>(&contents).unwrap_or_else(|e| panic!("could not deserialize talent tree '{path}': {e}"));
+            for node in &tree.nodes
+            {
+                assert_eq!(tree.nodes.iter().filter(|other| other.id == node.id).count(), 1, "talent '{}' in '{path}' is not unique", node.id);
+                for prerequisite in &node.prerequisites
+                {
+                    assert!(tree.node(prerequisite).is_some(), "talent '{}' in '{path}' requires unknown talent '{prerequisite}'", node.id);
+                }
+            }
+        }
+    }
+}
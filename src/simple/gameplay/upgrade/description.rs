@@ -1,4 +1,17 @@
-use super::UpgradeBehaviour;
+use super::{Upgrade, UpgradeBehaviour};
+
+impl Upgrade
+{
+    /// The upgrade's written description, or one generated from its behaviour if it was not given one
+    pub fn description(&self) -> String
+    {
+        match self.description.is_empty()
+        {
+            true => self.behaviour.describe(),
+            false => self.description.clone(),
+        }
+    }
+}
 
 impl UpgradeBehaviour
 {
@@ -8,7 +21,7 @@ impl UpgradeBehaviour
         {
             Self::AddEffects(effects) => 
             {
-                if effects.len() < 1
+                if effects.is_empty()
                 {
                     return "Does nothing!".into();
                 }
@@ -22,6 +35,22 @@ impl UpgradeBehaviour
                 }
                 format!("Gain status effect(s) that: {}", statuses.iter().map(|e| e.get_description()).collect::<Vec<String>>().join(" AND "))
             },
+            Self::ReplaceTriggered { trigger, effects } =>
+            {
+                if effects.is_empty()
+                {
+                    return format!("Lose all effects {}", trigger.describe());
+                }
+                format!("Lose all effects {}, and gain effect(s) that: {}", trigger.describe(), effects.iter().map(|e| e.describe()).collect::<Vec<String>>().join(" AND "))
+            },
+            Self::RemoveTriggered(triggers) =>
+            {
+                if triggers.is_empty()
+                {
+                    return "Does nothing!".into();
+                }
+                format!("Lose all effects {}", triggers.iter().map(|t| t.describe()).collect::<Vec<&str>>().join(" AND "))
+            },
         }
     }
 }
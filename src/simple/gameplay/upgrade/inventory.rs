@@ -0,0 +1,220 @@
+use bevy::prelude::*;
+
+use crate::simple::gameplay::{
+    actor::{effect::{SerializedEffectTrigger, TriggerType}, ActorContext, StatusEffect},
+    classes::ActorClass,
+};
+
+use super::{Upgrade, UpgradeBehaviour};
+
+
+/// An upgrade a player owns, and what it has given their actor
+pub struct OwnedUpgrade
+{
+    pub upgrade: Upgrade,
+    /// How many times the upgrade has been chosen
+    pub stacks: u32,
+    /// The (stacked) effects this upgrade added to the actor, so they can be found again when it is stacked
+    applied_effects: Vec<SerializedEffectTrigger>,
+    applied_status_effects: Vec<StatusEffect>,
+}
+
+/// Server side (and host) only component, tracking the upgrades a player owns in the order they chose them
+#[derive(Component, Default)]
+pub struct UpgradeInventory
+{
+    owned: Vec<OwnedUpgrade>,
+}
+
+impl UpgradeInventory
+{
+    /// How many times the player has chosen an upgrade
+    pub fn count(&self, name: &str) -> u32
+    {
+        self.get(name).map_or(0, |owned| owned.stacks)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OwnedUpgrade>
+    {
+        self.owned.iter().find(|owned| owned.upgrade.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &OwnedUpgrade>
+    {
+        self.owned.iter()
+    }
+
+    /// Gives an actor an upgrade. Choosing an upgrade that is already owned increases its magnitude, instead of adding its effects again
+    pub fn add_upgrade(&mut self, actor: &mut ActorContext, upgrade: &Upgrade)
+    {
+        let index = match self.owned.iter().position(|owned| owned.upgrade.name == upgrade.name)
+        {
+            Some(index) =>
+            {
+                self.owned[index].stacks += 1;
+                index
+            },
+            None =>
+            {
+                self.owned.push(OwnedUpgrade { upgrade: upgrade.clone(), stacks: 1, applied_effects: Vec::new(), applied_status_effects: Vec::new() });
+                self.owned.len() - 1
+            },
+        };
+        self.owned[index].apply(actor);
+    }
+
+    /// Gives an actor every owned upgrade again, in the order they were chosen.
+    /// Used when the actor's effects have been reset, e.g. by changing class
+    pub fn reapply(&mut self, actor: &mut ActorContext)
+    {
+        for owned in &mut self.owned
+        {
+            owned.applied_effects.clear();
+            owned.applied_status_effects.clear();
+            owned.apply(actor);
+        }
+    }
+}
+
+impl OwnedUpgrade
+{
+    /// Swaps whatever this upgrade previously gave the actor for its effects at the current stack count
+    fn apply(&mut self, actor: &mut ActorContext)
+    {
+        for applied in self.applied_effects.drain(..)
+        {
+            if let Some(index) = actor.effects.iter().position(|effect| effect.same_as(&applied))
+            {
+                actor.effects.remove(index);
+            }
+        }
+        for applied in self.applied_status_effects.drain(..)
+        {
+            if let Some(index) = actor.status_effects.iter().position(|status| status.same_as(&applied))
+            {
+                actor.status_effects.remove(index);
+            }
+        }
+
+        match &self.upgrade.behaviour
+        {
+            UpgradeBehaviour::AddEffects(effects) =>
+            {
+                self.applied_effects = effects.iter().map(|effect| effect.stacked(self.stacks)).collect();
+            },
+            UpgradeBehaviour::AddStatusEffects(statuses) =>
+            {
+                self.applied_status_effects = statuses.iter().map(|status| status.stacked(self.stacks)).collect();
+            },
+            UpgradeBehaviour::ReplaceTriggered { trigger, effects } =>
+            {
+                remove_effects(actor, &[*trigger]);
+                self.applied_effects = effects.iter().map(|effect| effect.stacked(self.stacks)).collect();
+            },
+            UpgradeBehaviour::RemoveTriggered(triggers) => remove_effects(actor, triggers),
+        }
+        actor.effects.extend(self.applied_effects.iter().cloned());
+        actor.status_effects.extend(self.applied_status_effects.iter().copied());
+    }
+}
+
+fn remove_effects(actor: &mut ActorContext, triggers: &[TriggerType])
+{
+    actor.effects.retain(|effect| !triggers.contains(&effect.trigger_type()));
+}
+
+/// This system (Authority only) gives players their upgrades back after changing class, as that resets their effects.
+/// It runs after talents are re-applied, so upgrades that replace or remove effects also act on effects from talents
+pub fn s_apply_upgrades_on_class_change(
+    mut players: Query<(&mut ActorContext, &mut UpgradeInventory), Changed<ActorClass>>,
+) {
+    for (mut actor, mut inventory) in &mut players
+    {
+        inventory.reapply(&mut actor);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::simple::gameplay::actor::{
+        effect::{SerializedDamageChangeEffect, SerializedKillEffect, SerializedActorEffect},
+        Stat, StatModification,
+    };
+
+    use super::*;
+
+    fn upgrade(name: &str, behaviour: UpgradeBehaviour) -> Upgrade
+    {
+        Upgrade { name: name.into(), behaviour, description: String::new(), rarity: default(), weight: None, unique: false, tags: Vec::new(), classes: Vec::new(), requires: Vec::new(), excludes: Vec::new() }
+    }
+
+    fn add_damage(amount: f32) -> SerializedEffectTrigger
+    {
+        SerializedEffectTrigger::OnDoDamage(SerializedDamageChangeEffect::AddDamageEffect { amount })
+    }
+
+    fn heal_on_kill(amount: f32) -> SerializedEffectTrigger
+    {
+        SerializedEffectTrigger::OnKill(SerializedKillEffect::RegularEffect { effect: SerializedActorEffect::AffectHealth(amount) })
+    }
+
+    #[test]
+    fn test_stacking_increases_magnitude_instead_of_duplicating()
+    {
+        let mut actor = ActorContext::default();
+        let class_effect = add_damage(0.5);
+        actor.effects.push(class_effect.clone());
+        let mut inventory = UpgradeInventory::default();
+        let damage = upgrade("Damage", UpgradeBehaviour::AddEffects(vec![add_damage(1.0)]));
+        let speed = upgrade("Speed", UpgradeBehaviour::AddStatusEffects(vec![StatusEffect { timeout: None, stat: Stat::MovementSpeed, modification: StatModification::Add { amount: 10.0 } }]));
+
+        inventory.add_upgrade(&mut actor, &damage);
+        inventory.add_upgrade(&mut actor, &speed);
+        inventory.add_upgrade(&mut actor, &damage);
+        inventory.add_upgrade(&mut actor, &damage);
+        inventory.add_upgrade(&mut actor, &speed);
+
+        assert_eq!(inventory.count("Damage"), 3);
+        assert_eq!(inventory.count("Speed"), 2);
+        assert_eq!(actor.effects, vec![class_effect, add_damage(3.0)]);
+        assert_eq!(actor.status_effects.len(), 1);
+        assert_eq!(actor.status_effects[0].modification, StatModification::Add { amount: 20.0 });
+    }
+
+    #[test]
+    fn test_replace_and_remove_by_trigger_type()
+    {
+        let mut actor = ActorContext::default();
+        actor.effects.extend([add_damage(1.0), heal_on_kill(1.0), heal_on_kill(2.0)]);
+        let mut inventory = UpgradeInventory::default();
+
+        inventory.add_upgrade(&mut actor, &upgrade("Vampire", UpgradeBehaviour::ReplaceTriggered { trigger: TriggerType::OnKill, effects: vec![heal_on_kill(5.0)] }));
+        assert_eq!(actor.effects, vec![add_damage(1.0), heal_on_kill(5.0)]);
+
+        // Stacking a replacement does not remove what it added, it scales it
+        inventory.add_upgrade(&mut actor, &upgrade("Vampire", UpgradeBehaviour::ReplaceTriggered { trigger: TriggerType::OnKill, effects: vec![heal_on_kill(5.0)] }));
+        assert_eq!(actor.effects, vec![add_damage(1.0), heal_on_kill(10.0)]);
+
+        inventory.add_upgrade(&mut actor, &upgrade("Pacifist", UpgradeBehaviour::RemoveTriggered(vec![TriggerType::OnDoDamage, TriggerType::OnKill])));
+        assert!(actor.effects.is_empty());
+    }
+
+    #[test]
+    fn test_reapply_restores_upgrades_after_reset()
+    {
+        let mut actor = ActorContext::default();
+        let mut inventory = UpgradeInventory::default();
+        let damage = upgrade("Damage", UpgradeBehaviour::AddEffects(vec![add_damage(1.0)]));
+        inventory.add_upgrade(&mut actor, &damage);
+        inventory.add_upgrade(&mut actor, &damage);
+
+        actor.effects.clear();
+        inventory.reapply(&mut actor);
+        assert_eq!(actor.effects, vec![add_damage(2.0)]);
+
+        // Upgrades chosen after reapplying still stack on what was reapplied
+        inventory.add_upgrade(&mut actor, &damage);
+        assert_eq!(actor.effects, vec![add_damage(3.0)]);
+    }
+}
@@ -1,48 +1,88 @@
 pub mod description;
+pub mod validation;
+mod inventory;
+
+pub use inventory::UpgradeInventory;
+pub use inventory::s_apply_upgrades_on_class_change;
+pub use validation::s_warn_about_invalid_upgrades;
 
 use bevy::prelude::*;
-use bevy_replicon::{core::ClientId, network_event::{client_event::FromClient, server_event::{SendMode, ToClients}}};
-use rand::{seq::IteratorRandom, thread_rng};
+use bevy_replicon::network_event::{client_event::FromClient, server_event::{SendMode, ToClients}};
+use rand::{rngs::StdRng, seq::SliceRandom, thread_rng, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 
 use crate::simple::{
-    gameplay::actor::{
-        effect::SerializedEffectTrigger,
-        StatusEffect,
-        ActorContext,
+    gameplay::{
+        actor::{
+            effect::{SerializedEffectTrigger, TriggerType},
+            StatusEffect,
+            ActorContext,
+        },
+        classes::{ActorClass, ClassType},
     },
     player::Player,
     consts::SERVER_STR,
     data::{StaticUpgrades, UpgradeCollection},
 };
 
-// #[derive(Deserialize, Serialize)]
-// pub enum TriggerType
-// {
-//     OnDamage,
-//     Periodically,
-//     OnKill,
-//     OnDeath,
-//     OnReceiveDamage,
-//     OnAbilityCast,
-//     OnAbilityHit,
-//     OnAbilityEnd,
-// }
-
-// #[derive(Deserialize, Serialize)]
-// pub enum ReplaceCriteria
-// {
-//     ByEffectTrigger(TriggerType),
-//     ByRegularEffectType(SerializedActorEffect),
-// }
-
 #[derive(Clone, PartialEq, Deserialize, Serialize, Reflect)]
 pub enum UpgradeBehaviour
 {
     AddEffects(Vec<SerializedEffectTrigger>),
     AddStatusEffects(Vec<StatusEffect>),
-    // ReplaceEffects{ criteria: ReplaceCriteria,  },
-    // RemoveEffects // Maybe?
+    /// Removes every effect the actor has with a trigger type, adding new effects in their place
+    ReplaceTriggered{ trigger: TriggerType, effects: Vec<SerializedEffectTrigger> },
+    /// Removes every effect the actor has with any of the trigger types
+    RemoveTriggered(Vec<TriggerType>),
+}
+
+/// How rare an upgrade is, rarer upgrades are offered less often
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize, Reflect)]
+pub enum UpgradeRarity
+{
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl UpgradeRarity
+{
+    /// How likely upgrades of this rarity are to be offered, relative to each other
+    pub fn weight(&self) -> f32
+    {
+        match self
+        {
+            Self::Common => 10.0,
+            Self::Uncommon => 5.0,
+            Self::Rare => 2.0,
+            Self::Legendary => 0.5,
+        }
+    }
+
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Common => "Common",
+            Self::Uncommon => "Uncommon",
+            Self::Rare => "Rare",
+            Self::Legendary => "Legendary",
+        }
+    }
+
+    /// The colour upgrades of this rarity are shown with
+    pub fn color(&self) -> Color
+    {
+        match self
+        {
+            Self::Common => Color::rgb(0.85, 0.85, 0.85),
+            Self::Uncommon => Color::rgb(0.3, 0.85, 0.3),
+            Self::Rare => Color::rgb(0.3, 0.5, 1.0),
+            Self::Legendary => Color::rgb(1.0, 0.6, 0.1),
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Deserialize, Serialize, Reflect)]
@@ -51,6 +91,71 @@ pub struct Upgrade
     pub name: String,
     pub behaviour: UpgradeBehaviour,
     pub description: String,
+    #[serde(default)]
+    pub rarity: UpgradeRarity,
+    /// Overrides the weight of the upgrade's rarity, 0.0 means it is never offered
+    #[serde(default)]
+    pub weight: Option<f32>,
+    /// Unique upgrades are not offered again once a player owns them
+    #[serde(default)]
+    pub unique: bool,
+    /// Labels other upgrades can require or exclude, e.g. "projectile" or "on_kill"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The classes this upgrade is offered to, every class when empty
+    #[serde(default)]
+    pub classes: Vec<ClassType>,
+    /// This upgrade is only offered once the player owns all of these
+    #[serde(default)]
+    pub requires: Vec<UpgradeRequirement>,
+    /// This upgrade is not offered once the player owns any of these
+    #[serde(default)]
+    pub excludes: Vec<UpgradeRequirement>,
+}
+
+impl Upgrade
+{
+    /// How likely this upgrade is to be offered, relative to other upgrades
+    pub fn weight(&self) -> f32
+    {
+        self.weight.unwrap_or_else(|| self.rarity.weight()).max(0.0)
+    }
+
+    pub fn allows_class(&self, class: &ClassType) -> bool
+    {
+        self.classes.is_empty() || self.classes.contains(class)
+    }
+
+    /// Whether this upgrade can be offered to a player of a class, given the upgrades they already own
+    pub fn available_to(&self, class: &ClassType, inventory: &UpgradeInventory) -> bool
+    {
+        self.allows_class(class)
+            && !(self.unique && inventory.count(&self.name) > 0)
+            && self.requires.iter().all(|requirement| requirement.met_by(inventory))
+            && !self.excludes.iter().any(|requirement| requirement.met_by(inventory))
+    }
+}
+
+/// Something a player can own, that upgrades can require or exclude
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Reflect)]
+pub enum UpgradeRequirement
+{
+    /// The upgrade with this name
+    Upgrade(String),
+    /// Any upgrade with this tag
+    Tag(String),
+}
+
+impl UpgradeRequirement
+{
+    pub fn met_by(&self, inventory: &UpgradeInventory) -> bool
+    {
+        match self
+        {
+            Self::Upgrade(name) => inventory.count(name) > 0,
+            Self::Tag(tag) => inventory.iter().any(|owned| owned.upgrade.tags.contains(tag)),
+        }
+    }
 }
 
 
@@ -59,6 +164,8 @@ pub struct Upgrade
 pub struct GeneratedAvailableUpgrades
 {
     pub upgrades: Vec<Upgrade>,
+    /// How many more times the player can reroll these upgrades
+    pub rerolls: u32,
 }
 
 /// An event emitted from clients to the server to indicate which upgrade a player has chosen
@@ -68,6 +175,10 @@ pub struct ChosenUpgrade
     pub upgrade: Upgrade,
 }
 
+/// An event emitted from clients to the server to swap their available upgrades for new ones
+#[derive(Event, PartialEq, Serialize, Deserialize)]
+pub struct RerollUpgrades;
+
 /// Stores upgrades that are available to the player.
 /// 
 /// Server side (and host) only component, used to track what upgrades are available to a player
@@ -77,36 +188,57 @@ pub struct AvailablePlayerUpgrades
 {
     pub chosen: bool,
     pub upgrades: Vec<Upgrade>,
+    pub rerolls: u32,
 }
 
+/// The seed every player's [`UpgradeRng`] is derived from, set it to make a match's upgrade offers reproducible
+#[derive(Resource)]
+pub struct UpgradeSeed(pub u64);
 
-fn generate_upgrades_for_client(_player_context: &ActorContext, upgrades: &UpgradeCollection) -> Vec<Upgrade>
+impl Default for UpgradeSeed
 {
-    // let behaviour = UpgradeBehaviour::AddEffects(vec![SerializedEffectTrigger::OnDamage(SerializedDamageEffect::AddDamageEffect { amount: -1.5 })]);
-    // let behaviour_2 = UpgradeBehaviour::AddEffects(vec![SerializedEffectTrigger::OnDamage(SerializedDamageEffect::RegularEffect{ effect: SerializedActorEffect::SpawnEffect(SpawnType::Explosion { radius: 50.0, damage: 1.0, knockback_strength: 50.0 }, SpawnLocation::AtCaster)})]);
-    // TODO: Randomly sample from predefined upgrades
-    //upgrades.upgrades.iter().map(|x| x.clone()).choose_multiple(&mut thread_rng(), 3)
-    upgrades.upgrades.iter().map(|x| x.clone()).collect::<Vec<Upgrade>>()
+    fn default() -> Self {
+        Self(thread_rng().gen())
+    }
 }
 
-fn add_upgrade_to_actor(actor: &mut ActorContext, upgrade: Upgrade)
+/// Server side (and host) only component, the RNG a player's upgrade offers are drawn with
+#[derive(Component)]
+pub struct UpgradeRng(pub StdRng);
+
+impl UpgradeRng
 {
-    match upgrade.behaviour
+    /// Each player gets their own sequence of offers, so one player's rerolls do not change what others are offered
+    pub fn for_client(seed: u64, client_id: u64) -> Self
     {
-        UpgradeBehaviour::AddEffects(effects) => 
-        {
-            for effect in effects
-            {
-                actor.effects.push(effect);
-            }
-        },
-        UpgradeBehaviour::AddStatusEffects(statuses) => 
-        {
-            for stat_effect in statuses
-            {
-                actor.status_effects.push(stat_effect);
-            }
-        }
+        Self(StdRng::seed_from_u64(seed ^ client_id.wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+    }
+}
+
+/// Draws the upgrades offered to a player, weighted by rarity, without repeats or upgrades unavailable to them (see [`Upgrade::available_to`])
+fn generate_upgrades_for_client(class: &ClassType, inventory: &UpgradeInventory, upgrades: &UpgradeCollection, rng: &mut impl Rng) -> Vec<Upgrade>
+{
+    let candidates: Vec<&Upgrade> = upgrades.upgrades.iter()
+        .filter(|upgrade| upgrade.available_to(class, inventory))
+        .filter(|upgrade| upgrade.weight() > 0.0)
+        .collect();
+    let Ok(chosen) = candidates.choose_multiple_weighted(rng, upgrades.offer_count, |upgrade| upgrade.weight()) else
+    {
+        error!("{SERVER_STR} Upgrade weights are invalid, offering no upgrades");
+        return Vec::new();
+    };
+    chosen.map(|upgrade| (*upgrade).clone()).collect()
+}
+
+/// This system (Authority only) gives new players their upgrade inventory and RNG
+pub fn s_setup_player_upgrades(
+    mut commands: Commands,
+    seed: Res<UpgradeSeed>,
+    new_players: Query<(Entity, &Player), Without<UpgradeInventory>>,
+) {
+    for (player_ent, player_id) in &new_players
+    {
+        commands.entity(player_ent).insert((UpgradeInventory::default(), UpgradeRng::for_client(seed.0, player_id.0.get())));
     }
 }
 
@@ -115,27 +247,60 @@ pub fn s_generate_and_emit_available_upgrades(
     mut available_upgrades_events: EventWriter<ToClients<GeneratedAvailableUpgrades>>,
     available_upgrades: Res<StaticUpgrades>,
     upgrade_asset: Res<Assets<UpgradeCollection>>,
-    players: Query<(Entity, &Player, &ActorContext)>,
+    mut players: Query<(Entity, &Player, &ActorClass, &UpgradeInventory, &mut UpgradeRng)>,
 ) {
     info!("{SERVER_STR} Generate availabe upgrades go!");
-    for (player_ent, player_id, context) in &players
+    for (player_ent, player_id, class, inventory, mut rng) in &mut players
     {
         let Some(static_upgrades) = upgrade_asset.get(&available_upgrades.upgrades) else { error!("Static Upgrades were not loaded!"); return; };
-        let upgrades = generate_upgrades_for_client(context, static_upgrades);
-        commands.entity(player_ent).insert(AvailablePlayerUpgrades { chosen: false, upgrades: upgrades.clone() });
-        available_upgrades_events.send(ToClients { mode: SendMode::Direct(player_id.0), event: GeneratedAvailableUpgrades { upgrades } });
+        let upgrades = generate_upgrades_for_client(class.get_class(), inventory, static_upgrades, &mut rng.0);
+        let rerolls = static_upgrades.rerolls_per_break;
+        commands.entity(player_ent).insert(AvailablePlayerUpgrades { chosen: false, upgrades: upgrades.clone(), rerolls });
+        available_upgrades_events.send(ToClients { mode: SendMode::Direct(player_id.0), event: GeneratedAvailableUpgrades { upgrades, rerolls } });
         debug!("{SERVER_STR} Sending upgrades to client {}", player_id.0.get());
     }
 }
 
+pub fn s_receive_upgrade_rerolls(
+    mut reroll_events: EventReader<FromClient<RerollUpgrades>>,
+    mut available_upgrades_events: EventWriter<ToClients<GeneratedAvailableUpgrades>>,
+    available_upgrades: Res<StaticUpgrades>,
+    upgrade_asset: Res<Assets<UpgradeCollection>>,
+    mut players: Query<(&Player, &ActorClass, &UpgradeInventory, &mut UpgradeRng, &mut AvailablePlayerUpgrades)>,
+) {
+    for FromClient { client_id, .. } in reroll_events.read()
+    {
+        let Some((_, class, inventory, mut rng, mut available)) = players.iter_mut().find(|(player_id, ..)| &player_id.0 == client_id) else
+        {
+            warn!("{SERVER_STR} Client {} asked to reroll upgrades, but has none available", client_id.get());
+            continue;
+        };
+        if available.chosen || available.rerolls == 0
+        {
+            warn!("{SERVER_STR} Client {} asked to reroll upgrades, but can not reroll", client_id.get());
+            continue;
+        }
+        let Some(static_upgrades) = upgrade_asset.get(&available_upgrades.upgrades) else { error!("Static Upgrades were not loaded!"); return; };
+
+        available.rerolls -= 1;
+        available.upgrades = generate_upgrades_for_client(class.get_class(), inventory, static_upgrades, &mut rng.0);
+        info!("{SERVER_STR} Rerolled upgrades for client {}, {} rerolls left", client_id.get(), available.rerolls);
+        available_upgrades_events.send(ToClients
+        {
+            mode: SendMode::Direct(*client_id),
+            event: GeneratedAvailableUpgrades { upgrades: available.upgrades.clone(), rerolls: available.rerolls },
+        });
+    }
+}
+
 pub fn s_receive_chosen_upgrades(
     mut commands: Commands,
     mut chosen_upgrades: EventReader<FromClient<ChosenUpgrade>>,
-    mut players: Query<(Entity, &Player, &mut ActorContext, &mut AvailablePlayerUpgrades)>,
+    mut players: Query<(Entity, &Player, &mut ActorContext, &mut UpgradeInventory, &mut AvailablePlayerUpgrades)>,
 ) {
     for FromClient { client_id, event } in chosen_upgrades.read()
     {
-        for (player_ent, player_id, mut actor_context, mut available_upgrades) in &mut players
+        for (player_ent, player_id, mut actor_context, mut inventory, mut available_upgrades) in &mut players
         {
             if &player_id.0 != client_id
             {
@@ -155,7 +320,7 @@ pub fn s_receive_chosen_upgrades(
             }
 
             info!("{SERVER_STR} Added upgrade to player's actor context. Description is: {}", event.upgrade.description);
-            add_upgrade_to_actor(&mut actor_context, event.upgrade.clone());
+            inventory.add_upgrade(&mut actor_context, &event.upgrade);
             available_upgrades.chosen = true;
             commands.entity(player_ent).remove::<AvailablePlayerUpgrades>();
             break;
@@ -163,3 +328,125 @@ pub fn s_receive_chosen_upgrades(
         warn!("{SERVER_STR} Client {} sent a ChosenUpgrade event that was not found (likely does not have AvailablePlayerUpgrades anymore)", client_id.get());
     }
 }
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn upgrade(name: &str, rarity: UpgradeRarity, unique: bool) -> Upgrade
+    {
+        Upgrade
+        {
+            name: name.into(),
+            behaviour: UpgradeBehaviour::AddEffects(Vec::new()),
+            description: String::new(),
+            rarity,
+            weight: None,
+            unique,
+            tags: Vec::new(),
+            classes: Vec::new(),
+            requires: Vec::new(),
+            excludes: Vec::new(),
+        }
+    }
+
+    fn collection(upgrades: Vec<Upgrade>, offer_count: usize) -> UpgradeCollection
+    {
+        UpgradeCollection { upgrades, offer_count, ..default() }
+    }
+
+    fn names(upgrades: &[Upgrade]) -> Vec<&str>
+    {
+        upgrades.iter().map(|upgrade| upgrade.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_offers_are_distinct_and_limited_to_offer_count()
+    {
+        let upgrades = collection((0..10).map(|i| upgrade(&i.to_string(), UpgradeRarity::Common, false)).collect(), 3);
+        let mut rng = UpgradeRng::for_client(1, 2).0;
+        for _ in 0..20
+        {
+            let offered = generate_upgrades_for_client(&ClassType::MELEE, &UpgradeInventory::default(), &upgrades, &mut rng);
+            let mut offered_names = names(&offered);
+            offered_names.sort();
+            offered_names.dedup();
+            assert_eq!(offered.len(), 3);
+            assert_eq!(offered_names.len(), 3);
+        }
+
+        // There may be fewer upgrades than offers
+        let few = collection(vec![upgrade("a", UpgradeRarity::Common, false)], 3);
+        assert_eq!(generate_upgrades_for_client(&ClassType::MELEE, &UpgradeInventory::default(), &few, &mut rng).len(), 1);
+    }
+
+    #[test]
+    fn test_owned_unique_and_weightless_upgrades_are_not_offered()
+    {
+        let mut never = upgrade("never", UpgradeRarity::Common, false);
+        never.weight = Some(0.0);
+        let upgrades = collection(vec![upgrade("unique", UpgradeRarity::Rare, true), upgrade("stackable", UpgradeRarity::Common, false), never], 3);
+        let mut rng = UpgradeRng::for_client(1, 2).0;
+
+        let mut inventory = UpgradeInventory::default();
+        let offered = generate_upgrades_for_client(&ClassType::MELEE, &inventory, &upgrades, &mut rng);
+        let mut offered_names = names(&offered);
+        offered_names.sort();
+        assert_eq!(offered_names, vec!["stackable", "unique"]);
+
+        inventory.add_upgrade(&mut ActorContext::default(), &upgrades.upgrades[0]);
+        inventory.add_upgrade(&mut ActorContext::default(), &upgrades.upgrades[1]);
+        assert_eq!(names(&generate_upgrades_for_client(&ClassType::MELEE, &inventory, &upgrades, &mut rng)), vec!["stackable"]);
+    }
+
+    #[test]
+    fn test_offers_are_reproducible_per_player()
+    {
+        let upgrades = collection((0..10).map(|i| upgrade(&i.to_string(), UpgradeRarity::Uncommon, false)).collect(), 3);
+        let offers = |seed, client| {
+            let mut rng = UpgradeRng::for_client(seed, client).0;
+            (0..5).flat_map(|_| generate_upgrades_for_client(&ClassType::MELEE, &UpgradeInventory::default(), &upgrades, &mut rng)).map(|upgrade| upgrade.name).collect::<Vec<_>>()
+        };
+        assert_eq!(offers(7, 1), offers(7, 1));
+        assert_ne!(offers(7, 1), offers(7, 2));
+    }
+
+    #[test]
+    fn test_rarer_upgrades_are_offered_less()
+    {
+        let upgrades = collection(vec![upgrade("common", UpgradeRarity::Common, false), upgrade("legendary", UpgradeRarity::Legendary, false)], 1);
+        let mut rng = UpgradeRng::for_client(3, 4).0;
+        let legendaries = (0..1000)
+            .filter(|_| names(&generate_upgrades_for_client(&ClassType::MELEE, &UpgradeInventory::default(), &upgrades, &mut rng)) == vec!["legendary"])
+            .count();
+        assert!(legendaries > 10 && legendaries < 150, "{legendaries} legendaries offered");
+    }
+
+    #[test]
+    fn test_offers_are_filtered_by_class_and_requirements()
+    {
+        let mut ranged_only = upgrade("ranged only", UpgradeRarity::Common, false);
+        ranged_only.classes = vec![ClassType::RANGED];
+        let mut tagged = upgrade("tagged", UpgradeRarity::Common, true);
+        tagged.tags = vec!["on_kill".into()];
+        let mut follow_up = upgrade("follow up", UpgradeRarity::Common, false);
+        follow_up.requires = vec![UpgradeRequirement::Upgrade("tagged".into())];
+        let mut rival = upgrade("rival", UpgradeRarity::Common, false);
+        rival.excludes = vec![UpgradeRequirement::Tag("on_kill".into())];
+        let upgrades = collection(vec![ranged_only, tagged, follow_up, rival], 4);
+        let mut rng = UpgradeRng::for_client(1, 2).0;
+        let offered = |class: &ClassType, inventory: &UpgradeInventory, rng: &mut StdRng| {
+            let mut offered_names: Vec<_> = generate_upgrades_for_client(class, inventory, &upgrades, rng).into_iter().map(|upgrade| upgrade.name).collect();
+            offered_names.sort();
+            offered_names
+        };
+
+        let mut inventory = UpgradeInventory::default();
+        assert_eq!(offered(&ClassType::MELEE, &inventory, &mut rng), vec!["rival", "tagged"]);
+        assert_eq!(offered(&ClassType::RANGED, &inventory, &mut rng), vec!["ranged only", "rival", "tagged"]);
+
+        inventory.add_upgrade(&mut ActorContext::default(), &upgrades.upgrades[1]);
+        assert_eq!(offered(&ClassType::MELEE, &inventory, &mut rng), vec!["follow up"]);
+    }
+}
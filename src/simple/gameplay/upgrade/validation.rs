@@ -0,0 +1,307 @@
+use std::fmt::Display;
+
+use bevy::prelude::*;
+
+use crate::simple::{
+    gameplay::{
+        actor::{effect::{SerializedEffectTrigger, SpawnType, TriggerType}, ChildType},
+        classes::{ClassType, Classes},
+    },
+    consts::SERVER_STR,
+    data::{ClassBaseData, StaticUpgrades, UpgradeCollection},
+};
+
+use super::{Upgrade, UpgradeBehaviour, UpgradeRequirement};
+
+
+/// Triggers that nothing applies yet, effects using them never happen for any class
+const UNAPPLIED_TRIGGERS: [TriggerType; 1] = [TriggerType::Periodically];
+
+/// Ability types spawned by effects (explosions and summons) rather than abilities, any class can have these through its other upgrades
+const SPAWNED_ABILITY_TYPES: [ChildType; 2] = [ChildType::Explosion, ChildType::ChildActor];
+
+/// The types of ability a class can use, so which ability effects can fire for it
+pub struct ClassAbilityTypes
+{
+    pub class: ClassType,
+    pub ability_types: Vec<ChildType>,
+}
+
+impl ClassAbilityTypes
+{
+    pub fn new(class: ClassType, data: &ClassBaseData) -> Self
+    {
+        let mut ability_types = Vec::new();
+        let data_types = data.abilities.iter().filter_map(|ability| ability.ability_type());
+        for ability_type in data_types.chain(SPAWNED_ABILITY_TYPES)
+        {
+            if !ability_types.contains(&ability_type)
+            {
+                ability_types.push(ability_type);
+            }
+        }
+        Self { class, ability_types }
+    }
+}
+
+/// Why an upgrade in a collection may never do anything, or may never be offered
+#[derive(Clone, Debug, PartialEq)]
+pub enum UpgradeIssue
+{
+    /// Nothing applies effects with this trigger
+    TriggerNeverFires(TriggerType),
+    /// These classes can be offered the upgrade, but have no abilities of the type its effects need
+    NoAbilityOfType{ ability_type: ChildType, classes: Vec<ClassType> },
+    /// The upgrade is limited to a class that does not exist
+    UnknownClass(ClassType),
+    /// A requirement or exclusion names an upgrade that does not exist
+    UnknownUpgrade(String),
+    /// A requirement or exclusion names a tag no upgrade has
+    UnknownTag(String),
+    /// An effect spawns an object that would spawn objects itself, which is not supported
+    NestedSpawn,
+}
+
+impl Display for UpgradeIssue
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            Self::TriggerNeverFires(trigger) => write!(f, "effects {} are never applied", trigger.describe()),
+            Self::NoAbilityOfType { ability_type, classes } =>
+            {
+                let classes: Vec<String> = classes.iter().map(ToString::to_string).collect();
+                write!(f, "needs {} abilities, which {} can not use", ability_type.name(), classes.join(", "))
+            },
+            Self::UnknownClass(class) => write!(f, "is limited to the unknown class '{class}'"),
+            Self::UnknownUpgrade(name) => write!(f, "refers to the unknown upgrade '{name}'"),
+            Self::UnknownTag(tag) => write!(f, "refers to the unknown tag '{tag}'"),
+            Self::NestedSpawn => write!(f, "spawns an object that spawns objects itself, the inner spawn never happens"),
+        }
+    }
+}
+
+fn upgrade_effects(upgrade: &Upgrade) -> &[SerializedEffectTrigger]
+{
+    match &upgrade.behaviour
+    {
+        UpgradeBehaviour::AddEffects(effects) | UpgradeBehaviour::ReplaceTriggered { effects, .. } => effects,
+        UpgradeBehaviour::AddStatusEffects(_) | UpgradeBehaviour::RemoveTriggered(_) => &[],
+    }
+}
+
+/// Finds the issues with every upgrade in a collection, given the ability types of every class, as pairs of upgrade name and issue
+pub fn validate_upgrades(collection: &UpgradeCollection, classes: &[ClassAbilityTypes]) -> Vec<(String, UpgradeIssue)>
+{
+    let mut issues = Vec::new();
+    for upgrade in &collection.upgrades
+    {
+        let mut report = |issue| issues.push((upgrade.name.clone(), issue));
+
+        for class in &upgrade.classes
+        {
+            if !classes.iter().any(|known| &known.class == class)
+            {
+                report(UpgradeIssue::UnknownClass(class.clone()));
+            }
+        }
+        for requirement in upgrade.requires.iter().chain(&upgrade.excludes)
+        {
+            match requirement
+            {
+                UpgradeRequirement::Upgrade(name) if !collection.upgrades.iter().any(|other| &other.name == name) =>
+                    report(UpgradeIssue::UnknownUpgrade(name.clone())),
+                UpgradeRequirement::Tag(tag) if !collection.upgrades.iter().any(|other| other.tags.contains(tag)) =>
+                    report(UpgradeIssue::UnknownTag(tag.clone())),
+                _ => {},
+            }
+        }
+
+        let mut reported_triggers = Vec::new();
+        let mut reported_ability_types = Vec::new();
+        if upgrade_effects(upgrade).iter().any(|effect| effect.spawn_type().is_some_and(SpawnType::nested_spawn))
+        {
+            report(UpgradeIssue::NestedSpawn);
+        }
+
+        for effect in upgrade_effects(upgrade)
+        {
+            let trigger = effect.trigger_type();
+            if UNAPPLIED_TRIGGERS.contains(&trigger)
+            {
+                if !reported_triggers.contains(&trigger)
+                {
+                    reported_triggers.push(trigger);
+                    report(UpgradeIssue::TriggerNeverFires(trigger));
+                }
+                continue;
+            }
+            let Some(ability_type) = effect.ability_type() else { continue; };
+            if reported_ability_types.contains(&ability_type)
+            {
+                continue;
+            }
+            let missing: Vec<ClassType> = classes.iter()
+                .filter(|class| upgrade.allows_class(&class.class))
+                .filter(|class| !class.ability_types.contains(&ability_type))
+                .map(|class| class.class.clone())
+                .collect();
+            if !missing.is_empty()
+            {
+                reported_ability_types.push(ability_type);
+                report(UpgradeIssue::NoAbilityOfType { ability_type, classes: missing });
+            }
+        }
+    }
+    issues
+}
+
+/// This system (Authority only) warns about upgrades that may never do anything for the classes they can be offered to.
+/// The `validate-upgrades` tool reports the same issues without starting the game
+pub fn s_warn_about_invalid_upgrades(
+    classes: Res<Classes>,
+    class_data: Res<Assets<ClassBaseData>>,
+    static_upgrades: Res<StaticUpgrades>,
+    upgrade_asset: Res<Assets<UpgradeCollection>>,
+) {
+    let Some(upgrades) = upgrade_asset.get(&static_upgrades.upgrades) else { error!("Static Upgrades were not loaded!"); return; };
+    let class_types: Vec<ClassAbilityTypes> = classes.classes.iter()
+        .filter_map(|(class_type, class)| Some(ClassAbilityTypes::new(class_type.clone(), class_data.get(&class.base_data)?)))
+        .collect();
+    for (upgrade, issue) in validate_upgrades(upgrades, &class_types)
+    {
+        warn!("{SERVER_STR} Upgrade '{upgrade}' {issue}");
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::simple::gameplay::{
+        actor::effect::{SerializedActorEffect, SerializedKillEffect, SerializedOnHitEffect, SpawnLocation, ZoneTargets},
+        classes::AbilityDefinition,
+        upgrade::UpgradeRarity,
+    };
+
+    use super::*;
+
+    fn upgrade(name: &str, effects: Vec<SerializedEffectTrigger>) -> Upgrade
+    {
+        Upgrade
+        {
+            name: name.into(),
+            behaviour: UpgradeBehaviour::AddEffects(effects),
+            description: String::new(),
+            rarity: UpgradeRarity::Common,
+            weight: None,
+            unique: false,
+            tags: Vec::new(),
+            classes: Vec::new(),
+            requires: Vec::new(),
+            excludes: Vec::new(),
+        }
+    }
+
+    fn on_hit(ability_type: ChildType) -> SerializedEffectTrigger
+    {
+        SerializedEffectTrigger::OnAbilityHit { ability_type, effect: SerializedOnHitEffect::RegularEffect { effect: SerializedActorEffect::AffectHealth(-1.0) } }
+    }
+
+    fn ability(object: &str) -> AbilityDefinition
+    {
+        ron::de::from_str(&format!("(name: \"Test\", trigger: JustPressed(Attack), object: {object}, damage: 1.0)"))
+            .expect("could not deserialize an ability")
+    }
+
+    fn classes() -> Vec<ClassAbilityTypes>
+    {
+        let melee_data = ClassBaseData { abilities: vec![ability("Stab(length: 15.0, width: 5.0)"), ability("Nothing")], ..Default::default() };
+        let ranged_data = ClassBaseData
+        {
+            abilities: vec![
+                ability("Bullet(speed: 175.0, size: 7.5, lifetime: 4.0, color: Rgba(red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0))"),
+                ability("Boomerang(speed: 400.0, size: 8.0, deceleration: 500.0, return_speed: 450.0)"),
+                ability("Missile(initial_speed: 50.0)"),
+                ability("Reload(Ammo)"),
+            ],
+            ..Default::default()
+        };
+        vec![
+            ClassAbilityTypes::new(ClassType::MELEE, &melee_data),
+            ClassAbilityTypes::new(ClassType::RANGED, &ranged_data),
+        ]
+    }
+
+    #[test]
+    fn test_class_ability_types()
+    {
+        let classes = classes();
+        assert_eq!(classes[0].ability_types, vec![ChildType::Melee, ChildType::Explosion, ChildType::ChildActor]);
+        assert_eq!(classes[1].ability_types, vec![ChildType::Projectile, ChildType::Boomerang, ChildType::Missile, ChildType::Explosion, ChildType::ChildActor]);
+    }
+
+    #[test]
+    fn test_ability_effects_are_checked_against_allowed_classes()
+    {
+        let mut ranged_only = upgrade("Ranged rounds", vec![on_hit(ChildType::Projectile)]);
+        ranged_only.classes = vec![ClassType::RANGED];
+        let collection = UpgradeCollection
+        {
+            upgrades: vec![
+                upgrade("Rounds", vec![on_hit(ChildType::Projectile), on_hit(ChildType::Projectile)]),
+                ranged_only,
+                upgrade("Explosive", vec![on_hit(ChildType::Explosion)]),
+                upgrade("Vampire", vec![SerializedEffectTrigger::OnKill(SerializedKillEffect::RegularEffect { effect: SerializedActorEffect::AffectHealth(1.0) })]),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(validate_upgrades(&collection, &classes()), vec![
+            ("Rounds".to_string(), UpgradeIssue::NoAbilityOfType { ability_type: ChildType::Projectile, classes: vec![ClassType::MELEE] }),
+        ]);
+    }
+
+    #[test]
+    fn test_unapplied_triggers_and_unknown_references()
+    {
+        let mut periodic = upgrade("Periodic", vec![SerializedEffectTrigger::Periodically { remaining_period: 0.0, period: 1.0, effect: SerializedActorEffect::AffectHealth(1.0) }]);
+        periodic.classes = vec![ClassType::from_static("necromancer")];
+        periodic.requires = vec![UpgradeRequirement::Upgrade("Missing".into())];
+        periodic.excludes = vec![UpgradeRequirement::Tag("missing".into())];
+        let collection = UpgradeCollection { upgrades: vec![periodic], ..Default::default() };
+
+        assert_eq!(validate_upgrades(&collection, &classes()), vec![
+            ("Periodic".to_string(), UpgradeIssue::UnknownClass(ClassType::from_static("necromancer"))),
+            ("Periodic".to_string(), UpgradeIssue::UnknownUpgrade("Missing".into())),
+            ("Periodic".to_string(), UpgradeIssue::UnknownTag("missing".into())),
+            ("Periodic".to_string(), UpgradeIssue::TriggerNeverFires(TriggerType::Periodically)),
+        ]);
+    }
+
+    #[test]
+    fn test_nested_spawns_are_rejected()
+    {
+        let zone = |effect| SerializedEffectTrigger::OnKill(SerializedKillEffect::RegularEffect
+        {
+            effect: SerializedActorEffect::SpawnEffect(SpawnType::Zone
+            {
+                radius: 40.0,
+                duration: 3.0,
+                tick_interval: 0.5,
+                effect: Box::new(effect),
+                affects: ZoneTargets::Enemies,
+            }, SpawnLocation::AtCaster),
+        });
+        let explosion = SpawnType::Explosion { radius: 10.0, damage: 1.0, knockback_strength: 0.0 };
+        let collection = UpgradeCollection
+        {
+            upgrades: vec![
+                upgrade("Burning", vec![zone(SerializedActorEffect::AffectHealth(-1.0))]),
+                upgrade("Exploding", vec![zone(SerializedActorEffect::SpawnEffect(explosion, SpawnLocation::AtCaster))]),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(validate_upgrades(&collection, &classes()), vec![("Exploding".to_string(), UpgradeIssue::NestedSpawn)]);
+    }
+}
@@ -1,13 +1,301 @@
+use std::{fs, path::Path};
 
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
 
+use crate::simple::consts::{ACTION_BINDINGS_PATH, CLIENT_STR};
+
+
+/// Something the player can do, independent of which input does it.
+/// Abilities and other input handling refer to these instead of specific keys, so players can rebind them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Reflect)]
 pub enum SimpleAction
 {
-    
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Attack,
+    SecondaryAttack,
+    AbilityOne,
+    AbilityTwo,
+    AbilityThree,
+    AbilityFour,
+    Utility,
+    Dash,
+    Reload,
+    SwapClass,
+    ToggleControls,
 }
 
+impl SimpleAction
+{
+    pub fn all() -> [Self; 15]
+    {
+        [
+            Self::MoveUp, Self::MoveDown, Self::MoveLeft, Self::MoveRight,
+            Self::Attack, Self::SecondaryAttack,
+            Self::AbilityOne, Self::AbilityTwo, Self::AbilityThree, Self::AbilityFour,
+            Self::Utility, Self::Dash, Self::Reload, Self::SwapClass, Self::ToggleControls,
+        ]
+    }
 
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Self::MoveUp => "Move Up",
+            Self::MoveDown => "Move Down",
+            Self::MoveLeft => "Move Left",
+            Self::MoveRight => "Move Right",
+            Self::Attack => "Attack",
+            Self::SecondaryAttack => "Secondary Attack",
+            Self::AbilityOne => "Ability 1",
+            Self::AbilityTwo => "Ability 2",
+            Self::AbilityThree => "Ability 3",
+            Self::AbilityFour => "Ability 4",
+            Self::Utility => "Utility",
+            Self::Dash => "Dash",
+            Self::Reload => "Reload",
+            Self::SwapClass => "Swap Class",
+            Self::ToggleControls => "Show Controls",
+        }
+    }
+}
 
+/// A physical input that can be bound to an action
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ActionTrigger
 {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// A button on any connected gamepad
+    Gamepad(GamepadButtonType),
+}
+
+impl std::fmt::Display for ActionTrigger
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self
+        {
+            Self::Key(key) => write!(f, "{key:?}"),
+            Self::Mouse(button) => write!(f, "Mouse {button:?}"),
+            Self::Gamepad(button) => write!(f, "Gamepad {button:?}"),
+        }
+    }
+}
+
+/// Which inputs trigger each action, loaded from (and saved to) a RON settings file on the client
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActionBindings
+{
+    pub bindings: HashMap<SimpleAction, Vec<ActionTrigger>>,
+}
+
+impl Default for ActionBindings
+{
+    fn default() -> Self {
+        use ActionTrigger::{Key, Gamepad};
+        // Movement on a gamepad uses the left stick, which isn't a button so isn't bound here
+        Self
+        {
+            bindings: HashMap::from_iter([
+                (SimpleAction::MoveUp, vec![Key(KeyCode::KeyW), Gamepad(GamepadButtonType::DPadUp)]),
+                (SimpleAction::MoveDown, vec![Key(KeyCode::KeyS), Gamepad(GamepadButtonType::DPadDown)]),
+                (SimpleAction::MoveLeft, vec![Key(KeyCode::KeyA), Gamepad(GamepadButtonType::DPadLeft)]),
+                (SimpleAction::MoveRight, vec![Key(KeyCode::KeyD), Gamepad(GamepadButtonType::DPadRight)]),
+                (SimpleAction::Attack, vec![Key(KeyCode::Space), Gamepad(GamepadButtonType::RightTrigger2)]),
+                (SimpleAction::SecondaryAttack, vec![Key(KeyCode::Enter), Gamepad(GamepadButtonType::RightTrigger)]),
+                (SimpleAction::AbilityOne, vec![Key(KeyCode::KeyQ), Gamepad(GamepadButtonType::West)]),
+                (SimpleAction::AbilityTwo, vec![Key(KeyCode::KeyE), Gamepad(GamepadButtonType::North)]),
+                (SimpleAction::AbilityThree, vec![Key(KeyCode::KeyC), Gamepad(GamepadButtonType::East)]),
+                (SimpleAction::AbilityFour, vec![Key(KeyCode::KeyF), Gamepad(GamepadButtonType::LeftTrigger)]),
+                (SimpleAction::Utility, vec![Key(KeyCode::KeyG), Gamepad(GamepadButtonType::LeftTrigger2)]),
+                (SimpleAction::Dash, vec![Key(KeyCode::KeyT), Gamepad(GamepadButtonType::South)]),
+                (SimpleAction::Reload, vec![Key(KeyCode::KeyR), Gamepad(GamepadButtonType::RightThumb)]),
+                (SimpleAction::SwapClass, vec![Key(KeyCode::Semicolon), Gamepad(GamepadButtonType::Select)]),
+                (SimpleAction::ToggleControls, vec![Key(KeyCode::F1), Gamepad(GamepadButtonType::Start)]),
+            ]),
+        }
+    }
+}
+
+impl ActionBindings
+{
+    pub fn triggers(&self, action: SimpleAction) -> &[ActionTrigger]
+    {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Binds an action to a trigger, replacing whatever the action was bound to on the same device (keyboard, mouse or gamepad)
+    pub fn rebind(&mut self, action: SimpleAction, trigger: ActionTrigger)
+    {
+        let triggers = self.bindings.entry(action).or_default();
+        triggers.retain(|existing| std::mem::discriminant(existing) != std::mem::discriminant(&trigger));
+        triggers.push(trigger);
+    }
+
+    /// Loads bindings from a file, any actions missing from the file keep their default bindings
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String>
+    {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let loaded = ron::de::from_str::<Self>(&contents).map_err(|e| e.to_string())?;
+        let mut bindings = Self::default();
+        bindings.bindings.extend(loaded.bindings);
+        Ok(bindings)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String>
+    {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ButtonState
+{
+    Pressed,
+    JustPressed,
+    JustReleased,
+}
+
+impl ButtonState
+{
+    fn check<T: Copy + Eq + std::hash::Hash + Send + Sync + 'static>(&self, input: &ButtonInput<T>, button: T) -> bool
+    {
+        match self
+        {
+            Self::Pressed => input.pressed(button),
+            Self::JustPressed => input.just_pressed(button),
+            Self::JustReleased => input.just_released(button),
+        }
+    }
+}
+
+/// Reads the state of actions through the current bindings
+#[derive(SystemParam)]
+pub struct ActionInput<'w>
+{
+    pub bindings: Res<'w, ActionBindings>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+}
+
+impl<'w> ActionInput<'w>
+{
+    /// Checks every trigger bound to an action, using the check that matches the given button state
+    fn any(&self, action: SimpleAction, state: ButtonState) -> bool
+    {
+        self.bindings.triggers(action).iter().any(|trigger| match trigger
+        {
+            ActionTrigger::Key(key) => state.check(&self.keys, *key),
+            ActionTrigger::Mouse(button) => state.check(&self.mouse, *button),
+            ActionTrigger::Gamepad(button_type) => self.gamepads.iter().any(|gamepad| state.check(&self.gamepad_buttons, GamepadButton::new(gamepad, *button_type))),
+        })
+    }
+
+    pub fn pressed(&self, action: SimpleAction) -> bool
+    {
+        self.any(action, ButtonState::Pressed)
+    }
+
+    pub fn just_pressed(&self, action: SimpleAction) -> bool
+    {
+        self.any(action, ButtonState::JustPressed)
+    }
+
+    pub fn just_released(&self, action: SimpleAction) -> bool
+    {
+        self.any(action, ButtonState::JustReleased)
+    }
+}
+
+/// The first key, mouse button or gamepad button pressed this frame, used when rebinding
+pub fn first_just_pressed(keys: &ButtonInput<KeyCode>, mouse: &ButtonInput<MouseButton>, gamepad_buttons: &ButtonInput<GamepadButton>) -> Option<ActionTrigger>
+{
+    keys.get_just_pressed().next().map(|key| ActionTrigger::Key(*key))
+        .or_else(|| mouse.get_just_pressed().next().map(|button| ActionTrigger::Mouse(*button)))
+        .or_else(|| gamepad_buttons.get_just_pressed().next().map(|button| ActionTrigger::Gamepad(button.button_type)))
+}
+
+/// Loads the action bindings from the settings file, creating the file from the defaults if it doesn't exist
+pub fn c_load_action_bindings(
+    mut bindings: ResMut<ActionBindings>,
+) {
+    if !Path::new(ACTION_BINDINGS_PATH).exists()
+    {
+        info!("{CLIENT_STR} No action bindings found, writing defaults to '{ACTION_BINDINGS_PATH}'");
+        if let Err(e) = bindings.save(ACTION_BINDINGS_PATH)
+        {
+            warn!("{CLIENT_STR} Could not save default action bindings: {e}");
+        }
+        return;
+    }
+    match ActionBindings::load(ACTION_BINDINGS_PATH)
+    {
+        Ok(loaded) => *bindings = loaded,
+        Err(e) => warn!("{CLIENT_STR} Could not load action bindings from '{ACTION_BINDINGS_PATH}', using defaults: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_every_action_has_a_default_binding()
+    {
+        let bindings = ActionBindings::default();
+        for action in SimpleAction::all()
+        {
+            assert!(!bindings.triggers(action).is_empty(), "{action:?} has no default binding");
+        }
+    }
+
+    #[test]
+    fn test_default_bindings_do_not_overlap()
+    {
+        let bindings = ActionBindings::default();
+        let mut bound = Vec::new();
+        for action in SimpleAction::all()
+        {
+            for trigger in bindings.triggers(action)
+            {
+                assert!(!bound.contains(trigger), "{trigger:?} is bound to {action:?} and another action by default");
+                bound.push(*trigger);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rebind_keeps_other_devices()
+    {
+        let mut bindings = ActionBindings::default();
+        bindings.rebind(SimpleAction::Attack, ActionTrigger::Key(KeyCode::KeyF));
+        bindings.rebind(SimpleAction::Attack, ActionTrigger::Gamepad(GamepadButtonType::RightTrigger));
+        bindings.rebind(SimpleAction::Attack, ActionTrigger::Key(KeyCode::KeyG));
+
+        let triggers = bindings.triggers(SimpleAction::Attack);
+        assert!(triggers.contains(&ActionTrigger::Key(KeyCode::KeyG)));
+        assert!(triggers.contains(&ActionTrigger::Gamepad(GamepadButtonType::RightTrigger)));
+        assert!(!triggers.contains(&ActionTrigger::Key(KeyCode::KeyF)));
+        assert_eq!(triggers.iter().filter(|trigger| matches!(trigger, ActionTrigger::Gamepad(_))).count(), 1);
+    }
+
+    #[test]
+    fn test_bindings_round_trip()
+    {
+        let mut bindings = ActionBindings::default();
+        bindings.rebind(SimpleAction::Attack, ActionTrigger::Mouse(MouseButton::Left));
+
+        let serialized = ron::ser::to_string(&bindings).expect("could not serialize bindings");
+        let deserialized = ron::de::from_str::<ActionBindings>(&serialized).expect("could not deserialize bindings");
 
+        assert_eq!(deserialized, bindings);
+        assert!(deserialized.triggers(SimpleAction::Attack).contains(&ActionTrigger::Mouse(MouseButton::Left)));
+    }
 }
@@ -0,0 +1,138 @@
+use bevy::{ecs::system::SystemParam, prelude::*, window::{CursorMoved, PrimaryWindow}};
+
+use crate::simple::{
+    gameplay::Enemy,
+    util::get_screenspace_cursor_pos_from_queries,
+    consts::{GAMEPAD_STICK_DEADZONE, GAMEPAD_AIM_POINT_DISTANCE, AIM_ASSIST_MAX_ANGLE, AIM_ASSIST_RANGE},
+};
+
+use super::LocalPlayer;
+
+
+/// Where the local player's aim came from this frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AimSource
+{
+    #[default]
+    Cursor,
+    Gamepad,
+}
+
+/// The direction the local player is aiming, updated every frame from the cursor or a gamepad's right stick.
+/// Ability systems should read this instead of querying the cursor themselves
+#[derive(Resource, Default)]
+pub struct LocalAim
+{
+    /// The normalised aim direction, None if there is nothing to aim with (e.g. the cursor is outside the window)
+    pub direction: Option<Vec2>,
+    /// The point in the world being aimed at, the cursor or a fixed distance along a gamepad's stick
+    pub point: Option<Vec2>,
+    pub source: AimSource,
+}
+
+/// Reads the sticks of connected gamepads
+#[derive(SystemParam)]
+pub struct GamepadSticks<'w>
+{
+    gamepads: Res<'w, Gamepads>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl<'w> GamepadSticks<'w>
+{
+    /// The direction of the first connected gamepad's stick, if it is pushed beyond the deadzone
+    pub fn stick(&self, x_axis: GamepadAxisType, y_axis: GamepadAxisType) -> Option<Vec2>
+    {
+        self.gamepads.iter().find_map(|gamepad| {
+            let x = self.axes.get(GamepadAxis::new(gamepad, x_axis))?;
+            let y = self.axes.get(GamepadAxis::new(gamepad, y_axis))?;
+            let stick = Vec2::new(x, y);
+            (stick.length() > GAMEPAD_STICK_DEADZONE).then_some(stick)
+        })
+    }
+}
+
+/// Snaps an aim direction onto the nearest target that is within range and close enough to the aimed direction
+pub fn apply_aim_assist(origin: Vec2, aim_direction: Vec2, targets: impl Iterator<Item = Vec2>) -> Vec2
+{
+    let min_alignment = AIM_ASSIST_MAX_ANGLE.cos();
+    targets
+        .filter_map(|target| {
+            let offset = target - origin;
+            let distance = offset.length();
+            if distance > AIM_ASSIST_RANGE || distance <= f32::EPSILON
+            {
+                return None;
+            }
+            let direction = offset / distance;
+            (direction.dot(aim_direction) >= min_alignment).then_some((distance, direction))
+        })
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map_or(aim_direction, |(_, direction)| direction)
+}
+
+/// Updates the LocalAim from the right stick of a gamepad (with aim assist) or otherwise the cursor
+pub fn c_update_local_aim(
+    mut aim: ResMut<LocalAim>,
+    player_q: Query<&GlobalTransform, With<LocalPlayer>>,
+    enemies: Query<&GlobalTransform, With<Enemy>>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    camera_q: Query<(&Camera, &GlobalTransform)>,
+    sticks: GamepadSticks,
+    mut cursor_moved: EventReader<CursorMoved>,
+) {
+    let mouse_moved = cursor_moved.read().count() > 0;
+    let Ok(player_trans) = player_q.get_single() else
+    {
+        aim.direction = None;
+        aim.point = None;
+        return;
+    };
+    let player_pos = player_trans.translation().truncate();
+
+    if let Some(stick) = sticks.stick(GamepadAxisType::RightStickX, GamepadAxisType::RightStickY)
+    {
+        let targets = enemies.iter().map(|trans| trans.translation().truncate());
+        let direction = apply_aim_assist(player_pos, stick.normalize(), targets);
+        aim.direction = Some(direction);
+        aim.point = Some(player_pos + direction * GAMEPAD_AIM_POINT_DISTANCE);
+        aim.source = AimSource::Gamepad;
+        return;
+    }
+
+    // Keep aiming where the stick last pointed until the mouse is moved
+    if aim.source == AimSource::Gamepad && !mouse_moved
+    {
+        return;
+    }
+    aim.point = get_screenspace_cursor_pos_from_queries(&window_q, &camera_q);
+    aim.direction = aim.point.and_then(|point| (point - player_pos).try_normalize());
+    aim.source = AimSource::Cursor;
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_aim_assist_picks_nearest_target_in_cone()
+    {
+        let near = Vec2::new(100.0, 10.0);
+        let far = Vec2::new(200.0, 0.0);
+        let behind = Vec2::new(-50.0, 0.0);
+
+        let aimed = apply_aim_assist(Vec2::ZERO, Vec2::X, [far, behind, near].into_iter());
+        assert!((aimed - near.normalize()).length() < 1e-5);
+    }
+
+    #[test]
+    fn test_aim_assist_ignores_targets_outside_cone_or_range()
+    {
+        let off_to_the_side = Vec2::new(10.0, 100.0);
+        let too_far = Vec2::new(AIM_ASSIST_RANGE * 2.0, 0.0);
+
+        let aimed = apply_aim_assist(Vec2::ZERO, Vec2::X, [off_to_the_side, too_far].into_iter());
+        assert_eq!(aimed, Vec2::X);
+    }
+}
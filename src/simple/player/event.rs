@@ -4,72 +4,107 @@ use serde::{Deserialize, Serialize};
 
 use crate::simple::{
     gameplay::{actor::ActorContext, classes::{ActorClass, Classes, ClassType}},
+    state::GameState,
     data::ClassBaseData,
+    consts::{CLIENT_STR, SERVER_STR},
 };
-use super::{LocalPlayer, Player};
+use super::{ActionInput, LobbyStatus, LocalPlayer, Player, SimpleAction};
 
 
+/// Events sent from clients to the server about their player, these are only accepted while in the lobby (GameState::ChoosingClass)
 #[derive(Event, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GeneralClientEvents
 {
     ChangeClass(ClassType),
     SwapClass,
+    SetReady(bool),
 }
 
+type ClassChangePlayers<'w, 's> = Query<'w, 's, (Entity, &'static Player, &'static mut ActorClass, &'static mut ActorContext, &'static mut LobbyStatus)>;
+
+/// Cycles the local player's class choice while in the lobby
 pub fn c_class_change(
     mut general_client_events: EventWriter<GeneralClientEvents>,
-    player_q: Query<&ActorClass, With<LocalPlayer>>,
-    input: Res<ButtonInput<KeyCode>>,
+    player_q: Query<&LobbyStatus, With<LocalPlayer>>,
+    classes: Res<Classes>,
+    input: ActionInput,
 ) {
 
-    if input.just_pressed(KeyCode::Semicolon)
+    if input.just_pressed(SimpleAction::SwapClass)
     {
-        info!("Sending Swap Class Request");
+        info!("{CLIENT_STR} Sending Swap Class Request");
         
-        let Ok(p_class) = player_q.get_single() else { return; };
+        let Ok(status) = player_q.get_single() else { return; };
 
-        let class_cycle = [ClassType::DefaultClass, ClassType::MeleeClass, ClassType::RangedClass];
+        let Some(next_class) = classes.next_class(&status.class) else { error!("Could not find class type {0:?}!", status.class); return; };
 
-        let Some(index) = class_cycle.iter().position(|x| *x == p_class.get_class()) else { error!("Could not find class type {0:?}!", p_class.get_class()); return; };
-        let index = (index + 1) % 3;
-        
-        general_client_events.send(GeneralClientEvents::ChangeClass(class_cycle[index]));
+        general_client_events.send(GeneralClientEvents::ChangeClass(next_class));
     }
 }
 
 pub fn s_general_client_events(
     mut commands: Commands,
-    mut players: Query<(Entity, &Player, &mut ActorClass, &mut ActorContext)>,
-    mut classes: ResMut<Classes>,
+    mut players: ClassChangePlayers,
+    classes: Res<Classes>,
     class_data: Res<Assets<ClassBaseData>>,
+    state: Res<State<GameState>>,
     mut client_events: EventReader<FromClient<GeneralClientEvents>>,
 ) {
     for FromClient { client_id, event } in client_events.read()
     {
+        if state.get() != &GameState::ChoosingClass
+        {
+            warn!("{SERVER_STR} Ignoring lobby event from client '{}' as the game is not in the lobby", client_id.get());
+            continue;
+        }
         match event
         {
-            GeneralClientEvents::ChangeClass(new_class) => change_class(&mut commands, &mut players, &class_data, &mut classes, *client_id, *new_class),
-            GeneralClientEvents::SwapClass => swap_class(&mut commands, &class_data, &mut classes, &mut players, *client_id),
+            GeneralClientEvents::ChangeClass(new_class) => change_class(&mut commands, &mut players, &class_data, &classes, *client_id, new_class.clone()),
+            GeneralClientEvents::SwapClass => swap_class(&mut commands, &class_data, &classes, &mut players, *client_id),
+            GeneralClientEvents::SetReady(ready) => set_ready(&mut players, *client_id, *ready),
         }
     }
 }
 
 fn change_class(
     commands: &mut Commands,
-    players: &mut Query<(Entity, &Player, &mut ActorClass, &mut ActorContext)>,
+    players: &mut ClassChangePlayers,
     class_data: &Res<Assets<ClassBaseData>>,
-    classes: &mut ResMut<Classes>,
+    classes: &Classes,
     player_id: ClientId,
     class: ClassType,
 ) {
-    for (entity, player, mut player_class, mut actor_context) in players
+    for (entity, player, mut player_class, mut actor_context, mut status) in players
     {
         if player.0 != player_id
         {
             continue;
         }
-        
-        player_class.set_class(commands, class_data, classes, &mut actor_context, entity, class);
+
+        // The lobby only shows the new class once the player actually has it
+        match player_class.set_class(commands, class_data, classes, &mut actor_context, entity, class.clone())
+        {
+            Ok(()) => status.choose_class(class),
+            Err(e) => warn!("{SERVER_STR} Client '{}' can not change class: {e}", player_id.get()),
+        }
+        break;
+    }
+}
+
+fn set_ready(
+    players: &mut ClassChangePlayers,
+    player_id: ClientId,
+    ready: bool,
+) {
+    for (_, player, _, _, mut status) in players
+    {
+        if player.0 != player_id
+        {
+            continue;
+        }
+
+        info!("{SERVER_STR} Client '{}' is {}", player_id.get(), if ready { "ready" } else { "not ready" });
+        status.ready = ready;
         break;
     }
 }
@@ -77,11 +112,11 @@ fn change_class(
 fn swap_class(
     commands: &mut Commands,
     class_data: &Res<Assets<ClassBaseData>>,
-    classes: &mut ResMut<Classes>,
-    players: &mut Query<(Entity, &Player, &mut ActorClass, &mut ActorContext)>,
+    classes: &Classes,
+    players: &mut ClassChangePlayers,
     player_id: ClientId,
 ) {
-    for (entity, player, mut player_class, mut actor) in players
+    for (entity, player, mut player_class, mut actor, mut status) in players
     {
         if player.0 != player_id
         {
@@ -89,13 +124,15 @@ fn swap_class(
         }
 
         info!("Swapping client '{}'s class", player_id.get());
-        if player_class.get_class() == ClassType::DefaultClass
+        let Some(new_class) = classes.next_class(player_class.get_class()) else
         {
-            player_class.set_class(commands, &class_data, classes, &mut actor, entity, ClassType::MeleeClass);
-        }
-        else
+            warn!("{SERVER_STR} Client '{}' can not swap from unknown class '{}'", player_id.get(), player_class.get_class());
+            break;
+        };
+        match player_class.set_class(commands, class_data, classes, &mut actor, entity, new_class.clone())
         {
-            player_class.set_class(commands, &class_data, classes, &mut actor, entity, ClassType::DefaultClass);
+            Ok(()) => status.choose_class(new_class),
+            Err(e) => warn!("{SERVER_STR} Client '{}' can not swap class: {e}", player_id.get()),
         }
         break;
     }
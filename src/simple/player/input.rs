@@ -1,30 +1,37 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::{ActionInput, SimpleAction, aim::GamepadSticks};
+
 #[derive(Debug, Default, Deserialize, Event, Serialize)]
 pub struct MoveDirection(pub Vec2);
 
-pub fn c_movement_input(mut move_events: EventWriter<MoveDirection>, input: Res<ButtonInput<KeyCode>>)
+pub fn c_movement_input(mut move_events: EventWriter<MoveDirection>, input: ActionInput, sticks: GamepadSticks)
 {
     let mut direction = Vec2::ZERO;
-    if input.pressed(KeyCode::KeyD)
+    if input.pressed(SimpleAction::MoveRight)
     {
         direction.x += 1.0;
     }
-    if input.pressed(KeyCode::KeyA)
+    if input.pressed(SimpleAction::MoveLeft)
     {
         direction.x -= 1.0;
     }
-    if input.pressed(KeyCode::KeyW)
+    if input.pressed(SimpleAction::MoveUp)
     {
         direction.y += 1.0;
     }
-    if input.pressed(KeyCode::KeyS)
+    if input.pressed(SimpleAction::MoveDown)
     {
         direction.y -= 1.0;
     }
+    if let Some(stick) = sticks.stick(GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY)
+    {
+        direction += stick;
+    }
+    // Clamped rather than normalised so a partly pushed stick moves the player slower
     if direction != Vec2::ZERO
     {
-        move_events.send(MoveDirection(direction.normalize_or_zero()));
+        move_events.send(MoveDirection(direction.clamp_length_max(1.0)));
     }
 }
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::simple::{
+    gameplay::classes::ClassType,
+    consts::SERVER_STR,
+};
+
+use super::Player;
+
+
+/// A player's choices while in the lobby (GameState::ChoosingClass), replicated so every client can show the roster
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LobbyStatus
+{
+    pub class: ClassType,
+    pub ready: bool,
+}
+
+impl LobbyStatus
+{
+    pub fn new(class: ClassType) -> Self
+    {
+        Self { class, ready: false }
+    }
+
+    /// Picks a new class, which un-readies the player so others don't start the game on a choice they haven't seen
+    pub fn choose_class(&mut self, class: ClassType)
+    {
+        if self.class != class
+        {
+            self.ready = false;
+        }
+        self.class = class;
+    }
+}
+
+/// Whether the game can be started, which is when there is at least one player and every player is ready
+pub fn everyone_ready<'a>(statuses: impl IntoIterator<Item = &'a LobbyStatus>) -> bool
+{
+    let mut any = false;
+    for status in statuses
+    {
+        if !status.ready
+        {
+            return false;
+        }
+        any = true;
+    }
+    any
+}
+
+/// A line of the lobby roster for the given player
+pub fn roster_line(player: &Player, status: &LobbyStatus, is_local: bool) -> String
+{
+    format!(
+        "{}{}: {} - {}",
+        if is_local { "(You) " } else { "" },
+        player.0.get(),
+        status.class,
+        if status.ready { "Ready" } else { "Not Ready" },
+    )
+}
+
+/// This system (Authority only) un-readies every player when returning to the lobby
+pub fn s_reset_lobby_ready(
+    mut players: Query<&mut LobbyStatus>,
+) {
+    info!("{SERVER_STR} Resetting lobby readiness");
+    for mut status in &mut players
+    {
+        status.ready = false;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_everyone_ready()
+    {
+        let mut statuses = [LobbyStatus::new(ClassType::MELEE), LobbyStatus::new(ClassType::RANGED)];
+        assert!(!everyone_ready(&[]));
+        assert!(!everyone_ready(&statuses));
+
+        statuses[0].ready = true;
+        assert!(!everyone_ready(&statuses));
+
+        statuses[1].ready = true;
+        assert!(everyone_ready(&statuses));
+    }
+
+    #[test]
+    fn test_changing_class_unreadies()
+    {
+        let mut status = LobbyStatus::new(ClassType::MELEE);
+        status.ready = true;
+        status.choose_class(ClassType::MELEE);
+        assert!(status.ready);
+
+        status.choose_class(ClassType::RANGED);
+        assert!(!status.ready);
+        assert_eq!(status.class, ClassType::RANGED);
+    }
+}
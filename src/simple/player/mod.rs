@@ -1,15 +1,24 @@
+mod action;
+mod aim;
 mod event;
 mod input;
+mod lobby;
 mod movement;
+mod profile;
 mod spawning;
 
 use std::thread::spawn;
 
 pub use spawning::{LocalPlayer, Player, PlayerServerBundle};
 pub use event::GeneralClientEvents;
+pub use lobby::{LobbyStatus, everyone_ready, roster_line};
+pub use action::{ActionBindings, ActionInput, ActionTrigger, SimpleAction, first_just_pressed};
+pub use aim::{LocalAim, c_update_local_aim};
+pub use profile::PlayerProfile;
 
 use bevy::prelude::*;
 use bevy_replicon::prelude::*;
+use bevy_replicon::core::common_conditions::has_authority;
 
 #[derive(Resource, Reflect)]
 pub struct LocalPlayerId
@@ -19,15 +28,7 @@ pub struct LocalPlayerId
     pub entity: Entity
 }
 
-impl LocalPlayerId
-{
-    pub fn should_predict(&self) -> bool
-    {
-        !self.is_host
-    }
-}
-
-use crate::simple::state;
+use crate::simple::{setup, state};
 
 pub struct PlayerPlugin;
 
@@ -38,6 +39,10 @@ impl Plugin for PlayerPlugin
         app
             .replicate::<Player>()
             .replicate::<spawning::PlayerColor>()
+            .replicate::<lobby::LobbyStatus>()
+            .init_resource::<action::ActionBindings>()
+            .init_resource::<aim::LocalAim>()
+            .add_systems(Startup, action::c_load_action_bindings.run_if(setup::is_client_instance))
             .add_client_event::<event::GeneralClientEvents>(ChannelKind::Ordered)
             .add_client_event::<input::MoveDirection>(ChannelKind::Ordered)
             .add_systems(FixedUpdate, (
@@ -48,8 +53,10 @@ impl Plugin for PlayerPlugin
             .add_systems(FixedUpdate, (
                 movement::c_movement_predict,
                 input::c_movement_input,
-                event::c_class_change,
+                aim::c_update_local_aim,
             ).in_set(state::HostAndClientSystems))
+            .add_systems(FixedUpdate, event::c_class_change.in_set(state::HostAndClientSystems).in_set(state::ChoosingClassSystems))
+            .add_systems(OnEnter(state::GameState::ChoosingClass), lobby::s_reset_lobby_ready.run_if(has_authority))
             .add_systems(PreUpdate, spawning::c_player_spawns.after(ClientSet::Receive))
             ;
     }
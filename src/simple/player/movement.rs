@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy_replicon::prelude::*;
 
-use crate::simple::gameplay::{Position, Knockback};
+use crate::simple::gameplay::{Dead, Position, Knockback};
 
 use super::{input::MoveDirection, Player, LocalPlayer};
 
@@ -10,7 +10,7 @@ pub const MOVE_SPEED: f32 = 300.0; // TODO: NOT THIS!! FK GLOBALS
 pub fn s_movement_events(
     time: Res<Time>,
     mut move_events: EventReader<FromClient<MoveDirection>>,
-    mut players: Query<(&Player, &mut Position, &Knockback)>,
+    mut players: Query<(&Player, &mut Position, &Knockback), Without<Dead>>,
 ) {
     for FromClient { client_id, event } in move_events.read()
     {
@@ -21,7 +21,7 @@ pub fn s_movement_events(
                 continue;
             }
             if client_id == &player.0 {
-                let movement = event.0 * time.delta_seconds() * MOVE_SPEED;
+                let movement = event.0.clamp_length_max(1.0) * time.delta_seconds() * MOVE_SPEED;
                 **position += movement;
             }
         }
@@ -0,0 +1,83 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use bevy_replicon_renet::renet::transport::NETCODE_USER_DATA_BYTES;
+use serde::{Deserialize, Serialize};
+
+use crate::simple::consts::CLIENT_STR;
+
+
+/// Identifies a player across sessions, unlike their ClientId which changes every time they connect.
+/// Clients keep theirs in a settings file and send it to the server when connecting, so the server can keep their progress
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PlayerProfile
+{
+    pub id: u64,
+}
+
+impl PlayerProfile
+{
+    /// The profile of the player playing on the server's machine, the server's saved progress is only ever for that machine so it doesn't need a unique id
+    pub const HOST: Self = Self { id: 0 };
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String>
+    {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::de::from_str::<Self>(&contents).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String>
+    {
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    /// Loads the profile from the settings file, creating a new profile if there isn't one
+    pub fn load_or_create(path: &str) -> Self
+    {
+        if Path::new(path).exists()
+        {
+            match Self::load(path)
+            {
+                Ok(profile) => return profile,
+                Err(e) => warn!("{CLIENT_STR} Could not load player profile from '{path}', creating a new one: {e}"),
+            }
+        }
+
+        let profile = Self { id: rand::random::<u64>().max(1) };
+        info!("{CLIENT_STR} Writing new player profile to '{path}'");
+        if let Err(e) = profile.save(path)
+        {
+            warn!("{CLIENT_STR} Could not save player profile, progress will not be kept between sessions: {e}");
+        }
+        profile
+    }
+
+    /// Packs the profile into the user data sent when connecting to a server
+    pub fn to_user_data(self) -> [u8; NETCODE_USER_DATA_BYTES]
+    {
+        let mut user_data = [0; NETCODE_USER_DATA_BYTES];
+        user_data[..8].copy_from_slice(&self.id.to_le_bytes());
+        user_data
+    }
+
+    pub fn from_user_data(user_data: &[u8; NETCODE_USER_DATA_BYTES]) -> Self
+    {
+        let mut id = [0; 8];
+        id.copy_from_slice(&user_data[..8]);
+        Self { id: u64::from_le_bytes(id) }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn test_profile_user_data_round_trip()
+    {
+        let profile = PlayerProfile { id: 0x0123_4567_89ab_cdef };
+        assert_eq!(PlayerProfile::from_user_data(&profile.to_user_data()), profile);
+    }
+}
@@ -1,19 +1,20 @@
 use bevy::prelude::*;
 use bevy_replicon::prelude::*;
+use bevy_replicon_renet::renet::{self, transport::NetcodeServerTransport};
 use bevy_rapier2d::prelude::*;
 use serde::{Deserialize, Serialize};
 
 
 use crate::simple::{
     gameplay::{
-        Position, Knockback, Damageable,
-        actor::{ActorContext, ActorSensors},
+        Position, Knockback, Damageable, PlayerTalents,
+        actor::{ActorContext, ActorResources, ActorSensors},
         classes::{ActorClass, ClassType},
     },
-    player::LocalPlayerId,
+    player::{LobbyStatus, LocalPlayerId, PlayerProfile},
     data::Images,
     ui::HealthBar,
-    consts::{PLAYER_GROUP, PLAYER_SENSOR_FILTER},
+    consts::{PLAYER_GROUP, PLAYER_SENSOR_FILTER, PLAYER_DEFAULT_CLASS},
 };
 
 
@@ -59,11 +60,15 @@ impl PlayerClientBundle
 pub struct PlayerServerBundle
 {
     player: Player,
+    profile: PlayerProfile,
     position: Position,
     velocity: Velocity,
     color: PlayerColor,
     class: ActorClass,
+    lobby: LobbyStatus,
+    talents: PlayerTalents,
     actor: ActorContext,
+    resources: ActorResources,
     knockback: Knockback,
     damageable: Damageable,
     sensor: Sensor,
@@ -75,18 +80,22 @@ pub struct PlayerServerBundle
 
 impl PlayerServerBundle
 {
-    pub fn new(id: ClientId, position: Vec2, color: Color) -> Self
+    pub fn new(id: ClientId, profile: PlayerProfile, position: Vec2, color: Color, class: ClassType) -> Self
     {
         Self 
         { 
             player: Player(id), 
+            profile,
             position: Position(position), 
             velocity: Velocity::zero(),
             color: PlayerColor(color), 
-            class: ActorClass::new(ClassType::MeleeClass),
+            class: ActorClass::new(class.clone()),
+            lobby: LobbyStatus::new(class),
+            talents: PlayerTalents::default(),
             actor: ActorContext::default(),
+            resources: ActorResources::default(),
             knockback: Knockback::default(),
-            damageable: Damageable { invulnerability_remaining: 0.0, invulnerability_duration: 0.5 },
+            damageable: Damageable { invulnerability_remaining: 0.0 },
             sensor: Sensor,
             actor_sensors: ActorSensors { sensors: vec![] },
             collider: Collider::ball(12.5),
@@ -100,6 +109,7 @@ impl PlayerServerBundle
 pub fn s_conn_events(
     mut commands: Commands, 
     mut server_event: EventReader<ServerEvent>,
+    transport: Option<Res<NetcodeServerTransport>>,
     players: Query<(Entity, &Player)>,
 ) {
     for event in server_event.read()
@@ -113,10 +123,20 @@ pub fn s_conn_events(
                 let r = ((p_id % 25) as f32) / 25.0;
                 let g = ((p_id % 19) as f32) / 19.0;
                 let b = ((p_id % 29) as f32) / 29.0;
+                let profile = transport.as_ref()
+                    .and_then(|transport| transport.user_data(renet::ClientId::from_raw(p_id)))
+                    .map(|user_data| PlayerProfile::from_user_data(&user_data))
+                    .filter(|profile| *profile != PlayerProfile::HOST)
+                    .unwrap_or_else(|| {
+                        warn!("Server: Client '{p_id}' did not send a player profile, their progress will not be kept");
+                        PlayerProfile { id: p_id }
+                    });
                 commands.spawn(PlayerServerBundle::new(
                     *client_id,
+                    profile,
                     Vec2::ZERO,
                     Color::rgb(r, g, b),
+                    PLAYER_DEFAULT_CLASS,
                 ));
             }
             ServerEvent::ClientDisconnected { client_id, reason } => {
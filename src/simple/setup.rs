@@ -8,8 +8,10 @@ use std::{error::Error, net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket}, time::Sy
 use crate::simple::{
     state::{GameState, SetupSystems},
     data::WaitingHandles,
-    player::{PlayerServerBundle, LocalPlayerId},
+    gameplay::{MatchMode, MatchRules, UpgradeSeed, classes::{PredictionMode, build_classes_from_manifest}},
+    player::{PlayerServerBundle, PlayerProfile, LocalPlayerId},
     ui::InfoText,
+    consts::{PLAYER_DEFAULT_CLASS, PLAYER_PROFILE_PATH},
 };
 
 pub struct SetupPlugin;
@@ -21,7 +23,7 @@ impl Plugin for SetupPlugin
         app
             .init_resource::<Cli>()
             .add_systems(Startup, (cli_system.map(Result::unwrap), init_system))
-            .add_systems(Update, wait_for_assets.in_set(SetupSystems))
+            .add_systems(Update, wait_for_assets.after(build_classes_from_manifest).in_set(SetupSystems))
             ;
     }
 }
@@ -35,7 +37,15 @@ pub enum Cli
     SinglePlayer,
     Server {
         #[arg(short, long, default_value_t = PORT)]
-        port: u16
+        port: u16,
+
+        /// Whether players fight together, can hurt each other, or fight each other with no enemies
+        #[arg(short, long, value_enum, default_value_t = MatchMode::Cooperative)]
+        mode: MatchMode,
+
+        /// Seeds the upgrades offered to players, so a match's upgrade offers can be reproduced
+        #[arg(long)]
+        upgrade_seed: Option<u64>,
     },
     Client {
         #[arg(short, long, default_value_t = Ipv4Addr::LOCALHOST.into())]
@@ -53,6 +63,18 @@ impl Default for Cli
     }
 }
 
+/// Run condition for systems that set up things only clients (and single player games) use, such as their settings files
+pub fn is_client_instance(cli: Res<Cli>) -> bool
+{
+    !matches!(*cli, Cli::Server { .. })
+}
+
+/// Run condition for systems that set up things only servers (and single player games) use, such as saved player progress
+pub fn is_server_instance(cli: Res<Cli>) -> bool
+{
+    !matches!(*cli, Cli::Client { .. })
+}
+
 fn wait_for_assets(
     mut next_state: ResMut<NextState<GameState>>,
     asset_server: Res<AssetServer>,
@@ -73,11 +95,18 @@ fn cli_system(
 ) -> Result<(), Box<dyn Error>> {
     match *cli {
         Cli::SinglePlayer => {
-            let ent = commands.spawn(PlayerServerBundle::new(ClientId::SERVER, Vec2::ZERO, Color::GREEN)).id();
+            let ent = commands.spawn(PlayerServerBundle::new(ClientId::SERVER, PlayerProfile::HOST, Vec2::ZERO, Color::GREEN, PLAYER_DEFAULT_CLASS)).id();
             commands.insert_resource(LocalPlayerId{ is_host: true, id: ClientId::SERVER.get(), entity: ent });
+            commands.insert_resource(PredictionMode::Host);
         }
-        Cli::Server { port } => {
-            info!("Starting a server on port {port}");
+        Cli::Server { port, mode, upgrade_seed } => {
+            info!("Starting a {} server on port {port}", mode.name());
+            commands.insert_resource(MatchRules { mode });
+            if let Some(seed) = upgrade_seed
+            {
+                info!("Using upgrade seed {seed}");
+                commands.insert_resource(UpgradeSeed(seed));
+            }
             let server_channels_config = network_channels.get_server_configs();
             let client_channels_config = network_channels.get_client_configs();
 
@@ -102,6 +131,7 @@ fn cli_system(
             commands.insert_resource(server);
             commands.insert_resource(transport);
             commands.insert_resource(LocalPlayerId{ is_host: true, id: ClientId::SERVER.get(), entity: Entity::PLACEHOLDER });
+            commands.insert_resource(PredictionMode::ListenServer);
 
             commands.spawn(TextBundle::from_section(
                 "Server",
@@ -111,7 +141,7 @@ fn cli_system(
                     ..default()
                 },
             ));
-            commands.spawn(PlayerServerBundle::new(ClientId::SERVER, Vec2::ZERO, Color::GREEN));
+            commands.spawn(PlayerServerBundle::new(ClientId::SERVER, PlayerProfile::HOST, Vec2::ZERO, Color::GREEN, PLAYER_DEFAULT_CLASS));
         }
         Cli::Client { port, ip } => {
             info!("Starting a client connecting to: {ip:?}:{port}");
@@ -127,6 +157,7 @@ fn cli_system(
 
             let current_time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
             let client_id = current_time.as_millis() as u64;
+            let profile = PlayerProfile::load_or_create(PLAYER_PROFILE_PATH);
             info!("Getting server address...");
             let server_addr = SocketAddr::new(ip, port);
             info!("Binding server address...");
@@ -135,7 +166,7 @@ fn cli_system(
                 client_id,
                 protocol_id: PROTOCOL_ID,
                 server_addr,
-                user_data: None,
+                user_data: Some(profile.to_user_data()),
             };
             info!("Creating transport...");
             let transport = NetcodeClientTransport::new(current_time, authentication, socket)?;
@@ -143,6 +174,7 @@ fn cli_system(
             commands.insert_resource(client);
             commands.insert_resource(transport);
             commands.insert_resource(LocalPlayerId{ is_host: false, id: client_id, entity: Entity::PLACEHOLDER });
+            commands.insert_resource(PredictionMode::RemoteClient);
 
             commands.spawn(TextBundle::from_section(
                 format!("Client: {client_id:?}"),
@@ -178,7 +210,11 @@ fn init_system(mut commands: Commands)
                 sections: vec![
                     TextSection::new("No enemies", txt_style.clone()), 
                     TextSection::new("\n", txt_style.clone()),
-                    TextSection::new("You are dead", txt_style.clone())
+                    TextSection::new("You are dead", txt_style.clone()),
+                    TextSection::new("\n", txt_style.clone()),
+                    TextSection::new("", txt_style.clone()),
+                    TextSection::new("\n", txt_style.clone()),
+                    TextSection::new("", txt_style.clone()),
                     ], 
                 linebreak_behavior: bevy::text::BreakLineOn::WordBoundary,
                 justify: JustifyText::Left,
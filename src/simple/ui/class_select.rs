@@ -5,7 +5,7 @@ use crate::simple::{
     gameplay::classes::{ClassType, Classes},
     state::{GameState, ServerStateEvent},
     data::{ClassBaseData, Fonts},
-    player::GeneralClientEvents,
+    player::{GeneralClientEvents, LobbyStatus, LocalPlayer, LocalPlayerId, Player, everyone_ready, roster_line},
     consts::{CLIENT_STR, SERVER_STR},
 };
 
@@ -23,6 +23,18 @@ pub struct ClassSelectUI
 #[derive(Component)]
 pub struct GoInGameButtonTag;
 
+/// Tag component for the button that toggles whether the local player is ready
+#[derive(Component)]
+pub struct ReadyButtonTag;
+
+/// Tag component for the text listing every player in the lobby
+#[derive(Component)]
+pub struct LobbyRosterText;
+
+/// Tag component for the text on the ready button
+#[derive(Component)]
+pub struct ReadyButtonText;
+
 pub fn setup_class_select_ui(
     mut commands: Commands,
     classes: Res<Classes>,
@@ -150,18 +162,19 @@ pub fn setup_class_select_ui(
             },
             Name::new("Class Select Lower Panel")
         )).with_children(|panel_builder| {
-            for (class_type, class_val) in &classes.classes
+            for class_type in classes.class_types()
             {
+                let class_val = &classes.classes[&class_type];
                 let class_data = class_datas.get(&class_val.base_data).expect("did not find class data in class select ui");
                 panel_builder.spawn((
                     NodeBundle {
                         style: class_node_style.clone(),
                         border_color: BorderColor(Color::WHITE),
-                        background_color: BackgroundColor(Color::GRAY),
+                        background_color: BackgroundColor(class_val.ui.color),
                         ..default()
                     },
                     Interaction::None,
-                    ClassSelectUI { class: *class_type },
+                    ClassSelectUI { class: class_type.clone() },
                     Name::new(format!("Class '{class_type}' node"))
                 )).with_children(|class_node| {
                     class_node.spawn((
@@ -199,6 +212,37 @@ pub fn setup_class_select_ui(
             },
             Name::new("Class Select Node")
         )).with_children(|class_select_node| {
+            class_select_node.spawn((
+                TextBundle {
+                    text: Text::from_section("", class_description_text_style.clone()),
+                    ..default()
+                },
+                LobbyRosterText,
+                Name::new("Lobby Roster Text")
+            ));
+            class_select_node.spawn((
+                ButtonBundle {
+                    style: Style {
+                        display: Display::Flex,
+                        border: UiRect::all(Val::Px(15.0)),
+                        margin: UiRect::vertical(Val::Px(15.0)),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::YELLOW),
+                    ..default()
+                },
+                ReadyButtonTag,
+                Name::new("Ready button")
+            )).with_children(|button| {
+                button.spawn((
+                    TextBundle {
+                        text: Text::from_section("Ready", go_in_game_text_style.clone()),
+                        ..default()
+                    },
+                    ReadyButtonText,
+                    Name::new("Ready button text")
+                ));
+            });
             class_select_node.spawn((
                 ButtonBundle {
                     style: Style {
@@ -215,7 +259,7 @@ pub fn setup_class_select_ui(
             )).with_children(|button| {
                 button.spawn((
                     TextBundle {
-                        text: Text::from_section("Go In Game (only works on host)", go_in_game_text_style.clone()),
+                        text: Text::from_section("Start Game (host only, once everyone is ready)", go_in_game_text_style.clone()),
                         ..default()
                     },
                     Name::new("Go in game button text")
@@ -237,12 +281,61 @@ pub fn handle_class_select_ui(
         }
 
         info!("{CLIENT_STR} Class {} clicked", class_s.class);
-        event_writer.send(GeneralClientEvents::ChangeClass(class_s.class));
+        event_writer.send(GeneralClientEvents::ChangeClass(class_s.class.clone()));
+    }
+}
+
+pub fn handle_ready_button(
+    interacted_entities: Query<&Interaction, (With<ReadyButtonTag>, Changed<Interaction>)>,
+    local_status: Query<&LobbyStatus, With<LocalPlayer>>,
+    mut event_writer: EventWriter<GeneralClientEvents>,
+) {
+    for interaction in &interacted_entities
+    {
+        if interaction != &Interaction::Pressed
+        {
+            continue;
+        }
+        let Ok(status) = local_status.get_single() else { warn!("{CLIENT_STR} Could not find local player's lobby status"); continue; };
+
+        info!("{CLIENT_STR} Setting ready to {}", !status.ready);
+        event_writer.send(GeneralClientEvents::SetReady(!status.ready));
+    }
+}
+
+/// Updates the lobby roster, the ready button and highlights the local player's chosen class
+pub fn update_lobby_ui(
+    players: Query<(&Player, &LobbyStatus)>,
+    local_player: Res<LocalPlayerId>,
+    mut roster_texts: Query<&mut Text, (With<LobbyRosterText>, Without<ReadyButtonText>)>,
+    mut ready_texts: Query<&mut Text, (With<ReadyButtonText>, Without<LobbyRosterText>)>,
+    mut class_nodes: Query<(&ClassSelectUI, &mut BorderColor)>,
+) {
+    let mut roster: Vec<_> = players.iter().collect();
+    roster.sort_by_key(|(player, _)| player.0.get());
+    let roster_text = roster.iter()
+        .map(|(player, status)| roster_line(player, status, player.0.get() == local_player.id))
+        .collect::<Vec<String>>()
+        .join("\n");
+    for mut text in &mut roster_texts
+    {
+        text.sections[0].value = format!("Players:\n{roster_text}");
+    }
+
+    let Some((_, local_status)) = roster.iter().find(|(player, _)| player.0.get() == local_player.id) else { return; };
+    for mut text in &mut ready_texts
+    {
+        text.sections[0].value = if local_status.ready { "Not Ready".into() } else { "Ready".into() };
+    }
+    for (class_node, mut border) in &mut class_nodes
+    {
+        border.0 = if class_node.class == local_status.class { Color::YELLOW } else { Color::WHITE };
     }
 }
 
 pub fn s_handle_go_in_game_ui(
     interacted_entities: Query<&Interaction, (With<GoInGameButtonTag>, Changed<Interaction>)>,
+    players: Query<&LobbyStatus, With<Player>>,
     mut event_writer: EventWriter<ToClients<ServerStateEvent>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
@@ -252,6 +345,12 @@ pub fn s_handle_go_in_game_ui(
         {
             continue;
         }
+        if !everyone_ready(&players)
+        {
+            let waiting = players.iter().filter(|status| !status.ready).count();
+            info!("{SERVER_STR} Not going in game, waiting on {waiting} player(s) to ready up");
+            continue;
+        }
 
         info!("{SERVER_STR} Going in game...");
         event_writer.send(ToClients { mode: bevy_replicon::network_event::server_event::SendMode::Broadcast, event: ServerStateEvent::GoInGame });
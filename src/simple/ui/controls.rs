@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+
+use crate::simple::{
+    player::{ActionBindings, ActionInput, ActionTrigger, SimpleAction, first_just_pressed},
+    data::Fonts,
+    consts::{ACTION_BINDINGS_PATH, CLIENT_STR},
+};
+
+/// UI Entity at the root of the controls (key rebinding) menu
+#[derive(Component)]
+pub struct ControlsMenuTag;
+
+/// UI Button that starts rebinding an action when clicked
+#[derive(Component)]
+pub struct BindingButton
+{
+    pub action: SimpleAction,
+}
+
+/// UI Text showing the current binding(s) of an action
+#[derive(Component)]
+pub struct BindingText
+{
+    pub action: SimpleAction,
+}
+
+/// The action currently waiting for a new input to be bound to it, if any
+#[derive(Resource, Default)]
+pub struct RebindingAction(pub Option<SimpleAction>);
+
+fn binding_description(triggers: &[ActionTrigger]) -> String
+{
+    if triggers.is_empty()
+    {
+        return "Unbound".into();
+    }
+    triggers.iter().map(|trigger| trigger.to_string()).collect::<Vec<String>>().join(" / ")
+}
+
+fn spawn_controls_menu(
+    commands: &mut Commands,
+    fonts: &Fonts,
+    input: &ActionInput,
+) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(5.0),
+                padding: UiRect::all(Val::Px(15.0)),
+                align_self: AlignSelf::Center,
+                justify_self: JustifySelf::Center,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.2, 0.85)),
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+        ControlsMenuTag,
+        Name::new("Controls Menu Root")
+    )).with_children(|menu| {
+        menu.spawn((
+            TextBundle {
+                text: Text::from_section("Controls (click a binding, then press a key, mouse or gamepad button, Escape cancels)", TextStyle { font: fonts.thick_font.clone(), font_size: 25.0, color: Color::WHITE }),
+                ..default()
+            },
+            Name::new("Controls Menu Title")
+        ));
+
+        for action in SimpleAction::all()
+        {
+            menu.spawn((
+                NodeBundle {
+                    style: Style {
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Row,
+                        justify_content: JustifyContent::SpaceBetween,
+                        column_gap: Val::Px(25.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Name::new(format!("{} Binding Row", action.name()))
+            )).with_children(|row| {
+                row.spawn(TextBundle {
+                    text: Text::from_section(action.name(), TextStyle { font: fonts.thick_font.clone(), font_size: 20.0, color: Color::WHITE }),
+                    ..default()
+                });
+                row.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            display: Display::Flex,
+                            padding: UiRect::horizontal(Val::Px(5.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    BindingButton { action },
+                )).with_children(|button| {
+                    button.spawn((
+                        TextBundle {
+                            text: Text::from_section(binding_description(input.bindings.triggers(action)), TextStyle { font: fonts.thick_font.clone(), font_size: 20.0, color: Color::BLACK }),
+                            ..default()
+                        },
+                        BindingText { action },
+                    ));
+                });
+            });
+        }
+    });
+}
+
+/// Opens and closes the controls menu
+pub fn c_toggle_controls_menu(
+    mut commands: Commands,
+    input: ActionInput,
+    fonts: Res<Fonts>,
+    rebinding: Res<RebindingAction>,
+    menus: Query<Entity, With<ControlsMenuTag>>,
+) {
+    if rebinding.0.is_some() || !input.just_pressed(SimpleAction::ToggleControls)
+    {
+        return;
+    }
+    if menus.is_empty()
+    {
+        spawn_controls_menu(&mut commands, &fonts, &input);
+        return;
+    }
+    for menu in &menus
+    {
+        commands.entity(menu).despawn_recursive();
+    }
+}
+
+pub fn c_handle_binding_buttons(
+    buttons: Query<(&Interaction, &BindingButton), Changed<Interaction>>,
+    mut rebinding: ResMut<RebindingAction>,
+) {
+    for (interaction, button) in &buttons
+    {
+        if interaction != &Interaction::Pressed
+        {
+            continue;
+        }
+        info!("{CLIENT_STR} Waiting for a new binding for '{}'", button.action.name());
+        rebinding.0 = Some(button.action);
+    }
+}
+
+/// Binds the next key or mouse button pressed to the action being rebound, and saves the bindings
+/// This must run before c_handle_binding_buttons so the click that started rebinding is not captured
+pub fn c_capture_rebind(
+    mut bindings: ResMut<ActionBindings>,
+    mut rebinding: ResMut<RebindingAction>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    let Some(action) = rebinding.0 else { return; };
+    let Some(trigger) = first_just_pressed(&keys, &mouse, &gamepad_buttons) else { return; };
+    rebinding.0 = None;
+    if trigger == ActionTrigger::Key(KeyCode::Escape)
+    {
+        return;
+    }
+
+    info!("{CLIENT_STR} Binding '{}' to {trigger}", action.name());
+    bindings.rebind(action, trigger);
+    if let Err(e) = bindings.save(ACTION_BINDINGS_PATH)
+    {
+        warn!("{CLIENT_STR} Could not save action bindings to '{ACTION_BINDINGS_PATH}': {e}");
+    }
+}
+
+pub fn c_update_binding_texts(
+    bindings: Res<ActionBindings>,
+    rebinding: Res<RebindingAction>,
+    mut texts: Query<(&mut Text, &BindingText)>,
+) {
+    if !bindings.is_changed() && !rebinding.is_changed()
+    {
+        return;
+    }
+    for (mut text, binding) in &mut texts
+    {
+        text.sections[0].value = match rebinding.0 == Some(binding.action)
+        {
+            true => "Press a key...".into(),
+            false => binding_description(bindings.triggers(binding.action)),
+        };
+    }
+}
+
+pub fn cleanup_controls_menu(
+    mut commands: Commands,
+    menus: Query<Entity, With<ControlsMenuTag>>,
+    mut rebinding: ResMut<RebindingAction>,
+) {
+    rebinding.0 = None;
+    for menu in &menus
+    {
+        commands.entity(menu).despawn_recursive();
+    }
+}
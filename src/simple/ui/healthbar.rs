@@ -28,6 +28,26 @@ pub struct HealthBarEntity
     pub parent: Entity
 }
 
+// A tag component for the sprite entity drawn over the health bar, showing the actor's shield
+// Do not add yourself, used for implementing the HealthBar tag component
+#[derive(Component)]
+pub struct ShieldBarEntity
+{
+    pub parent: Entity
+}
+
+const SHIELD_BAR_COLOR: Color = Color::rgba(0.6, 0.85, 1.0, 0.75);
+
+/// How much of a bar should be filled, given a current and max value
+fn bar_fraction(current: f32, max: f32) -> f32
+{
+    if max <= 0.0
+    {
+        return 0.0;
+    }
+    (current / max).clamp(0.0, 1.0)
+}
+
 fn new_health_bar(commands: &mut Commands, health_bar: &HealthBar, parent: Entity) -> Entity
 {
     let background_entity = commands.spawn(SpatialBundle {
@@ -40,6 +60,12 @@ fn new_health_bar(commands: &mut Commands, health_bar: &HealthBar, parent: Entit
             transform: Transform::from_translation(Vec3::new(-12.5, 0.0, 1.0)),
             ..default()
         }, HealthBarEntity { parent }));
+        // Shields are measured against max health, so a full width shield absorbs as much as a full health bar
+        c.spawn((SpriteBundle{
+            sprite: Sprite { color: SHIELD_BAR_COLOR, custom_size: Some(Vec2::new(0.0, 5.0)), anchor: Anchor::CenterLeft, ..default() },
+            transform: Transform::from_translation(Vec3::new(-12.5, 0.0, 2.0)),
+            ..default()
+        }, ShieldBarEntity { parent }));
     }).id();
     background_entity
 }
@@ -82,3 +108,16 @@ pub fn c_update_healthbars(
         sprite.custom_size = Some(Vec2::new(25.0 * percent_health, 9.0));
     }
 }
+
+pub fn c_update_shieldbars(
+    parent_actors: Query<&ActorContext, With<HealthBar>>,
+    mut shieldbars: Query<(&mut Sprite, &ShieldBarEntity), Without<HealthBar>>,
+) {
+    for (mut sprite, shield_bar) in &mut shieldbars
+    {
+        let Ok(parent_actor) = parent_actors.get(shield_bar.parent) else { continue; };
+        let shield = parent_actor.get_stat(&Stat::Shield).unwrap_or(0.0);
+        let max_health = parent_actor.get_stat(&Stat::MaxHealth).unwrap_or(0.0);
+        sprite.custom_size = Some(Vec2::new(25.0 * bar_fraction(shield, max_health), 5.0));
+    }
+}
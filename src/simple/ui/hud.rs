@@ -1,15 +1,19 @@
 use bevy::prelude::*;
 
-use crate::simple::{gameplay::{Enemy, actor::{ActorContext, Stat}}, player::LocalPlayer};
+use crate::simple::{gameplay::{Enemy, MatchMode, Scoreboard, actor::{ActorContext, ActorResources, Stat}}, player::LocalPlayer};
 
 #[derive(Component)]
 pub struct InfoText;
 
 
+/// Type alias for the local player's actor and resources, shown in the info text
+type LocalPlayerStats<'w, 's> = Query<'w, 's, (&'static ActorContext, Option<&'static ActorResources>), (Without<Enemy>, Without<InfoText>, With<LocalPlayer>)>;
+
 pub fn c_update_info_text(
     enemies: Query<(), With<Enemy>>,
-    local_player_health: Query<&ActorContext, (Without<Enemy>, Without<InfoText>, With<LocalPlayer>)>,
+    local_player_health: LocalPlayerStats,
     mut text: Query<&mut Text, (Without<Enemy>, With<InfoText>)>,
+    scoreboard: Res<Scoreboard>,
 ) {
     let Ok(mut text) = text.get_single_mut() else { return; };
     let enemy_count = enemies.iter().count();
@@ -21,8 +25,9 @@ pub fn c_update_info_text(
     {
         text.sections[0].value = "No enemies".into();
     }
-    if let Ok(local_player) = local_player_health.get_single()
+    if let Ok((local_player, resources)) = local_player_health.get_single()
     {
+        text.sections[6].value = resources.map_or(String::new(), |resources| resources.describe());
         if local_player.stats.contains_key(&Stat::Health)
         {
             let extra = match local_player.stats.contains_key(&Stat::MaxHealth)
@@ -30,7 +35,12 @@ pub fn c_update_info_text(
                 true => format!("/{}", local_player.stats[&Stat::MaxHealth]),
                 false => "".into()
             };
-            text.sections[2].value = format!("You have {}{} health", local_player.stats[&Stat::Health], extra);
+            let shield = match local_player.get_stat(&Stat::Shield)
+            {
+                Some(shield) if shield > 0.0 => format!(" and {shield:.0} shield"),
+                _ => "".into()
+            };
+            text.sections[2].value = format!("You have {}{} health{}", local_player.stats[&Stat::Health], extra, shield);
         }
         else
         {
@@ -40,5 +50,20 @@ pub fn c_update_info_text(
     else
     {
         text.sections[2].value = format!("You are dead.");
+        text.sections[6].value = "".into();
+    }
+
+    if scoreboard.mode == MatchMode::Cooperative || scoreboard.scores.is_empty()
+    {
+        text.sections[4].value = "".into();
+    }
+    else
+    {
+        let mut scores: Vec<_> = scoreboard.scores.iter().collect();
+        scores.sort_by(|(_, a), (_, b)| b.kills.cmp(&a.kills).then(a.deaths.cmp(&b.deaths)));
+        text.sections[4].value = format!("{} scores:\n{}", scoreboard.mode.name(), scores.iter()
+            .map(|(id, score)| format!("Player {id}: {} kills / {} deaths", score.kills, score.deaths))
+            .collect::<Vec<String>>()
+            .join("\n"));
     }
 }
@@ -23,6 +23,10 @@ pub struct NextWaveButtonTag;
 #[derive(Component)]
 pub struct UpgradeContainerTag;
 
+/// UI Entity that contains the local player's talent tree
+#[derive(Component)]
+pub struct TalentContainerTag;
+
 /// UI Entity at the root of the Pause Menu
 #[derive(Component)]
 pub struct PauseMenuTag;
@@ -166,6 +170,22 @@ pub fn setup_uis(
                 Name::new("Upgrades UI Container")
             ));
 
+            break_root.spawn((
+                NodeBundle {
+                    style: Style {
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(5.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                TalentContainerTag,
+                Name::new("Talents UI Container")
+            ));
+
             break_root.spawn((
                 NodeBundle {
                     style: Style {
@@ -1,7 +1,9 @@
 mod class_select;
+mod controls;
 mod healthbar;
 mod hud;
 mod in_game;
+mod talents;
 mod upgrade_select;
 
 use bevy_replicon::core::common_conditions::has_authority;
@@ -21,7 +23,11 @@ impl Plugin for UIPlugin
         app
             // Class select v
             .add_systems(OnEnter(state::GameState::ChoosingClass), class_select::setup_class_select_ui)
-            .add_systems(Update, (class_select::handle_class_select_ui,).in_set(state::ChoosingClassSystems))
+            .add_systems(Update, (
+                class_select::handle_class_select_ui,
+                class_select::handle_ready_button,
+                class_select::update_lobby_ui,
+            ).in_set(state::HostAndClientSystems).in_set(state::ChoosingClassSystems))
             .add_systems(OnExit(state::GameState::ChoosingClass), class_select::teardown_class_select_ui)
             .add_systems(OnEnter(state::GameState::InGame), in_game::setup_uis.run_if(host_or_client_cond.clone()))
             .add_systems(OnExit(state::GameState::InGame), in_game::cleanup_uis.run_if(host_or_client_cond.clone()))
@@ -29,6 +35,15 @@ impl Plugin for UIPlugin
             .add_systems(OnExit(state::InGameState::Paused), in_game::on_resume.run_if(host_or_client_cond.clone()))
             .add_systems(OnEnter(state::InGameState::Break), in_game::on_enter_upgrade_select.run_if(host_or_client_cond.clone()))
             .add_systems(OnTransition { from: state::InGameState::Break, to: state::InGameState::Fighting }, in_game::on_upgrade_select_to_fighting.run_if(host_or_client_cond.clone()))
+            // Controls menu v
+            .init_resource::<controls::RebindingAction>()
+            .add_systems(Update, (
+                controls::c_capture_rebind,
+                controls::c_handle_binding_buttons,
+                controls::c_toggle_controls_menu,
+                controls::c_update_binding_texts,
+            ).chain().run_if(in_state(state::GameState::InGame)).run_if(host_or_client_cond.clone()))
+            .add_systems(OnExit(state::GameState::InGame), controls::cleanup_controls_menu)
             .add_systems(FixedUpdate, (in_game::handle_resume_button, in_game::s_handle_next_wave_button).run_if(has_authority))
 
             .add_systems(FixedUpdate, (
@@ -38,8 +53,12 @@ impl Plugin for UIPlugin
                 hud::c_update_info_text,
                 healthbar::c_add_healthbars,
                 healthbar::c_update_healthbars,
+                healthbar::c_update_shieldbars,
                 upgrade_select::c_create_upgrade_ui,
                 upgrade_select::c_handle_upgrade_clicked,
+                upgrade_select::c_handle_reroll_clicked,
+                talents::c_update_talent_ui,
+                talents::c_handle_talent_clicked,
             ).in_set(state::HostAndClientSystems))
             ;
     }
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+
+use crate::simple::{
+    gameplay::{
+        classes::{ActorClass, Classes},
+        ChosenTalent, ClassTalents, PlayerTalents, TalentNode, can_unlock,
+    },
+    player::LocalPlayer,
+    data::{Fonts, TalentTree},
+    consts::CLIENT_STR,
+};
+
+use super::in_game::TalentContainerTag;
+
+
+/// UI Button that unlocks a talent when clicked
+#[derive(Component)]
+pub struct TalentButton
+{
+    pub talent: String,
+}
+
+const UNLOCKED_COLOR: Color = Color::rgb(0.2, 0.45, 0.2);
+const AVAILABLE_COLOR: Color = Color::DARK_GRAY;
+const LOCKED_COLOR: Color = Color::rgba(0.2, 0.2, 0.2, 0.6);
+
+fn talent_text(node: &TalentNode) -> String
+{
+    let mut text = format!("{} ({} pt) - {}", node.name, node.cost, node.description);
+    if !node.prerequisites.is_empty()
+    {
+        text.push_str(&format!(" [Requires: {}]", node.prerequisites.join(", ")));
+    }
+    text
+}
+
+fn talent_color(tree: &TalentTree, talents: &ClassTalents, node: &TalentNode) -> Color
+{
+    if talents.is_unlocked(&node.id)
+    {
+        return UNLOCKED_COLOR;
+    }
+    match can_unlock(tree, talents, &node.id)
+    {
+        Ok(_) => AVAILABLE_COLOR,
+        Err(_) => LOCKED_COLOR,
+    }
+}
+
+/// Rebuilds the talent tree in the break screen whenever the local player's class or talents change
+pub fn c_update_talent_ui(
+    mut commands: Commands,
+    fonts: Res<Fonts>,
+    classes: Res<Classes>,
+    trees: Res<Assets<TalentTree>>,
+    player: Query<(Ref<ActorClass>, Ref<PlayerTalents>), With<LocalPlayer>>,
+    containers: Query<(Entity, Ref<TalentContainerTag>)>,
+) {
+    let Ok((class, talents)) = player.get_single() else { return; };
+    let Ok((container, container_tag)) = containers.get_single() else { return; };
+    if !class.is_changed() && !talents.is_changed() && !container_tag.is_added()
+    {
+        return;
+    }
+
+    debug!("{CLIENT_STR} Rebuilding talent UI");
+    commands.entity(container).despawn_descendants();
+    let Some(tree) = classes.classes.get(class.get_class()).and_then(|c| c.talents.as_ref()).and_then(|handle| trees.get(handle)) else { return; };
+    let class_talents = talents.class(class.get_class()).cloned().unwrap_or_default();
+
+    commands.entity(container).with_children(|container| {
+        container.spawn((
+            TextBundle {
+                text: Text::from_section(format!("Talents - {} point(s) available", class_talents.points), TextStyle { font: fonts.thick_font.clone(), font_size: 25.0, color: Color::WHITE }),
+                ..default()
+            },
+            Name::new("Talent Points Text")
+        ));
+
+        for node in &tree.nodes
+        {
+            container.spawn((
+                ButtonBundle {
+                    style: Style {
+                        display: Display::Flex,
+                        padding: UiRect::all(Val::Px(5.0)),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(talent_color(tree, &class_talents, node)),
+                    ..default()
+                },
+                TalentButton { talent: node.id.clone() },
+                Name::new(format!("'{}' Talent Button", node.name))
+            )).with_children(|button| {
+                button.spawn(TextBundle {
+                    text: Text::from_section(talent_text(node), TextStyle { font: fonts.upgrade_font.clone(), font_size: 15.0, color: Color::WHITE }),
+                    ..default()
+                });
+            });
+        }
+    });
+}
+
+/// Asks the server to unlock clicked talents, the server checks the player can actually unlock them
+pub fn c_handle_talent_clicked(
+    buttons: Query<(&Interaction, &TalentButton), Changed<Interaction>>,
+    mut chosen_talents: EventWriter<ChosenTalent>,
+) {
+    for (interaction, button) in &buttons
+    {
+        if interaction != &Interaction::Pressed
+        {
+            continue;
+        }
+
+        info!("{CLIENT_STR} Sending Talent chosen event to server");
+        chosen_talents.send(ChosenTalent { talent: button.talent.clone() });
+    }
+}
@@ -4,7 +4,7 @@ use crate::simple::{consts::CLIENT_STR, data::Fonts};
 
 use super::in_game::UpgradeContainerTag;
 
-use crate::simple::gameplay::{ChosenUpgrade, GeneratedAvailableUpgrades, Upgrade};
+use crate::simple::gameplay::{ChosenUpgrade, GeneratedAvailableUpgrades, RerollUpgrades, Upgrade};
 
 
 #[derive(Component)]
@@ -13,6 +13,10 @@ pub struct UpgradeUI
     pub upgrade: Upgrade,
 }
 
+/// UI Button that asks the server for different upgrades when clicked
+#[derive(Component)]
+pub struct RerollButton;
+
 fn create_upgrade_ui_entity(commands: &mut Commands, upgrade: Upgrade, font_handle: Handle<Font>) -> Entity
 {
     let background_entity = commands.spawn((
@@ -43,12 +47,19 @@ fn create_upgrade_ui_entity(commands: &mut Commands, upgrade: Upgrade, font_hand
     )).with_children(|background_builder| {
         background_builder.spawn((
             TextBundle {
-                text: Text::from_section(&upgrade.name, TextStyle { font: font_handle.clone(), font_size: 20.0, color: Color::rgb(1.0, 0.2, 0.2) }),
+                text: Text::from_section(&upgrade.name, TextStyle { font: font_handle.clone(), font_size: 20.0, color: upgrade.rarity.color() }),
                 style: Style { display: Display::Flex, padding: UiRect::all(Val::Px(2.0)), ..default() },
                 ..default()
             },
             Name::new(format!("'{}' Upgrade Name", &upgrade.name)),
         ));
+        background_builder.spawn((
+            TextBundle {
+                text: Text::from_section(upgrade.rarity.name(), TextStyle { font: font_handle.clone(), font_size: 12.0, color: upgrade.rarity.color() }),
+                ..default()
+            },
+            Name::new(format!("'{}' Upgrade Rarity", upgrade.name)),
+        ));
         background_builder.spawn((
             ImageBundle {
                 image: UiImage::default(),
@@ -59,7 +70,7 @@ fn create_upgrade_ui_entity(commands: &mut Commands, upgrade: Upgrade, font_hand
         ));
         background_builder.spawn((
             TextBundle {
-                text: Text::from_section(upgrade.description, TextStyle { font: font_handle.clone(), font_size: 12.0, color: Color::WHITE }),
+                text: Text::from_section(upgrade.description(), TextStyle { font: font_handle.clone(), font_size: 12.0, color: Color::WHITE }),
                 style: Style { 
                     display: Display::Flex,
                     border: UiRect::all(Val::Px(3.0)),
@@ -76,14 +87,37 @@ fn create_upgrade_ui_entity(commands: &mut Commands, upgrade: Upgrade, font_hand
     background_entity
 }
 
+fn create_reroll_button(commands: &mut Commands, rerolls: u32, font_handle: Handle<Font>) -> Entity
+{
+    commands.spawn((
+        ButtonBundle {
+            style: Style { display: Display::Flex, padding: UiRect::all(Val::Px(5.0)), justify_content: JustifyContent::Center, align_items: AlignItems::Center, ..default() },
+            ..default()
+        },
+        RerollButton,
+        Name::new("Reroll Upgrades Button"),
+    )).with_children(|button| {
+        button.spawn((
+            TextBundle {
+                text: Text::from_section(format!("Reroll ({rerolls})"), TextStyle { font: font_handle, font_size: 20.0, color: Color::BLACK }),
+                ..default()
+            },
+            Name::new("Reroll Upgrades Button Text"),
+        ));
+    }).id()
+}
+
+/// Type alias for the upgrade cards and reroll button currently shown
+type ShownUpgradeUI<'w, 's> = Query<'w, 's, Entity, (Or<(With<UpgradeUI>, With<RerollButton>)>, Without<UpgradeContainerTag>)>;
+
 pub fn c_create_upgrade_ui(  // TODO: rename this function
     mut commands: Commands,
     fonts: Res<Fonts>,
     root_node_query: Query<Entity, (With<UpgradeContainerTag>, Without<UpgradeUI>)>,
-    existing_upgrades: Query<Entity, (With<UpgradeUI>, Without<UpgradeContainerTag>)>,
+    existing_upgrades: ShownUpgradeUI,
     mut upgrade_events: EventReader<GeneratedAvailableUpgrades>,
 ) {
-    for GeneratedAvailableUpgrades { upgrades } in upgrade_events.read()
+    for GeneratedAvailableUpgrades { upgrades, rerolls } in upgrade_events.read()
     {
         info!("{CLIENT_STR} Received available upgrades from server!");
         for existing_upgrade in &existing_upgrades
@@ -98,6 +132,22 @@ pub fn c_create_upgrade_ui(  // TODO: rename this function
             let upgrade_ent = create_upgrade_ui_entity(&mut commands, upgrade.clone(), fonts.upgrade_font.clone());
             commands.entity(root_node).add_child(upgrade_ent);
         }
+        if *rerolls > 0
+        {
+            let reroll_ent = create_reroll_button(&mut commands, *rerolls, fonts.upgrade_font.clone());
+            commands.entity(root_node).add_child(reroll_ent);
+        }
+    }
+}
+
+pub fn c_handle_reroll_clicked(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<RerollButton>)>,
+    mut reroll_events: EventWriter<RerollUpgrades>,
+) {
+    if buttons.iter().any(|interaction| interaction == &Interaction::Pressed)
+    {
+        info!("{CLIENT_STR} Asking the server to reroll upgrades");
+        reroll_events.send(RerollUpgrades);
     }
 }
 
@@ -107,7 +157,7 @@ pub fn c_handle_upgrade_clicked(
     mut commands: Commands,
     mut upgrade_set: ParamSet<(
         Query<(&Interaction, &UpgradeUI), Changed<Interaction>>, // Clicked upgrades
-        Query<Entity, With<UpgradeUI>>, // All upgrades
+        Query<Entity, Or<(With<UpgradeUI>, With<RerollButton>)>>, // All upgrades, and the reroll button
     )>,
     mut chosen_upgrade_events: EventWriter<ChosenUpgrade>,
 ) {
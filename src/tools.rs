@@ -1,30 +1,80 @@
-use std::{fs::File, io::Write};
+use std::{fs::File, io::Write, path::Path};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use serde::de::DeserializeOwned;
 
 use crate::simple::gameplay::{
     actor::effect::*,
-    Upgrade, UpgradeBehaviour
+    upgrade::{validation::{validate_upgrades, ClassAbilityTypes}, Upgrade, UpgradeBehaviour, UpgradeRarity},
 };
-use crate::simple::data::UpgradeCollection;
+use crate::simple::data::{ClassBaseData, ClassManifest, UpgradeCollection};
 
 pub mod simple;
 
 #[derive(Parser)]
 pub struct MyArgs
 {
+    #[command(subcommand)]
+    pub command: Option<ToolCommand>,
     /// Path of file to write serialized data to
     #[arg(short, long, default_value_t = String::from("joe.ron"))]
     pub file: String,
 }
 
+#[derive(Subcommand)]
+pub enum ToolCommand
+{
+    /// Checks that every upgrade can do something for the classes it can be offered to
+    ValidateUpgrades
+    {
+        /// Path of the assets folder, the class manifest and class data are read from here
+        #[arg(short, long, default_value_t = String::from("assets"))]
+        assets: String,
+        /// Path of the upgrade collection, relative to the assets folder
+        #[arg(short, long, default_value_t = String::from("predefined_upgrades.upgrades"))]
+        upgrades: String,
+    },
+}
+
+fn read_ron<T: DeserializeOwned>(path: &Path) -> T
+{
+    let file = File::open(path).unwrap_or_else(|e| panic!("Could not open '{}': {e}", path.display()));
+    ron::de::from_reader(file).unwrap_or_else(|e| panic!("Could not deserialize '{}': {e}", path.display()))
+}
+
+/// Prints every issue with an upgrade collection, returning whether there were any
+fn print_upgrade_issues(assets: &str, upgrades: &str) -> bool
+{
+    let assets = Path::new(assets);
+    let collection: UpgradeCollection = read_ron(&assets.join(upgrades));
+    let manifest: ClassManifest = read_ron(&assets.join("classes.manifest"));
+    let classes: Vec<ClassAbilityTypes> = manifest.classes.iter()
+        .map(|entry| ClassAbilityTypes::new(entry.id.clone(), &read_ron::<ClassBaseData>(&assets.join(&entry.data))))
+        .collect();
+
+    let issues = validate_upgrades(&collection, &classes);
+    for (upgrade, issue) in &issues
+    {
+        println!("Upgrade '{upgrade}' {issue}");
+    }
+    println!("Checked {} upgrades against {} classes, found {} issues", collection.upgrades.len(), classes.len(), issues.len());
+    !issues.is_empty()
+}
+
 fn upgrade_for_behaviour(behaviour: UpgradeBehaviour) -> Upgrade
 {
     Upgrade
     {
         behaviour: behaviour.clone(),
         name: "Upgrade Name".into(),
-        description: behaviour.describe()
+        description: behaviour.describe(),
+        rarity: UpgradeRarity::Common,
+        weight: None,
+        unique: false,
+        tags: Vec::new(),
+        classes: Vec::new(),
+        requires: Vec::new(),
+        excludes: Vec::new(),
     }
 }
 
@@ -34,6 +84,15 @@ fn main()
 
     println!("Tools here, whats the prob?");
 
+    if let Some(ToolCommand::ValidateUpgrades { assets, upgrades }) = &args.command
+    {
+        if print_upgrade_issues(assets, upgrades)
+        {
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let upgrade_collection = UpgradeCollection { upgrades: vec![
         upgrade_for_behaviour(UpgradeBehaviour::AddEffects(vec![
             SerializedEffectTrigger::OnAbilityHit { 
@@ -54,7 +113,7 @@ fn main()
         upgrade_for_behaviour(UpgradeBehaviour::AddEffects(vec![
             SerializedEffectTrigger::OnDamageDone(SerializedDamageViewEffect::EveryXDamageEffect { accumulated_damage: 0.0, damage_threshold: 50.0, which_actor: DamageActor::Instigator, effect: SerializedActorEffect::AffectHealth(10.0) })
         ]))
-    ] };
+    ], ..Default::default() };
 
     let mut f = File::create(&args.file).expect("Could not create output file");
 